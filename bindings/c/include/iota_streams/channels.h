@@ -18,6 +18,22 @@ typedef struct KePks ke_pks_t;
 typedef struct NextMsgIds next_msg_ids_t;
 extern void drop_next_msg_ids(next_msg_ids_t const *);
 
+typedef struct Message message_t;
+extern void drop_message(message_t const *);
+
+typedef struct MessageHeader {
+  uint8_t encoding;
+  uint8_t version;
+  uint8_t content_type;
+  size_t payload_length;
+  uint32_t payload_frame_count;
+  uint64_t seq_num;
+  address_t const *link;
+  uint64_t timestamp;
+} message_header_t;
+
+extern void drop_header(message_header_t);
+
 typedef struct UnwrappedMessage unwrapped_message_t;
 extern void drop_unwrapped_message(unwrapped_message_t const *);
 
@@ -56,6 +72,7 @@ extern void tsp_drop(transport_t *);
 extern transport_t *tsp_client_new_from_url(char const *url);
 extern void tsp_client_set_mwm(transport_t *tsp, uint8_t mwm);
 #endif
+extern message_t const *tsp_recv_message(transport_t *tsp, address_t const *address);
 
 ////////////
 /// Author
@@ -144,6 +161,9 @@ extern char const *get_address_id_str(address_t const *address);
 
 extern char const *public_key_to_string(public_key_t *pk);
 
+// Header inspection, no keys needed; link is null if the header can't be parsed
+extern message_header_t inspect_message(message_t const *message);
+
 extern packet_payloads_t get_payload(unwrapped_message_t const *message);
 extern packet_payloads_t get_indexed_payload(unwrapped_messages_t const *messages, size_t index);
 
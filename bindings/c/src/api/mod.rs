@@ -4,10 +4,13 @@ use iota_streams::{
         psk,
     },
     app::{
-        message::Cursor,
+        message::{
+            inspect,
+            Cursor,
+        },
         transport::tangle::MsgId,
         cstr_core::{CStr, CString},
-        cty::{c_char, size_t, uint8_t},
+        cty::{c_char, size_t, uint8_t, uint32_t, uint64_t},
     },
     app_channels::api::tangle::*,
 };
@@ -17,7 +20,7 @@ use core::ptr::{null, null_mut};
 
 #[no_mangle]
 pub extern "C" fn address_from_string(c_addr: *const c_char) -> *const Address {
-    Address::from_c_str(c_addr)
+    unsafe { Address::from_c_str(c_addr) }
 }
 
 #[no_mangle]
@@ -95,6 +98,23 @@ pub extern "C" fn tsp_client_set_mwm(tsp: *mut TransportWrap, mwm: uint8_t) {
 }
 
 
+#[no_mangle]
+pub extern "C" fn tsp_recv_message(tsp: *mut TransportWrap, link: *const Address) -> *const Message {
+    unsafe {
+        tsp.as_mut().map_or(null(), |tsp| {
+            link.as_ref().map_or(null(), |link| {
+                tsp.recv_message(link).map_or(null(), |msg| Box::into_raw(Box::new(msg)))
+            })
+        })
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn drop_message(msg: *const Message) {
+    unsafe { Box::from_raw(msg as *mut Message); }
+}
+
+
 #[repr(C)]
 pub struct MessageLinks {
     pub msg_link: *const Address,
@@ -299,6 +319,68 @@ pub extern "C" fn get_address_id_str(address: *mut Address) -> *mut c_char {
     }
 }
 
+/// Public header fields of a message, `link` is null if the header can't be parsed.
+#[repr(C)]
+pub struct MessageHeader {
+    pub encoding: uint8_t,
+    pub version: uint8_t,
+    pub content_type: uint8_t,
+    pub payload_length: size_t,
+    pub payload_frame_count: uint32_t,
+    pub seq_num: uint64_t,
+    pub link: *const Address,
+    pub timestamp: uint64_t,
+}
+
+impl Default for MessageHeader {
+    fn default() -> Self {
+        Self {
+            encoding: 0,
+            version: 0,
+            content_type: 0,
+            payload_length: 0,
+            payload_frame_count: 0,
+            seq_num: 0,
+            link: null(),
+            timestamp: 0,
+        }
+    }
+}
+
+impl MessageHeader {
+    pub fn drop(self) {
+        unsafe {
+            if self.link != null() {
+                Box::from_raw(self.link as *mut Address);
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn drop_header(header: MessageHeader) {
+    header.drop()
+}
+
+/// Read message header without any keys, no user state is involved.
+#[no_mangle]
+pub extern "C" fn inspect_message(msg: *const Message) -> MessageHeader {
+    unsafe {
+        msg.as_ref().and_then(|msg| inspect(msg).ok()).map_or(MessageHeader::default(), |info| {
+            MessageHeader {
+                encoding: info.encoding,
+                version: info.version,
+                content_type: info.content_type,
+                payload_length: info.payload_length,
+                payload_frame_count: info.payload_frame_count,
+                seq_num: info.seq_num,
+                link: Box::into_raw(Box::new(Address::new(info.appinst, info.msgid))),
+                timestamp: info.timestamp,
+            }
+        })
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn get_payload(msg: *const UnwrappedMessage) -> PacketPayloads {
     unsafe {
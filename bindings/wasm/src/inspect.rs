@@ -0,0 +1,59 @@
+use core::convert::TryInto as _;
+use wasm_bindgen::prelude::*;
+
+use crate::types::*;
+
+/// Streams imports
+use iota_streams::{
+    app::{
+        message::inspect,
+        transport::{
+            tangle::client::Client,
+            Transport as _,
+        },
+    },
+    app_channels::api::tangle::{
+        Address as ApiAddress,
+        Message as ApiMessage,
+    },
+    core::prelude::{String, ToString},
+};
+
+/// Public header fields of a message.
+#[wasm_bindgen]
+pub struct HeaderInfo {
+    pub encoding: u8,
+    pub version: u8,
+    pub content_type: u8,
+    pub payload_length: usize,
+    pub payload_frame_count: u32,
+    pub seq_num: u64,
+    link: Address,
+    pub timestamp: u64,
+}
+
+#[wasm_bindgen]
+impl HeaderInfo {
+    pub fn get_link(&self) -> Address {
+        self.link.copy()
+    }
+}
+
+/// Fetch a message from the node and read its header, no keys or user are involved.
+#[wasm_bindgen]
+pub async fn inspect_message(node: String, link: Address) -> Result<HeaderInfo> {
+    let mut client = Client::new_from_url(&node);
+    let link: ApiAddress = link.try_into()?;
+    let msg: ApiMessage = to_result(client.recv_message(&link).await)?;
+    let info = to_result(inspect(&msg))?;
+    Ok(HeaderInfo {
+        encoding: info.encoding,
+        version: info.version,
+        content_type: info.content_type,
+        payload_length: info.payload_length,
+        payload_frame_count: info.payload_frame_count,
+        seq_num: info.seq_num,
+        link: Address::from_string(ApiAddress::new(info.appinst, info.msgid).to_string()),
+        timestamp: info.timestamp,
+    })
+}
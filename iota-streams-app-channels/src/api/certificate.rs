@@ -0,0 +1,46 @@
+//! Publisher certificates issued by Author.
+
+use iota_streams_core_edsig::signature::ed25519;
+
+/// Author's statement that `subject` may publish signed packets.
+#[derive(Clone, Default)]
+pub struct Certificate<Rel> {
+    /// Public key of the certified publisher.
+    pub subject: ed25519::PublicKey,
+
+    /// If present, packets must be linked to this message, otherwise to any message.
+    pub branch: Option<Rel>,
+
+    /// Start of the validity window, milliseconds since Unix epoch.
+    pub not_before: u64,
+
+    /// End of the validity window, milliseconds since Unix epoch, `0` if the certificate doesn't expire.
+    pub not_after: u64,
+}
+
+impl<Rel> Certificate<Rel> {
+    /// Certificate revoking `subject`, its validity window is empty.
+    pub fn revocation(subject: ed25519::PublicKey) -> Self {
+        Self {
+            subject,
+            branch: None,
+            not_before: 2,
+            not_after: 1,
+        }
+    }
+
+    /// Whether the validity window is empty, no packet of the subject is accepted then.
+    pub fn is_revoked(&self) -> bool {
+        self.not_after != 0 && self.not_after < self.not_before
+    }
+}
+
+impl<Rel: Eq> Certificate<Rel> {
+    /// Whether a packet linked to `link_to` is covered when it's received at `now`.
+    pub fn allows(&self, link_to: &Rel, now: u64) -> bool {
+        !self.is_revoked()
+            && self.branch.as_ref().is_none_or(|branch| branch == link_to)
+            && self.not_before <= now
+            && (self.not_after == 0 || now <= self.not_after)
+    }
+}
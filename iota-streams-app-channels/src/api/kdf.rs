@@ -0,0 +1,101 @@
+//! Password-based key derivation for user state exports.
+//!
+//! Argon2id is provided by the `argon2-kdf` feature, without it exports with password
+//! fail with `ExportKdfNotSupported`.
+
+#[cfg(feature = "argon2-kdf")]
+use argon2::{
+    Algorithm,
+    Argon2,
+    Params,
+    Version,
+};
+#[cfg(feature = "argon2-kdf")]
+use iota_streams_core::prelude::string::ToString;
+use iota_streams_core::{
+    err,
+    try_or,
+    Errors::*,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_ddml::types::{
+    NBytes,
+    U32,
+};
+
+/// Identifier of Argon2id key derivation function in export header.
+pub const KDF_ARGON2ID: u8 = 1;
+
+/// Identifier of export key encapsulated to the recipient's X25519 public key.
+pub const KDF_X25519: u8 = 2;
+
+/// Identifier of export encrypted with a key managed by the caller.
+pub const KDF_EXTERNAL_KEY: u8 = 3;
+
+/// Largest Argon2id memory size in KiB accepted from an export header, 256 MiB.
+pub const MAX_M_COST: u32 = 256 * 1024;
+
+/// Largest Argon2id number of passes accepted from an export header.
+pub const MAX_T_COST: u32 = 16;
+
+/// Largest Argon2id degree of parallelism accepted from an export header.
+pub const MAX_P_COST: u32 = 16;
+
+/// Argon2id parameters, they are stored in export header and reused on import.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory size in KiB.
+    pub m_cost: u32,
+
+    /// Number of passes over memory.
+    pub t_cost: u32,
+
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Check parameters read from an export header, so that a crafted export can't make
+    /// the key derivation allocate unbounded memory or run unbounded time.
+    pub fn check_limits(&self) -> Result<()> {
+        try_or!(
+            self.m_cost <= MAX_M_COST && self.t_cost <= MAX_T_COST && self.p_cost <= MAX_P_COST,
+            ExportKdfParamsOutOfBounds(self.m_cost, self.t_cost, self.p_cost)
+        )
+    }
+
+    /// Derive export key from password and salt.
+    #[cfg(feature = "argon2-kdf")]
+    pub fn derive_key(&self, pwd: &str, salt: &[u8]) -> Result<NBytes<U32>> {
+        let params = match Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32)) {
+            Ok(params) => params,
+            Err(e) => return err!(ExportKeyDerivationFailure(e.to_string())),
+        };
+        let mut key = NBytes::<U32>::default();
+        match Argon2::new(Algorithm::Argon2id, Version::V0x13, params).hash_password_into(
+            pwd.as_bytes(),
+            salt,
+            key.as_mut_slice(),
+        ) {
+            Ok(()) => Ok(key),
+            Err(e) => err!(ExportKeyDerivationFailure(e.to_string())),
+        }
+    }
+
+    /// Derive export key from password and salt.
+    #[cfg(not(feature = "argon2-kdf"))]
+    pub fn derive_key(&self, _pwd: &str, _salt: &[u8]) -> Result<NBytes<U32>> {
+        err!(ExportKdfNotSupported(KDF_ARGON2ID))
+    }
+}
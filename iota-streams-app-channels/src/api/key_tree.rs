@@ -0,0 +1,315 @@
+//! Logical key hierarchy used for group rekeying.
+//!
+//! Author keeps a sparse binary tree of node keys, every member (Subscriber) is assigned
+//! a leaf and holds the keys on the path from its leaf to the root. The root key is the group key.
+//! Adding or removing a member refreshes only the keys of the leaf ancestors, so a rekey costs
+//! O(log n) encrypted keys instead of one key exchange per member.
+
+use iota_streams_app::message::{
+    ContentSizeof,
+    ContentUnwrap,
+    ContentWrap,
+};
+use iota_streams_core::{
+    err,
+    prelude::{
+        HashMap,
+        HashSet,
+        Vec,
+    },
+    prng,
+    sponge::prp::PRP,
+    Errors::*,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    types::*,
+};
+
+/// Node identifier, root is `1`, children of node `n` are `2n` and `2n+1`.
+pub type NodeId = u32;
+
+/// Node key.
+pub type NodeKey = NBytes<U32>;
+
+/// Root node identifier.
+pub const ROOT_NODE: NodeId = 1;
+
+/// Default depth of the tree, allows for `2^16` members.
+pub const KEY_TREE_DEPTH: u8 = 16;
+
+#[derive(Clone)]
+pub struct KeyTree {
+    /// Depth of the tree, leaves are at level `depth`.
+    pub(crate) depth: u8,
+
+    /// Known node keys: whole tree for Author, own path for Subscriber.
+    pub(crate) keys: HashMap<NodeId, NodeKey>,
+
+    /// Leaves assigned to members [Author].
+    pub(crate) leaves: HashMap<ed25519::PublicKeyWrap, NodeId>,
+
+    /// Members whose leaf keys have not been distributed yet [Author].
+    pub(crate) joins: Vec<ed25519::PublicKey>,
+
+    /// Nodes whose keys have been refreshed but not distributed yet [Author].
+    pub(crate) dirty: HashSet<NodeId>,
+}
+
+impl Default for KeyTree {
+    fn default() -> Self {
+        Self::new(KEY_TREE_DEPTH)
+    }
+}
+
+impl KeyTree {
+    /// Create an empty tree, `depth` is limited to 30.
+    pub fn new(depth: u8) -> Self {
+        Self {
+            depth: core::cmp::min(depth, 30),
+            keys: HashMap::new(),
+            leaves: HashMap::new(),
+            joins: Vec::new(),
+            dirty: HashSet::new(),
+        }
+    }
+
+    /// Group key, if known.
+    pub fn root_key(&self) -> Option<&NodeKey> {
+        self.keys.get(&ROOT_NODE)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&NodeKey> {
+        self.keys.get(&id)
+    }
+
+    /// Leaf assigned to the member.
+    pub fn leaf(&self, pk: &ed25519::PublicKey) -> Option<NodeId> {
+        self.leaves.get(pk.into()).copied()
+    }
+
+    pub fn is_member(&self, pk: &ed25519::PublicKey) -> bool {
+        self.leaves.contains_key(pk.into())
+    }
+
+    /// Whether there are refreshed keys or new members to be distributed with a tree keyload.
+    pub fn has_pending(&self) -> bool {
+        !self.joins.is_empty() || !self.dirty.is_empty()
+    }
+
+    /// Assign a leaf to a new member and refresh keys on its path.
+    pub fn add_member(&mut self, pk: ed25519::PublicKey) -> Result<NodeId> {
+        if let Some(leaf) = self.leaf(&pk) {
+            return Ok(leaf);
+        }
+
+        let first = 1_u32 << self.depth;
+        let occupied: HashSet<NodeId> = self.leaves.values().copied().collect();
+        match (first..first << 1).find(|leaf| !occupied.contains(leaf)) {
+            Some(leaf) => {
+                self.keys.insert(leaf, NBytes::from(prng::random_key()));
+                self.refresh_path(leaf);
+                self.leaves.insert(pk.into(), leaf);
+                self.joins.push(pk);
+                Ok(leaf)
+            }
+            None => err!(KeyTreeFull(first as usize)),
+        }
+    }
+
+    /// Forget member's leaf and refresh keys on its path so that it can't recover new group key.
+    pub fn remove_member(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        match self.leaves.remove(pk.into()) {
+            Some(leaf) => {
+                self.keys.remove(&leaf);
+                self.joins.retain(|p| p != pk);
+                self.refresh_path(leaf);
+                Ok(())
+            }
+            None => err!(KeyTreeMemberNotFound),
+        }
+    }
+
+    /// Refresh group key only.
+    pub fn rekey(&mut self) {
+        if self.keys.contains_key(&ROOT_NODE) {
+            self.keys.insert(ROOT_NODE, NBytes::from(prng::random_key()));
+            self.dirty.insert(ROOT_NODE);
+        }
+    }
+
+    /// Refresh keys of all ancestors of the node, nodes without children are removed.
+    fn refresh_path(&mut self, mut id: NodeId) {
+        while id > ROOT_NODE {
+            id >>= 1;
+            if self.keys.contains_key(&(id << 1)) || self.keys.contains_key(&((id << 1) | 1)) {
+                self.keys.insert(id, NBytes::from(prng::random_key()));
+                self.dirty.insert(id);
+            } else {
+                self.keys.remove(&id);
+                self.dirty.remove(&id);
+            }
+        }
+    }
+
+    /// Refreshed parent keys each encrypted with child key: `(child, child_key, parent_key)`.
+    /// Entries are ordered bottom-up so that a member can recover its path in one pass.
+    pub fn updates(&self) -> Vec<(NodeId, &NodeKey, &NodeKey)> {
+        let mut dirty: Vec<NodeId> = self.dirty.iter().copied().collect();
+        dirty.sort_unstable_by(|a, b| b.cmp(a));
+        let mut updates = Vec::new();
+        for parent in dirty {
+            if let Some(parent_key) = self.keys.get(&parent) {
+                for child in [(parent << 1) | 1, parent << 1].iter() {
+                    if let Some(child_key) = self.keys.get(child) {
+                        updates.push((*child, child_key, parent_key));
+                    }
+                }
+            }
+        }
+        updates
+    }
+
+    /// Refreshed parent keys needed by the members of the given leaves, ordered as in `updates`.
+    /// Refreshed group key is always encrypted with both children of the root, so that any member
+    /// knowing its current path recovers it.
+    pub fn path_updates(&self, leaves: &[NodeId]) -> Vec<(NodeId, &NodeKey, &NodeKey)> {
+        let mut children = self.root_children();
+        children.extend(leaves.iter().flat_map(|leaf| self.path_children(*leaf)));
+        children.sort_unstable_by(|a, b| b.cmp(a));
+        children.dedup();
+        children
+            .into_iter()
+            .filter_map(|child| Some((child, self.keys.get(&child)?, self.keys.get(&(child >> 1))?)))
+            .collect()
+    }
+
+    /// Children of the root with known keys if the group key has been refreshed.
+    pub fn root_children(&self) -> Vec<NodeId> {
+        let mut children = Vec::new();
+        if self.dirty.contains(&ROOT_NODE) {
+            for child in [(ROOT_NODE << 1) | 1, ROOT_NODE << 1].iter() {
+                if self.keys.contains_key(child) {
+                    children.push(*child);
+                }
+            }
+        }
+        children
+    }
+
+    /// Nodes on the path from the leaf whose parents have been refreshed.
+    pub fn path_children(&self, mut id: NodeId) -> Vec<NodeId> {
+        let mut children = Vec::new();
+        while id > ROOT_NODE {
+            if self.dirty.contains(&(id >> 1)) {
+                children.push(id);
+            }
+            id >>= 1;
+        }
+        children
+    }
+
+    /// Forget pending joins and refreshed nodes once they have been distributed.
+    pub fn clear_pending(&mut self) {
+        self.joins.clear();
+        self.dirty.clear();
+    }
+}
+
+impl<F: PRP> ContentSizeof<F> for KeyTree {
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        ctx
+            .absorb(Uint8(self.depth))?
+            .absorb(Size(self.keys.len()))?
+            .repeated(self.keys.iter(), |ctx, (id, key)| {
+                ctx.absorb(Uint32(*id))?.mask(key)
+            })?
+            .absorb(Size(self.leaves.len()))?
+            .repeated(self.leaves.iter(), |ctx, (pk, leaf)| {
+                ctx.absorb(&pk.0)?.absorb(Uint32(*leaf))
+            })?
+            .absorb(Size(self.joins.len()))?
+            .repeated(self.joins.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Size(self.dirty.len()))?
+            .repeated(self.dirty.iter(), |ctx, id| ctx.absorb(Uint32(*id)))?;
+        Ok(ctx)
+    }
+}
+
+impl<F: PRP, Store> ContentWrap<F, Store> for KeyTree {
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        _store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx
+            .absorb(Uint8(self.depth))?
+            .absorb(Size(self.keys.len()))?
+            .repeated(self.keys.iter(), |ctx, (id, key)| {
+                ctx.absorb(Uint32(*id))?.mask(key)
+            })?
+            .absorb(Size(self.leaves.len()))?
+            .repeated(self.leaves.iter(), |ctx, (pk, leaf)| {
+                ctx.absorb(&pk.0)?.absorb(Uint32(*leaf))
+            })?
+            .absorb(Size(self.joins.len()))?
+            .repeated(self.joins.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Size(self.dirty.len()))?
+            .repeated(self.dirty.iter(), |ctx, id| ctx.absorb(Uint32(*id)))?;
+        Ok(ctx)
+    }
+}
+
+impl<F: PRP, Store> ContentUnwrap<F, Store> for KeyTree {
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        _store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut depth = Uint8(0);
+        let mut repeated_keys = Size(0);
+        let mut repeated_leaves = Size(0);
+        let mut repeated_joins = Size(0);
+        let mut repeated_dirty = Size(0);
+        let mut tree = KeyTree::default();
+        ctx
+            .absorb(&mut depth)?
+            .absorb(&mut repeated_keys)?
+            .repeated(repeated_keys, |ctx| {
+                let mut id = Uint32(0);
+                let mut key = NodeKey::default();
+                ctx.absorb(&mut id)?.mask(&mut key)?;
+                tree.keys.insert(id.0, key);
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_leaves)?
+            .repeated(repeated_leaves, |ctx| {
+                let mut pk = ed25519::PublicKey::default();
+                let mut leaf = Uint32(0);
+                ctx.absorb(&mut pk)?.absorb(&mut leaf)?;
+                tree.leaves.insert(pk.into(), leaf.0);
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_joins)?
+            .repeated(repeated_joins, |ctx| {
+                let mut pk = ed25519::PublicKey::default();
+                ctx.absorb(&mut pk)?;
+                tree.joins.push(pk);
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_dirty)?
+            .repeated(repeated_dirty, |ctx| {
+                let mut id = Uint32(0);
+                ctx.absorb(&mut id)?;
+                tree.dirty.insert(id.0);
+                Ok(ctx)
+            })?;
+        tree.depth = depth.0;
+        *self = tree;
+        Ok(ctx)
+    }
+}
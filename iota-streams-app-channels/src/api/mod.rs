@@ -1,8 +1,16 @@
+pub mod certificate;
+pub mod kdf;
+pub mod key_tree;
 pub mod pk_store;
+pub mod proof;
 pub mod psk_store;
+pub mod rekey;
+pub mod role;
+pub mod storage;
+pub mod subscription;
 
 pub mod user;
 
 /// Tangle-specific Channel API.
-#[cfg(all(feature = "tangle"))]
+#[cfg(feature = "tangle")]
 pub mod tangle;
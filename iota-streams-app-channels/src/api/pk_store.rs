@@ -3,6 +3,7 @@ use iota_streams_core::Result;
 
 use iota_streams_core::prelude::{
     HashMap,
+    HashSet,
     Vec,
 };
 use iota_streams_core_edsig::{
@@ -11,7 +12,7 @@ use iota_streams_core_edsig::{
 };
 
 pub trait PublicKeyStore<Info>: Default {
-    fn filter<'a>(&'a self, pks: &'a Vec<ed25519::PublicKey>) -> Vec<(&'a ed25519::PublicKey, &'a x25519::PublicKey)>;
+    fn filter<'a>(&'a self, pks: &'a [ed25519::PublicKey]) -> Vec<(&'a ed25519::PublicKey, &'a x25519::PublicKey)>;
 
     /// Retrieve the sequence state for a given publisher
     fn get(&self, pk: &ed25519::PublicKey) -> Option<&Info>;
@@ -23,15 +24,34 @@ pub trait PublicKeyStore<Info>: Default {
     fn iter_mut(&mut self) -> Vec<(&ed25519::PublicKey, &mut Info)>;
 }
 
+#[derive(Clone)]
 pub struct PublicKeyMap<Info> {
     /// Map from user identity -- ed25519 pk -- to
     /// a precalculated corresponding x25519 pk and some additional info.
     pks: HashMap<ed25519::PublicKeyWrap, (x25519::PublicKey, Info)>,
+
+    /// Keys inserted or possibly modified since the changes were last cleared.
+    changed: HashSet<ed25519::PublicKeyWrap>,
 }
 
 impl<Info> PublicKeyMap<Info> {
     pub fn new() -> Self {
-        Self { pks: HashMap::new() }
+        Self {
+            pks: HashMap::new(),
+            changed: HashSet::new(),
+        }
+    }
+
+    /// Keys inserted or possibly modified since `clear_changed`, together with their info.
+    pub fn changed(&self) -> Vec<(&ed25519::PublicKey, &Info)> {
+        self.changed
+            .iter()
+            .filter_map(|pk| self.pks.get_key_value(pk).map(|(k, (_x, i))| (&k.0, i)))
+            .collect()
+    }
+
+    pub fn clear_changed(&mut self) {
+        self.changed.clear();
     }
 }
 
@@ -42,7 +62,7 @@ impl<Info> Default for PublicKeyMap<Info> {
 }
 
 impl<Info> PublicKeyStore<Info> for PublicKeyMap<Info> {
-    fn filter<'a>(&'a self, pks: &'a Vec<ed25519::PublicKey>) -> Vec<(&'a ed25519::PublicKey, &'a x25519::PublicKey)> {
+    fn filter<'a>(&'a self, pks: &'a [ed25519::PublicKey]) -> Vec<(&'a ed25519::PublicKey, &'a x25519::PublicKey)> {
         pks.iter()
             .filter_map(|pk| self.pks.get_key_value(pk.into()).map(|(e, (x, _))| (&e.0, x)))
             .collect()
@@ -52,7 +72,11 @@ impl<Info> PublicKeyStore<Info> for PublicKeyMap<Info> {
         self.pks.get(pk.into()).map(|(_x, i)| i)
     }
     fn get_mut(&mut self, pk: &ed25519::PublicKey) -> Option<&mut Info> {
-        self.pks.get_mut(pk.into()).map(|(_x, i)| i)
+        let entry = self.pks.get_mut(pk.into());
+        if entry.is_some() {
+            self.changed.insert((*pk).into());
+        }
+        entry.map(|(_x, i)| i)
     }
     fn get_ke_pk(&self, pk: &ed25519::PublicKey) -> Option<&x25519::PublicKey> {
         self.pks.get(pk.into()).map(|(x, _i)| x)
@@ -60,6 +84,7 @@ impl<Info> PublicKeyStore<Info> for PublicKeyMap<Info> {
     fn insert(&mut self, pk: ed25519::PublicKey, info: Info) -> Result<()>{
         let xpk = x25519::public_from_ed25519(&pk)?;
         self.pks.insert(pk.into(), (xpk, info));
+        self.changed.insert(pk.into());
         Ok(())
     }
     fn keys(&self) -> Vec<(&ed25519::PublicKey, &x25519::PublicKey)> {
@@ -69,6 +94,7 @@ impl<Info> PublicKeyStore<Info> for PublicKeyMap<Info> {
         self.pks.iter().map(|(k, (_x, i))| (&k.0, i)).collect()
     }
     fn iter_mut(&mut self) -> Vec<(&ed25519::PublicKey, &mut Info)> {
+        self.changed.extend(self.pks.keys().copied());
         self.pks.iter_mut().map(|(k, (_x, i))| (&k.0, i)).collect()
     }
 }
@@ -0,0 +1,195 @@
+//! Proofs disclosing a single signed packet to an outside party.
+//!
+//! Proof contains the packet, spongos state of the message it's joined to and
+//! publisher's public key, that's enough to unwrap the packet without user state.
+//! Note, the spongos state also unwraps other messages joined to the same message.
+//!
+//! Anyone can make a proof of a packet they signed themselves, so the verifier must
+//! know whose packet is expected, eg. the Author's public key from the announcement.
+//!
+//! ```ddml
+//! message SignedPacketProof {
+//!     absorb u8 version;
+//!     absorb fallback link;
+//!     absorb bytes body;
+//!     absorb fallback link_to;
+//!     absorb u8 spongos[capacity];
+//!     absorb u8 sig_pk[32];
+//!     absorb u8 certified;
+//!     commit;
+//!     squeeze u8 mac[32];
+//! }
+//! ```
+
+use core::{
+    fmt,
+    hash,
+};
+
+use iota_streams_app::message::{
+    BinaryMessage,
+    HasLink,
+};
+use iota_streams_core::{
+    prelude::Vec,
+    sponge::prp::{
+        Inner,
+        PRP,
+    },
+    try_or,
+    Errors::*,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    link_store::{
+        DefaultLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+use crate::message::{
+    signed_packet,
+    SIGNED_PACKET,
+};
+
+const VERSION: u8 = 0;
+
+/// Self-contained proof of a signed packet.
+pub struct SignedPacketProof<F: PRP, Link: HasLink> {
+    /// Disclosed signed packet.
+    pub msg: BinaryMessage<F, Link>,
+
+    /// Link to the message the packet is joined to.
+    pub link_to: <Link as HasLink>::Rel,
+
+    /// Spongos state of the joined message.
+    pub spongos: Inner<F>,
+
+    /// Public key of the packet publisher.
+    pub sig_pk: ed25519::PublicKey,
+
+    /// Packet carries publication time, as packets of channels with certified publishers do.
+    pub certified: bool,
+}
+
+impl<F, Link> SignedPacketProof<F, Link>
+where
+    F: PRP,
+    Link: HasLink + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: AbsorbFallback<F>,
+{
+    /// Encode the proof.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let body = Bytes(self.msg.body.bytes.clone());
+        let buf_size = {
+            let mut ctx = sizeof::Context::<F>::new();
+            ctx.absorb(Uint8(VERSION))?
+                .absorb(<&Fallback<Link>>::from(&self.msg.link))?
+                .absorb(&body)?
+                .absorb(<&Fallback<<Link as HasLink>::Rel>>::from(&self.link_to))?
+                .absorb(<&NBytes<F::CapacitySize>>::from(self.spongos.arr()))?
+                .absorb(&self.sig_pk)?
+                .absorb(Uint8(self.certified as u8))?
+                .commit()?
+                .squeeze(Mac(32))?;
+            ctx.get_size()
+        };
+
+        let mut buf = vec![0; buf_size];
+
+        {
+            let mut ctx = wrap::Context::<F, &mut [u8]>::new(&mut buf[..]);
+            ctx.absorb(Uint8(VERSION))?
+                .absorb(<&Fallback<Link>>::from(&self.msg.link))?
+                .absorb(&body)?
+                .absorb(<&Fallback<<Link as HasLink>::Rel>>::from(&self.link_to))?
+                .absorb(<&NBytes<F::CapacitySize>>::from(self.spongos.arr()))?
+                .absorb(&self.sig_pk)?
+                .absorb(Uint8(self.certified as u8))?
+                .commit()?
+                .squeeze(Mac(32))?;
+            try_or!(
+                ctx.stream.is_empty(),
+                OutputStreamNotFullyConsumed(ctx.stream.len())
+            )?;
+        }
+
+        Ok(buf)
+    }
+}
+
+impl<F, Link> SignedPacketProof<F, Link>
+where
+    F: PRP,
+    Link: HasLink + Clone + Default + AbsorbExternalFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + Clone + Default + hash::Hash + fmt::Display + SkipFallback<F> + AbsorbFallback<F>,
+{
+    /// Decode the proof, its signature is not checked.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut ctx = unwrap::Context::new(bytes);
+        let mut version = Uint8(0);
+        let mut link = Fallback(Link::default());
+        let mut body = Bytes::default();
+        let mut link_to = Fallback(<Link as HasLink>::Rel::default());
+        let mut spongos = Inner::<F>::default();
+        let mut sig_pk = ed25519::PublicKey::default();
+        let mut certified = Uint8(0);
+        ctx.absorb(&mut version)?
+            .guard(version.0 == VERSION, ProofVersionNotSupported(VERSION, version.0))?
+            .absorb(&mut link)?
+            .absorb(&mut body)?
+            .absorb(&mut link_to)?
+            .absorb(<&mut NBytes<F::CapacitySize>>::from(spongos.arr_mut()))?
+            .absorb(&mut sig_pk)?
+            .absorb(&mut certified)?
+            .commit()?
+            .squeeze(Mac(32))?;
+        Ok(Self {
+            msg: BinaryMessage::new(link.0, body.0.into()),
+            link_to: link_to.0,
+            spongos,
+            sig_pk,
+            certified: certified.0 != 0,
+        })
+    }
+
+    /// Check the packet is signed by `publisher` and return its public and masked payloads.
+    ///
+    ///  # Arguments
+    ///  * `publisher` - Public key of the expected publisher, eg. the Author of the channel
+    pub fn verify(&self, publisher: &ed25519::PublicKey) -> Result<(Bytes, Bytes)> {
+        let mut store = DefaultLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        store.insert(&self.link_to, self.spongos.clone(), ())?;
+        let preparsed = self.msg.parse_header()?;
+        try_or!(
+            preparsed.content_type() == SIGNED_PACKET,
+            NotSignedPacket(preparsed.content_type())
+        )?;
+        let mut content = signed_packet::ContentUnwrap::<F, Link>::default();
+        if self.certified {
+            content = content.with_timestamp();
+        }
+        let content = preparsed.unwrap(&store, content)?.pcf.content;
+        try_or!(content.sig_pk == self.sig_pk, ProofSignerMismatch)?;
+        try_or!(content.sig_pk == *publisher, ProofPublisherMismatch)?;
+        Ok((content.public_payload, content.masked_payload))
+    }
+}
+
+/// Verify an encoded signed packet proof of a packet signed by `publisher` and return
+/// its public and masked payloads.
+pub fn verify_signed_packet_proof<F, Link>(
+    bytes: &[u8],
+    publisher: &ed25519::PublicKey,
+) -> Result<(Bytes, Bytes)>
+where
+    F: PRP,
+    Link: HasLink + Clone + Default + AbsorbExternalFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + Clone + Default + hash::Hash + fmt::Display + SkipFallback<F> + AbsorbFallback<F>,
+{
+    SignedPacketProof::<F, Link>::from_bytes(bytes)?.verify(publisher)
+}
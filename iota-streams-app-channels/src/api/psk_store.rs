@@ -1,6 +1,7 @@
 use iota_streams_core::{
     prelude::{
         HashMap,
+        HashSet,
         Vec,
     },
     psk,
@@ -13,14 +14,29 @@ pub trait PresharedKeyStore: Default {
     fn iter(&self) -> Vec<(&psk::PskId, &psk::Psk)>;
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct PresharedKeyMap {
     psks: HashMap<psk::PskId, psk::Psk>,
+
+    /// Identifiers of the keys inserted since the changes were last cleared.
+    changed: HashSet<psk::PskId>,
+}
+
+impl PresharedKeyMap {
+    /// Keys inserted since `clear_changed`.
+    pub fn changed(&self) -> Vec<(&psk::PskId, &psk::Psk)> {
+        self.changed.iter().filter_map(|pskid| self.psks.get_key_value(pskid)).collect()
+    }
+
+    pub fn clear_changed(&mut self) {
+        self.changed.clear();
+    }
 }
 
 impl PresharedKeyStore for PresharedKeyMap {
     fn insert(&mut self, pskid: psk::PskId, psk: psk::Psk) {
         self.psks.insert(pskid, psk);
+        self.changed.insert(pskid);
     }
     fn filter<'a>(&'a self, psk_ids: &'_ psk::PskIds) -> Vec<psk::IPsk<'a>> {
         psk_ids
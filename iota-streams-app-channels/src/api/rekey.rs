@@ -0,0 +1,326 @@
+//! Automatic rekeying policy for Author.
+
+use iota_streams_app::message::{
+    ContentSizeof,
+    ContentUnwrap,
+    ContentWrap,
+};
+use core::hash;
+
+use iota_streams_core::{
+    prelude::{
+        HashMap,
+        Vec,
+    },
+    psk,
+    sponge::prp::PRP,
+    Result,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    types::*,
+};
+
+/// Conditions under which Author publishes a fresh keyload before the next packet.
+/// All the conditions are disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RekeyPolicy {
+    /// Rekey once this many packets have been sent since the last keyload, `0` disables.
+    pub every_packets: usize,
+
+    /// Rekey once the last keyload is older than this many seconds, `0` disables.
+    /// Requires `std` feature as there's no clock otherwise.
+    pub every_secs: u64,
+
+    /// Rekey when a subscriber or a pre-shared key has been added or removed since the last keyload.
+    pub on_subscriber_change: bool,
+}
+
+impl RekeyPolicy {
+    pub fn every_packets(mut self, n: usize) -> Self {
+        self.every_packets = n;
+        self
+    }
+
+    pub fn every_secs(mut self, secs: u64) -> Self {
+        self.every_secs = secs;
+        self
+    }
+
+    pub fn on_subscriber_change(mut self, on: bool) -> Self {
+        self.on_subscriber_change = on;
+        self
+    }
+}
+
+/// Step taken before a packet is sent according to the policy.
+pub(crate) enum Rekey<Rel> {
+    /// Packet is linked as requested.
+    Keep,
+
+    /// Branch has been rekeyed, packet is linked to the latest keyload for everyone instead.
+    Follow(Rel),
+
+    /// Keyload for everyone is sequenced after the given message, packet is linked to the keyload.
+    After(Rel),
+
+    /// Rekey is due, but packet is linked to a branch of some of the recipients or to no branch.
+    Restricted,
+}
+
+/// Author's progress against the policy since the latest keyload for everyone, exported with
+/// user state. Keyloads for some of the recipients start branches the policy doesn't apply to.
+#[derive(Clone, Default)]
+pub(crate) struct RekeyState<Rel> {
+    pub(crate) policy: RekeyPolicy,
+
+    /// Latest keyload for everyone. Policy applies only after the first one, channels without
+    /// keyloads are public.
+    keyload: Option<Rel>,
+
+    /// Keyload for everyone of the branch each packet belongs to. Branches of the latest and
+    /// the preceding keyload are tracked.
+    links: HashMap<Rel, Rel>,
+
+    /// Packets sent since the latest keyload for everyone.
+    packets: usize,
+
+    /// Time of the latest keyload for everyone in milliseconds.
+    timestamp: u64,
+
+    /// Recipients of the latest keyload for everyone.
+    recipients: (psk::PskIds, Vec<ed25519::PublicKey>),
+}
+
+impl<Rel: Eq + hash::Hash> PartialEq for RekeyState<Rel> {
+    fn eq(&self, other: &Self) -> bool {
+        self.policy == other.policy
+            && self.keyload == other.keyload
+            && self.links == other.links
+            && self.packets == other.packets
+            && self.timestamp == other.timestamp
+            && self.recipients == other.recipients
+    }
+}
+
+impl<Rel: Clone + Default + Eq + hash::Hash> RekeyState<Rel> {
+    pub(crate) fn new(policy: RekeyPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Step to take before a packet linked to `link_to` is sent.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Link of the message the packet is to be linked to
+    ///  * `recipients` - Current recipients of a keyload for everyone
+    ///  * `now` - Current time in milliseconds
+    pub(crate) fn check(&self, link_to: &Rel, recipients: &(psk::PskIds, Vec<ed25519::PublicKey>), now: u64) -> Rekey<Rel> {
+        let keyload = match self.keyload {
+            Some(ref keyload) => keyload,
+            None => return Rekey::Keep,
+        };
+        let is_due = self.is_due(recipients, now);
+        match self.links.get(link_to) {
+            Some(branch) if branch != keyload && is_due => Rekey::After(keyload.clone()),
+            Some(branch) if branch != keyload => Rekey::Follow(keyload.clone()),
+            Some(_) if is_due => Rekey::After(link_to.clone()),
+            None if is_due => Rekey::Restricted,
+            _ => Rekey::Keep,
+        }
+    }
+
+    fn is_due(&self, recipients: &(psk::PskIds, Vec<ed25519::PublicKey>), now: u64) -> bool {
+        let policy = &self.policy;
+        (policy.every_packets != 0 && self.packets >= policy.every_packets)
+            || (policy.every_secs != 0
+                && now.saturating_sub(self.timestamp) >= policy.every_secs.saturating_mul(1000))
+            || (policy.on_subscriber_change && !same_recipients(&self.recipients, recipients))
+    }
+
+    /// Keyload `link` for `recipients` has been sent at `now`. Only a keyload for `everyone`,
+    /// the current recipients of a keyload for everyone, restarts counting. Keyloads are not
+    /// tracked while all the conditions are disabled.
+    pub(crate) fn keyload_sent(
+        &mut self,
+        link: Rel,
+        recipients: (psk::PskIds, Vec<ed25519::PublicKey>),
+        everyone: &(psk::PskIds, Vec<ed25519::PublicKey>),
+        now: u64,
+    ) {
+        if self.policy == RekeyPolicy::default() || !same_recipients(&recipients, everyone) {
+            return;
+        }
+        let preceding = self.keyload.replace(link.clone());
+        self.links.retain(|_, branch| Some(&*branch) == preceding.as_ref());
+        self.links.insert(link.clone(), link);
+        self.packets = 0;
+        self.timestamp = now;
+        self.recipients = recipients;
+    }
+
+    /// Packet `link` linked to `link_to` belongs to the branch of `link_to`.
+    pub(crate) fn track(&mut self, link: Rel, link_to: &Rel) {
+        if let Some(branch) = self.links.get(link_to).cloned() {
+            self.links.insert(link, branch);
+        }
+    }
+
+    /// Packet `link` has been sent, only packets of the latest keyload for everyone are counted.
+    pub(crate) fn packet_sent(&mut self, link: &Rel) {
+        if self.keyload.is_some() && self.links.get(link) == self.keyload.as_ref() {
+            self.packets += 1;
+        }
+    }
+
+    /// Forget packets `is_kept` rejects, see `User::prune_link_store`.
+    pub(crate) fn retain<P: FnMut(&Rel) -> bool>(&mut self, mut is_kept: P) {
+        self.links.retain(|link, _| is_kept(link));
+    }
+}
+
+fn same_recipients(
+    a: &(psk::PskIds, Vec<ed25519::PublicKey>),
+    b: &(psk::PskIds, Vec<ed25519::PublicKey>),
+) -> bool {
+    a.0.len() == b.0.len()
+        && a.1.len() == b.1.len()
+        && a.0.iter().all(|id| b.0.contains(id))
+        && a.1.iter().all(|pk| b.1.contains(pk))
+}
+
+impl<F: PRP, Rel: AbsorbFallback<F>> ContentSizeof<F> for RekeyState<Rel> {
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        ctx
+            .absorb(Uint64(self.policy.every_packets as u64))?
+            .absorb(Uint64(self.policy.every_secs))?
+            .absorb(Uint8(self.policy.on_subscriber_change as u8))?
+            .absorb(Uint8(self.keyload.is_some() as u8))?;
+        if let Some(ref keyload) = self.keyload {
+            ctx.absorb(<&Fallback<Rel>>::from(keyload))?;
+        }
+        ctx
+            .absorb(Size(self.links.len()))?
+            .repeated(self.links.iter(), |ctx, (link, branch)| {
+                ctx.absorb(<&Fallback<Rel>>::from(link))?
+                    .absorb(<&Fallback<Rel>>::from(branch))
+            })?
+            .absorb(Uint64(self.packets as u64))?
+            .absorb(Uint64(self.timestamp))?
+            .absorb(Size(self.recipients.0.len()))?
+            .repeated(self.recipients.0.iter(), |ctx, pskid| {
+                ctx.absorb(<&NBytes<psk::PskIdSize>>::from(pskid))
+            })?
+            .absorb(Size(self.recipients.1.len()))?
+            .repeated(self.recipients.1.iter(), |ctx, pk| ctx.absorb(pk))?;
+        Ok(ctx)
+    }
+}
+
+impl<F: PRP, Rel: AbsorbFallback<F>, Store> ContentWrap<F, Store> for RekeyState<Rel> {
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        _store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx
+            .absorb(Uint64(self.policy.every_packets as u64))?
+            .absorb(Uint64(self.policy.every_secs))?
+            .absorb(Uint8(self.policy.on_subscriber_change as u8))?
+            .absorb(Uint8(self.keyload.is_some() as u8))?;
+        if let Some(ref keyload) = self.keyload {
+            ctx.absorb(<&Fallback<Rel>>::from(keyload))?;
+        }
+        ctx
+            .absorb(Size(self.links.len()))?
+            .repeated(self.links.iter(), |ctx, (link, branch)| {
+                ctx.absorb(<&Fallback<Rel>>::from(link))?
+                    .absorb(<&Fallback<Rel>>::from(branch))
+            })?
+            .absorb(Uint64(self.packets as u64))?
+            .absorb(Uint64(self.timestamp))?
+            .absorb(Size(self.recipients.0.len()))?
+            .repeated(self.recipients.0.iter(), |ctx, pskid| {
+                ctx.absorb(<&NBytes<psk::PskIdSize>>::from(pskid))
+            })?
+            .absorb(Size(self.recipients.1.len()))?
+            .repeated(self.recipients.1.iter(), |ctx, pk| ctx.absorb(pk))?;
+        Ok(ctx)
+    }
+}
+
+impl<F: PRP, Rel, Store> ContentUnwrap<F, Store> for RekeyState<Rel>
+where
+    Rel: Default + Eq + hash::Hash + AbsorbFallback<F>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        _store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut every_packets = Uint64(0);
+        let mut every_secs = Uint64(0);
+        let mut on_subscriber_change = Uint8(0);
+        let mut has_keyload = Uint8(0);
+        let mut repeated_links = Size(0);
+        let mut packets = Uint64(0);
+        let mut timestamp = Uint64(0);
+        let mut repeated_psks = Size(0);
+        let mut repeated_pks = Size(0);
+        let mut links = HashMap::new();
+        let mut psk_ids = Vec::new();
+        let mut pks = Vec::new();
+        ctx
+            .absorb(&mut every_packets)?
+            .absorb(&mut every_secs)?
+            .absorb(&mut on_subscriber_change)?
+            .absorb(&mut has_keyload)?;
+        let keyload = if has_keyload.0 != 0 {
+            let mut keyload = Fallback(Rel::default());
+            ctx.absorb(&mut keyload)?;
+            Some(keyload.0)
+        } else {
+            None
+        };
+        ctx
+            .absorb(&mut repeated_links)?
+            .repeated(repeated_links, |ctx| {
+                let mut link = Fallback(Rel::default());
+                let mut branch = Fallback(Rel::default());
+                ctx.absorb(&mut link)?.absorb(&mut branch)?;
+                links.insert(link.0, branch.0);
+                Ok(ctx)
+            })?
+            .absorb(&mut packets)?
+            .absorb(&mut timestamp)?
+            .absorb(&mut repeated_psks)?
+            .repeated(repeated_psks, |ctx| {
+                let mut pskid = psk::PskId::default();
+                ctx.absorb(<&mut NBytes<psk::PskIdSize>>::from(&mut pskid))?;
+                psk_ids.push(pskid);
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_pks)?
+            .repeated(repeated_pks, |ctx| {
+                let mut pk = ed25519::PublicKey::default();
+                ctx.absorb(&mut pk)?;
+                pks.push(pk);
+                Ok(ctx)
+            })?;
+        self.policy = RekeyPolicy {
+            every_packets: every_packets.0 as usize,
+            every_secs: every_secs.0,
+            on_subscriber_change: on_subscriber_change.0 != 0,
+        };
+        self.keyload = keyload;
+        self.links = links;
+        self.packets = packets.0 as usize;
+        self.timestamp = timestamp.0;
+        self.recipients = (psk_ids, pks);
+        Ok(ctx)
+    }
+}
@@ -0,0 +1,40 @@
+//! Permissions of channel participants.
+
+use core::convert::TryFrom;
+
+/// Role assigned to a participant's public key by Author.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Can read messages, packets published by readers are rejected.
+    Reader = 0,
+
+    /// Can read and publish packets.
+    Writer = 1,
+
+    /// Can read and publish packets, manages the channel together with Author.
+    Admin = 2,
+}
+
+impl Role {
+    pub fn can_write(&self) -> bool {
+        *self >= Role::Writer
+    }
+}
+
+impl From<Role> for u8 {
+    fn from(role: Role) -> Self {
+        role as u8
+    }
+}
+
+impl TryFrom<u8> for Role {
+    type Error = ();
+    fn try_from(x: u8) -> Result<Self, ()> {
+        match x {
+            0 => Ok(Role::Reader),
+            1 => Ok(Role::Writer),
+            2 => Ok(Role::Admin),
+            _ => Err(()),
+        }
+    }
+}
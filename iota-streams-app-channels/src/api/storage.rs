@@ -0,0 +1,221 @@
+//! Key-value storage backends for persisting user state.
+
+use core::cell::RefCell;
+
+use iota_streams_core::{
+    prelude::{
+        HashMap,
+        Rc,
+        String,
+        ToString,
+        Vec,
+    },
+    Result,
+};
+
+/// Storage of opaque values identified by string keys. Values are stored as is,
+/// encryption is up to the caller.
+pub trait Storage {
+    /// Fetch the value stored under `key`, `None` if there's no such value.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store `value` under `key` replacing the previous one. A failed write must leave
+    /// the previous value intact.
+    fn put(&mut self, key: &str, value: &[u8]) -> Result<()>;
+
+    /// Remove the value stored under `key`, removing a missing value is not an error.
+    fn remove(&mut self, key: &str) -> Result<()>;
+}
+
+/// Shared storage, eg. to keep access to the storage owned by a user.
+impl<S: Storage> Storage for Rc<RefCell<S>> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.borrow().get(key)
+    }
+    fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        self.borrow_mut().put(key, value)
+    }
+    fn remove(&mut self, key: &str) -> Result<()> {
+        self.borrow_mut().remove(key)
+    }
+}
+
+/// In-memory storage, the values are lost once it's dropped.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    values: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.values.get(key).cloned())
+    }
+    fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        self.values.insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+    fn remove(&mut self, key: &str) -> Result<()> {
+        self.values.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::file::FileStorage;
+
+#[cfg(feature = "std")]
+mod file {
+    use std::{
+        fs,
+        io,
+        path::PathBuf,
+    };
+
+    use iota_streams_core::{
+        err,
+        prelude::{
+            ToString,
+            Vec,
+        },
+        Errors::*,
+        Result,
+        LOCATION_LOG,
+    };
+
+    use super::Storage;
+
+    /// Filesystem storage keeping each value in a separate file of a directory.
+    /// Values are written to a temporary file first and then renamed over the old file.
+    /// Meant for a few values like checkpoints, see `DatabaseStorage` for many small values.
+    pub struct FileStorage {
+        dir: PathBuf,
+    }
+
+    impl FileStorage {
+        /// Open storage in directory `dir`, the directory is created if it doesn't exist.
+        pub fn new<P: Into<PathBuf>>(dir: P) -> Result<Self> {
+            let dir = dir.into();
+            match fs::create_dir_all(&dir) {
+                Ok(()) => Ok(Self { dir }),
+                Err(e) => err!(StorageFailure(e.to_string())),
+            }
+        }
+
+        fn path(&self, key: &str) -> PathBuf {
+            self.dir.join(key)
+        }
+    }
+
+    impl Storage for FileStorage {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            match fs::read(self.path(key)) {
+                Ok(value) => Ok(Some(value)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => err!(StorageFailure(e.to_string())),
+            }
+        }
+        fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+            let tmp = self.dir.join(format!("{}.tmp", key));
+            match fs::write(&tmp, value).and_then(|()| fs::rename(&tmp, self.path(key))) {
+                Ok(()) => Ok(()),
+                Err(e) => err!(StorageFailure(e.to_string())),
+            }
+        }
+        fn remove(&mut self, key: &str) -> Result<()> {
+            match fs::remove_file(self.path(key)) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(e) => err!(StorageFailure(e.to_string())),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "db-storage")]
+pub use self::db::DatabaseStorage;
+
+#[cfg(feature = "db-storage")]
+mod db {
+    use std::path::Path;
+
+    use redb::{
+        Database,
+        ReadableTable as _,
+        TableDefinition,
+    };
+
+    use iota_streams_core::{
+        err,
+        prelude::{
+            ToString,
+            Vec,
+        },
+        Errors::*,
+        Result,
+        LOCATION_LOG,
+    };
+
+    use super::Storage;
+
+    const VALUES: TableDefinition<&str, &[u8]> = TableDefinition::new("values");
+
+    fn or_failure<T, E: ToString>(result: core::result::Result<T, E>) -> Result<T> {
+        match result {
+            Ok(t) => Ok(t),
+            Err(e) => err!(StorageFailure(e.to_string())),
+        }
+    }
+
+    /// Storage keeping all the values in a single embedded database file, eg. for
+    /// `PersistentLinkStore` with a value per message. Each write is a transaction,
+    /// so a failed write leaves the previous value intact.
+    pub struct DatabaseStorage {
+        db: Database,
+    }
+
+    impl DatabaseStorage {
+        /// Open database file at `path`, the file is created if it doesn't exist.
+        pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+            let db = or_failure(Database::create(path))?;
+            // Create the table, so that reads of a new database don't fail.
+            let txn = or_failure(db.begin_write())?;
+            or_failure(txn.open_table(VALUES))?;
+            or_failure(txn.commit())?;
+            Ok(Self { db })
+        }
+    }
+
+    impl Storage for DatabaseStorage {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            let txn = or_failure(self.db.begin_read())?;
+            let table = or_failure(txn.open_table(VALUES))?;
+            let value = or_failure(table.get(key))?;
+            Ok(value.map(|value| value.value().to_vec()))
+        }
+        fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+            let txn = or_failure(self.db.begin_write())?;
+            or_failure(or_failure(txn.open_table(VALUES))?.insert(key, value))?;
+            or_failure(txn.commit())
+        }
+        fn remove(&mut self, key: &str) -> Result<()> {
+            let txn = or_failure(self.db.begin_write())?;
+            or_failure(or_failure(txn.open_table(VALUES))?.remove(key))?;
+            or_failure(txn.commit())
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Author's policy for approving incoming subscriptions.
+
+use iota_streams_core::prelude::{
+    Box,
+    Vec,
+};
+use iota_streams_core_edsig::signature::ed25519;
+
+/// Decides which subscribers are approved on receipt of their Subscribe message.
+/// Subscribers that are not approved are kept in a pending queue until Author approves
+/// or rejects them explicitly.
+///
+/// Policy is exported with user state, except for the callback which is restored as `Manual`.
+#[derive(Default)]
+pub enum SubscriptionPolicy {
+    /// Approve every subscriber.
+    #[default]
+    AcceptAll,
+
+    /// Keep every subscriber pending.
+    Manual,
+
+    /// Approve listed keys only.
+    Allowlist(Vec<ed25519::PublicKey>),
+
+    /// Approve subscribers the callback returns `true` for.
+    Callback(Box<dyn Fn(&ed25519::PublicKey) -> bool + Send + Sync>),
+
+    /// Approve subscribers while their number is below the limit.
+    MaxSubscribers(usize),
+}
+
+impl SubscriptionPolicy {
+    /// Whether subscriber `pk` is approved given the number of already approved subscribers.
+    pub fn approves(&self, pk: &ed25519::PublicKey, subscribers: usize) -> bool {
+        match self {
+            SubscriptionPolicy::AcceptAll => true,
+            SubscriptionPolicy::Manual => false,
+            SubscriptionPolicy::Allowlist(pks) => pks.contains(pk),
+            SubscriptionPolicy::Callback(f) => f(pk),
+            SubscriptionPolicy::MaxSubscribers(n) => subscribers < *n,
+        }
+    }
+
+    /// Variant identifier used in exported state.
+    pub(crate) fn tag(&self) -> u8 {
+        match self {
+            SubscriptionPolicy::AcceptAll => 0,
+            SubscriptionPolicy::Manual => 1,
+            SubscriptionPolicy::Allowlist(_) => 2,
+            SubscriptionPolicy::Callback(_) => 3,
+            SubscriptionPolicy::MaxSubscribers(_) => 4,
+        }
+    }
+}
@@ -4,13 +4,23 @@ use iota_streams_core::Result;
 use core::fmt;
 
 use super::*;
-use crate::api::tangle::{
-    UnwrappedMessage,
-    User,
+use crate::api::{
+    rekey::RekeyState,
+    tangle::{
+        RekeyPolicy,
+        Role,
+        Storage,
+        SubscriptionPolicy,
+        UnwrappedMessage,
+        User,
+    },
 };
 
 use iota_streams_core::prelude::Vec;
-use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_core_edsig::{
+    key_exchange::x25519,
+    signature::ed25519,
+};
 
 /// Author Object. Contains User API.
 pub struct Author<Trans> {
@@ -29,17 +39,104 @@ impl<Trans> Author<Trans>
     /// * `transport` - Transport object used for sending and receiving
     ///
     pub fn new(seed: &str, encoding: &str, payload_length: usize, multi_branching: bool, transport: Trans) -> Self {
-        let mut user = User::new(seed, encoding, payload_length, multi_branching, transport);
+        let flags = if multi_branching { FLAG_BRANCHING_MASK } else { 0 };
+        Self::new_with_flags(seed, encoding, payload_length, flags, transport)
+    }
+
+    /// Create a new Author instance with the given channel flags. With `FLAG_ANONYMOUS_KEYLOAD_MASK`
+    /// keyloads disclose subscribers to the recipients only.
+    ///
+    /// # Arguments
+    /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
+    /// * `encoding` - A string slice representing the encoding type for the message [supported: utf-8]
+    /// * `payload_length` - Maximum size in bytes of payload per message chunk [1-1024],
+    /// * `flags` - Channel flags bit field, eg. `FLAG_BRANCHING_MASK | FLAG_ANONYMOUS_KEYLOAD_MASK`
+    /// * `transport` - Transport object used for sending and receiving
+    ///
+    pub fn new_with_flags(seed: &str, encoding: &str, payload_length: usize, flags: u8, transport: Trans) -> Self {
+        let mut user = User::new_with_flags(seed, encoding, payload_length, flags, transport);
         let channel_idx = 0_u64;
         let _ = user.user.create_channel(channel_idx);
         Self { user }
     }
 
+    /// Create a new Author instance of a hidden channel. Channel address is derived from the
+    /// pre-shared key and the announcement is masked with it, only the key holders can
+    /// find the channel and learn Author's public key.
+    ///
+    /// # Arguments
+    /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
+    /// * `encoding` - A string slice representing the encoding type for the message [supported: utf-8]
+    /// * `payload_length` - Maximum size in bytes of payload per message chunk [1-1024],
+    /// * `flags` - Channel flags bit field, eg. `FLAG_BRANCHING_MASK | FLAG_ANONYMOUS_KEYLOAD_MASK`
+    /// * `pskid` - Identifier of the pre-shared key
+    /// * `psk` - Pre-shared key hiding the channel
+    /// * `transport` - Transport object used for sending and receiving
+    ///
+    pub fn new_hidden(
+        seed: &str,
+        encoding: &str,
+        payload_length: usize,
+        flags: u8,
+        pskid: PskId,
+        psk: Psk,
+        transport: Trans,
+    ) -> Self {
+        let mut user = User::new_with_flags(seed, encoding, payload_length, flags, transport);
+        let channel_idx = 0_u64;
+        let _ = user.user.create_hidden_channel(pskid, psk, channel_idx);
+        Self { user }
+    }
+
     /// Return boolean representing the sequencing nature of the channel
     pub fn is_multi_branching(&self) -> bool {
         self.user.is_multi_branching()
     }
 
+    /// Return boolean representing whether keyloads hide recipient identities
+    pub fn is_anonymous_keyload(&self) -> bool {
+        self.user.is_anonymous_keyload()
+    }
+
+    /// Return boolean representing whether packets ratchet the session key forward
+    pub fn is_ratchet(&self) -> bool {
+        self.user.is_ratchet()
+    }
+
+    /// Return boolean representing whether signed packets require a certificate issued by Author
+    pub fn is_certified_publishers(&self) -> bool {
+        self.user.is_certified_publishers()
+    }
+
+    /// Return boolean representing whether channel address and announcement are hidden with a pre-shared key
+    pub fn is_hidden(&self) -> bool {
+        self.user.is_hidden()
+    }
+
+    /// Fetch the Address of the topic root message, topic keyloads are attached to it.
+    ///
+    ///   # Arguments
+    ///   * `name` - Topic name
+    ///
+    pub fn topic(&self, name: &Bytes) -> Option<Address> {
+        self.user.topic(name)
+    }
+
+    /// Fetch the names of the declared topics.
+    pub fn topics(&self) -> Vec<Bytes> {
+        self.user.topics()
+    }
+
+    /// Store a pre-shared key, keys are included in keyloads for everyone.
+    ///
+    ///   # Arguments
+    ///   * `pskid` - Identifier of the pre-shared key
+    ///   * `psk` - Pre-shared key
+    ///
+    pub fn store_psk(&mut self, pskid: PskId, psk: Psk) {
+        self.user.store_psk(pskid, psk)
+    }
+
     /// Fetch the Address (application instance) of the channel.
     pub fn channel_address(&self) -> Option<&ChannelAddress> {
         self.user.channel_address()
@@ -68,7 +165,7 @@ impl<Trans> Author<Trans>
     ///   * `link` - Address link to be stored in internal sequence state mapping
     ///
     pub fn store_state(&mut self, pk: ed25519::PublicKey, link: &Address) -> Result<()> {
-        Ok(self.user.store_state(pk, link)?)
+        self.user.store_state(pk, link)
     }
 
     /// Stores the provided link and sequence number to the internal sequencing state for all participants
@@ -79,7 +176,134 @@ impl<Trans> Author<Trans>
     ///   * `seq_num` - New sequence state to be stored in internal sequence state mapping
     ///
     pub fn store_state_for_all(&mut self, link: &Address, seq_num: u32) -> Result<()> {
-        Ok(self.user.store_state_for_all(link, seq_num)?)
+        self.user.store_state_for_all(link, seq_num)
+    }
+
+    /// Add a subscriber to the key tree, its leaf key is sent with the next tree keyload.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of a subscribed participant
+    ///
+    pub fn add_tree_member(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.add_tree_member(pk)
+    }
+
+    /// Remove a subscriber from the key tree, it can't read messages attached to the following
+    /// tree keyloads.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the removed participant
+    ///
+    pub fn remove_tree_member(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.remove_tree_member(pk)
+    }
+
+    /// Refresh key tree group key without changing members.
+    pub fn rekey_tree(&mut self) {
+        self.user.rekey_tree()
+    }
+
+    /// Assign a role to a subscriber. Once roles are assigned, packets are accepted only from
+    /// Author and subscribers with write permission.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of a subscribed participant
+    ///   * `role` - Role of the participant
+    ///
+    pub fn set_role(&mut self, pk: &ed25519::PublicKey, role: Role) -> Result<()> {
+        self.user.set_role(pk, role)
+    }
+
+    /// Fetch the role assigned to a participant.
+    pub fn role(&self, pk: &ed25519::PublicKey) -> Option<Role> {
+        self.user.role(pk)
+    }
+
+    /// Fetch the latest certificate issued for a publisher.
+    pub fn certificate(&self, pk: &ed25519::PublicKey) -> Option<&Certificate> {
+        self.user.certificate(pk)
+    }
+
+    /// Add a channel administrator. Administrators added before the channel is announced are
+    /// included in the announcement, the ones added later are sent with the next admin set.
+    /// Administrators can only be added later to channels created with `FLAG_ADMINS_MASK`.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the administrator
+    ///
+    pub fn add_admin(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.add_admin(pk)
+    }
+
+    /// Remove a channel administrator, subscribers reject its keyloads once they receive the next admin set.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the administrator
+    ///
+    pub fn remove_admin(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.remove_admin(pk)
+    }
+
+    /// Fetch administrators of the channel besides Author.
+    pub fn admins(&self) -> &[ed25519::PublicKey] {
+        self.user.admins()
+    }
+
+    /// Add an auditor sharing the session key of every keyload. Auditors are recorded in the
+    /// announcement, so they must be added before the channel is announced.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the auditor
+    ///
+    pub fn add_auditor(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.add_auditor(pk)
+    }
+
+    /// Add a pre-shared key to every keyload, it's recorded in the announcement as well.
+    ///
+    ///   # Arguments
+    ///   * `pskid` - Identifier of a pre-shared key stored with `store_psk`
+    ///
+    pub fn add_auditor_psk(&mut self, pskid: PskId) -> Result<()> {
+        self.user.add_auditor_psk(pskid)
+    }
+
+    /// Fetch auditor public keys and pre-shared key identifiers included in every keyload.
+    pub fn auditors(&self) -> (&[ed25519::PublicKey], &[PskId]) {
+        self.user.auditors()
+    }
+
+    /// Set the policy applied to incoming subscriptions, subscribers are approved by default.
+    /// Policy is exported with the author, a callback policy is imported as `Manual` and must be set again.
+    ///
+    ///   # Arguments
+    ///   * `policy` - Subscription policy, subscribers it doesn't approve are kept pending
+    ///
+    pub fn set_subscription_policy(&mut self, policy: SubscriptionPolicy) {
+        self.user.set_subscription_policy(policy)
+    }
+
+    /// Fetch the public keys of subscribers awaiting approval.
+    pub fn pending_subscribers(&self) -> &[ed25519::PublicKey] {
+        self.user.pending_subscribers()
+    }
+
+    /// Approve a pending subscriber, it is included in the following keyloads and accepted as publisher.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the pending subscriber
+    ///
+    pub fn approve_subscriber(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.approve_subscriber(pk)
+    }
+
+    /// Reject a pending subscriber.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the pending subscriber
+    ///
+    pub fn reject_subscriber(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.user.reject_subscriber(pk)
     }
 
     /// Serialize user state and encrypt it with password.
@@ -91,6 +315,36 @@ impl<Trans> Author<Trans>
         self.user.export(0, pwd)
     }
 
+    /// Serialize user state and encrypt it with a key derived from password with the given
+    /// Argon2id parameters, they are stored with the state.
+    ///
+    ///   # Arguments
+    ///   * `pwd` - Encryption password
+    ///   * `kdf` - Argon2id memory, time and parallelism costs
+    ///
+    pub fn export_with_kdf(&self, pwd: &str, kdf: &KdfParams) -> Result<Vec<u8>> {
+        self.user.export_with_kdf(0, pwd, kdf)
+    }
+
+    /// Erase link store entries not needed to continue in the channel: the announcement,
+    /// branch roots, latest keyload of each branch and messages referenced by sequencing states
+    /// are kept. Messages linked to erased entries can't be received anymore.
+    ///
+    /// Return the number of erased entries.
+    pub fn prune_link_store(&mut self) -> Result<usize> {
+        self.user.prune_link_store()
+    }
+
+    /// Prune the link store, then serialize author state and encrypt it with password.
+    ///
+    ///   # Arguments
+    ///   * `pwd` - Encryption password
+    ///
+    /// Return the serialized state and the number of erased link store entries.
+    pub fn export_compact(&mut self, pwd: &str) -> Result<(Vec<u8>, usize)> {
+        self.user.export_compact(0, pwd)
+    }
+
     /// Deserialize user state and decrypt it with password.
     ///
     ///   # Arguments
@@ -101,6 +355,94 @@ impl<Trans> Author<Trans>
     pub fn import(bytes: &[u8], pwd: &str, tsp: Trans) -> Result<Self> {
         User::<Trans>::import(bytes, 0, pwd, tsp).map(|user| Self { user })
     }
+
+    /// Serialize user state and encrypt it to a recipient public key, eg. for a backup escrowed
+    /// to an organizational recovery key.
+    ///
+    ///   # Arguments
+    ///   * `ke_pk` - X25519 public key of the recipient
+    ///
+    pub fn export_to(&self, ke_pk: &x25519::PublicKey) -> Result<Vec<u8>> {
+        self.user.export_to(0, ke_pk)
+    }
+
+    /// Deserialize user state encrypted with `export_to` and decrypt it with the recipient secret key.
+    ///
+    ///   # Arguments
+    ///   * `bytes` - Encrypted serialized user state
+    ///   * `ke_sk` - X25519 secret key of the recipient
+    ///   * `tsp` - Transport object
+    ///
+    pub fn import_with(bytes: &[u8], ke_sk: &x25519::StaticSecret, tsp: Trans) -> Result<Self> {
+        User::<Trans>::import_with(bytes, 0, ke_sk, tsp).map(|user| Self { user })
+    }
+
+    /// Write user state to `storage` and keep it up to date, an encrypted checkpoint is written
+    /// after each sent or received message. Resume the author with `open`.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend, eg. `FileStorage`
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///
+    pub fn attach_storage<S: Storage + 'static>(&mut self, storage: S, pwd: &str) -> Result<()> {
+        self.user.attach_storage(storage, 0, pwd, &KdfParams::default())
+    }
+
+    /// Attach storage with checkpoint key derived with the given Argon2id parameters.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///   * `kdf` - Argon2id memory, time and parallelism costs
+    ///
+    pub fn attach_storage_with_kdf<S: Storage + 'static>(&mut self, storage: S, pwd: &str, kdf: &KdfParams) -> Result<()> {
+        self.user.attach_storage(storage, 0, pwd, kdf)
+    }
+
+    /// Resume author from the checkpoint in storage.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend the checkpoint was written to
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///   * `tsp` - Transport object
+    ///
+    pub fn open<S: Storage + 'static>(storage: S, pwd: &str, tsp: Trans) -> Result<Self> {
+        User::<Trans>::open(storage, 0, pwd, tsp).map(|user| Self { user })
+    }
+
+    /// Write the pending changes of link store, sequencing states and pre-shared keys to storage.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        self.user.checkpoint()
+    }
+
+    /// Write a snapshot of the whole author state to storage.
+    pub fn checkpoint_snapshot(&mut self) -> Result<()> {
+        self.user.checkpoint_snapshot()
+    }
+
+    /// Whether the last checkpoint failed to be written, call `checkpoint` to retry.
+    pub fn checkpoint_failed(&self) -> bool {
+        self.user.checkpoint_failed()
+    }
+
+    /// Set the policy for publishing keyloads automatically. When the policy fires, the next packet
+    /// on the branch of the latest keyload for everyone is preceded by a keyload for everyone and
+    /// linked to it, packets linked to the branch of the preceding one are linked to the latest
+    /// keyload. Branches of keyloads for some of the recipients are not rekeyed, sending a packet
+    /// to them fails with `RekeyBranchNotEveryone` while rekey is due. Rekey policy and progress are
+    /// exported with the author.
+    ///
+    ///   # Arguments
+    ///   * `policy` - Rekey conditions, counting starts with the next keyload
+    ///
+    pub fn set_rekey_policy(&mut self, policy: RekeyPolicy) {
+        self.user.user.rekey = RekeyState::new(policy);
+    }
+
+    /// Fetch the current rekey policy.
+    pub fn rekey_policy(&self) -> &RekeyPolicy {
+        &self.user.user.rekey.policy
+    }
 }
 
 #[cfg(not(feature = "async"))]
@@ -122,7 +464,7 @@ impl<Trans: Transport> Author<Trans>
         &mut self,
         link_to: &Address,
         psk_ids: &PskIds,
-        ke_pks: &Vec<ed25519::PublicKey>,
+        ke_pks: &[ed25519::PublicKey],
     ) -> Result<(Address, Option<Address>)> {
         self.user.send_keyload(link_to, psk_ids, ke_pks)
     }
@@ -136,6 +478,78 @@ impl<Trans: Transport> Author<Trans>
         self.user.send_keyload_for_everyone(link_to)
     }
 
+    /// Create and send tree keyload with pending key tree updates. Updates exceeding
+    /// `uniform_payload_length` are split into several tree keyloads, links of the last one are returned.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the tree keyload will be attached to
+    ///
+    pub fn send_tree_keyload(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_tree_keyload(link_to)
+    }
+
+    /// Create and send role assignment with all the assigned roles.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the role assignment will be attached to
+    ///
+    pub fn send_role_assignment(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_role_assignment(link_to)
+    }
+
+    /// Create and send the set of channel administrators.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the admin set will be attached to, usually the announcement
+    ///
+    pub fn send_admin_set(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_admin_set(link_to)
+    }
+
+    /// Create and send a topic declaration, topic keyloads and packets form a separate branch
+    /// that subscribers may choose not to follow. Fails in single-branch channels.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the topic will be attached to
+    ///  * `name` - Topic name, unique within the channel
+    ///
+    pub fn send_topic(&mut self, link_to: &Address, name: &Bytes) -> Result<(Address, Option<Address>)> {
+        self.user.send_topic(link_to, name)
+    }
+
+    /// Create and send a certificate allowing a publisher to send signed packets. A later
+    /// certificate replaces the earlier one, a publisher is retired with an expired certificate
+    /// or with `revoke_publisher`.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the certificate will be attached to
+    ///  * `subject` - ed25519 Public Key of the certified publisher
+    ///  * `branch` - Address of the message certified packets must be linked to, `None` for any message
+    ///  * `not_before` - Start of the validity window, milliseconds since Unix epoch
+    ///  * `not_after` - End of the validity window, milliseconds since Unix epoch, `0` for no expiry
+    ///
+    pub fn send_certificate(
+        &mut self,
+        link_to: &Address,
+        subject: &ed25519::PublicKey,
+        branch: Option<&Address>,
+        not_before: u64,
+        not_after: u64,
+    ) -> Result<(Address, Option<Address>)> {
+        self.user.send_certificate(link_to, subject, branch, not_before, not_after)
+    }
+
+    /// Create and send a certificate revoking a publisher, none of its signed packets are
+    /// accepted until it's certified again.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the revocation will be attached to
+    ///  * `subject` - ed25519 Public Key of the revoked publisher
+    ///
+    pub fn revoke_publisher(&mut self, link_to: &Address, subject: &ed25519::PublicKey) -> Result<(Address, Option<Address>)> {
+        self.user.revoke_publisher(link_to, subject)
+    }
+
     /// Create and send a signed packet.
     ///
     ///  # Arguments
@@ -169,7 +583,9 @@ impl<Trans: Transport> Author<Trans>
     }
 
 
-    /// Receive and process a subscribe message.
+    /// Receive and process a subscribe message. Subscriber is approved or kept pending
+    /// according to the subscription policy. If a callback policy was lost with export,
+    /// the subscriber is kept pending and `SubscriptionCallbackNotRestored` is returned.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
@@ -178,6 +594,26 @@ impl<Trans: Transport> Author<Trans>
         self.user.receive_subscribe(link)
     }
 
+    /// Receive and process a keyload sent by a channel administrator, subscribers it
+    /// shares the key with become known to Author. Private keyloads of subscribers are accepted too.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_keyload(&mut self, link: &Address) -> Result<bool> {
+        self.user.receive_keyload(link)
+    }
+
+    /// Receive and process a direct message sent by a subscriber, returns sender's public key
+    /// and decrypted payload.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_direct_message(&mut self, link: &Address) -> Result<Option<(ed25519::PublicKey, Bytes)>> {
+        self.user.receive_direct_message(link)
+    }
+
     /// Receive and process a signed packet message.
     ///
     ///  # Arguments
@@ -187,6 +623,16 @@ impl<Trans: Transport> Author<Trans>
         self.user.receive_signed_packet(link)
     }
 
+    /// Export a proof of a received signed packet, it discloses the packet to an outside party
+    /// without sharing the state. Proof is checked with `verify_signed_packet_proof`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the signed packet
+    ///
+    pub fn export_signed_packet_proof(&mut self, link: &Address) -> Result<Vec<u8>> {
+        self.user.export_signed_packet_proof(link)
+    }
+
     /// Receive and process a tagged packet message.
     ///
     ///  # Arguments
@@ -211,7 +657,8 @@ impl<Trans: Transport> Author<Trans>
     }
 
     /// Receive and process a message of unknown type. Message will be handled appropriately and
-    /// the unwrapped contents returned
+    /// the unwrapped contents returned. Receiving a processed message again returns the same
+    /// contents, so failed calls can be safely retried.
     ///
     ///   # Arguments
     ///   * `link` - Address of the message to be processed
@@ -220,6 +667,16 @@ impl<Trans: Transport> Author<Trans>
         self.user.receive_message(link)
     }
 
+    /// Unwrap a message of unknown type like `receive_msg` without processing it, the link
+    /// store and sequencing states are left as they are.
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be previewed
+    ///
+    pub fn preview_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        self.user.peek_message(link)
+    }
+
     // Unsubscribe a subscriber
     // pub pub fn receive_unsubscribe(&mut self, link: Address) -> Result<()> {
     // self.user.handle_unsubscribe(link, MsgInfo::Unsubscribe)
@@ -245,7 +702,7 @@ impl<Trans: Transport> Author<Trans>
         &mut self,
         link_to: &Address,
         psk_ids: &PskIds,
-        ke_pks: &Vec<ed25519::PublicKey>,
+        ke_pks: &[ed25519::PublicKey],
     ) -> Result<(Address, Option<Address>)> {
         self.user.send_keyload(link_to, psk_ids, ke_pks).await
     }
@@ -259,6 +716,78 @@ impl<Trans: Transport> Author<Trans>
         self.user.send_keyload_for_everyone(link_to).await
     }
 
+    /// Create and send tree keyload with pending key tree updates. Updates exceeding
+    /// `uniform_payload_length` are split into several tree keyloads, links of the last one are returned.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the tree keyload will be attached to
+    ///
+    pub async fn send_tree_keyload(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_tree_keyload(link_to).await
+    }
+
+    /// Create and send role assignment with all the assigned roles.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the role assignment will be attached to
+    ///
+    pub async fn send_role_assignment(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_role_assignment(link_to).await
+    }
+
+    /// Create and send the set of channel administrators.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the admin set will be attached to, usually the announcement
+    ///
+    pub async fn send_admin_set(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_admin_set(link_to).await
+    }
+
+    /// Create and send a topic declaration, topic keyloads and packets form a separate branch
+    /// that subscribers may choose not to follow. Fails in single-branch channels.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the topic will be attached to
+    ///  * `name` - Topic name, unique within the channel
+    ///
+    pub async fn send_topic(&mut self, link_to: &Address, name: &Bytes) -> Result<(Address, Option<Address>)> {
+        self.user.send_topic(link_to, name).await
+    }
+
+    /// Create and send a certificate allowing a publisher to send signed packets. A later
+    /// certificate replaces the earlier one, a publisher is retired with an expired certificate
+    /// or with `revoke_publisher`.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the certificate will be attached to
+    ///  * `subject` - ed25519 Public Key of the certified publisher
+    ///  * `branch` - Address of the message certified packets must be linked to, `None` for any message
+    ///  * `not_before` - Start of the validity window, milliseconds since Unix epoch
+    ///  * `not_after` - End of the validity window, milliseconds since Unix epoch, `0` for no expiry
+    ///
+    pub async fn send_certificate(
+        &mut self,
+        link_to: &Address,
+        subject: &ed25519::PublicKey,
+        branch: Option<&Address>,
+        not_before: u64,
+        not_after: u64,
+    ) -> Result<(Address, Option<Address>)> {
+        self.user.send_certificate(link_to, subject, branch, not_before, not_after).await
+    }
+
+    /// Create and send a certificate revoking a publisher, none of its signed packets are
+    /// accepted until it's certified again.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the revocation will be attached to
+    ///  * `subject` - ed25519 Public Key of the revoked publisher
+    ///
+    pub async fn revoke_publisher(&mut self, link_to: &Address, subject: &ed25519::PublicKey) -> Result<(Address, Option<Address>)> {
+        self.user.revoke_publisher(link_to, subject).await
+    }
+
     /// Create and send a signed packet.
     ///
     ///  # Arguments
@@ -291,7 +820,9 @@ impl<Trans: Transport> Author<Trans>
         self.user.send_tagged_packet(link_to, public_payload, masked_payload).await
     }
 
-    /// Receive and process a subscribe message.
+    /// Receive and process a subscribe message. Subscriber is approved or kept pending
+    /// according to the subscription policy. If a callback policy was lost with export,
+    /// the subscriber is kept pending and `SubscriptionCallbackNotRestored` is returned.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
@@ -300,6 +831,26 @@ impl<Trans: Transport> Author<Trans>
         self.user.receive_subscribe(link).await
     }
 
+    /// Receive and process a keyload sent by a channel administrator, subscribers it
+    /// shares the key with become known to Author. Private keyloads of subscribers are accepted too.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_keyload(&mut self, link: &Address) -> Result<bool> {
+        self.user.receive_keyload(link).await
+    }
+
+    /// Receive and process a direct message sent by a subscriber, returns sender's public key
+    /// and decrypted payload.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_direct_message(&mut self, link: &Address) -> Result<Option<(ed25519::PublicKey, Bytes)>> {
+        self.user.receive_direct_message(link).await
+    }
+
     /// Receive and process a signed packet message.
     ///
     ///  # Arguments
@@ -309,6 +860,16 @@ impl<Trans: Transport> Author<Trans>
         self.user.receive_signed_packet(link).await
     }
 
+    /// Export a proof of a received signed packet, it discloses the packet to an outside party
+    /// without sharing the state. Proof is checked with `verify_signed_packet_proof`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the signed packet
+    ///
+    pub async fn export_signed_packet_proof(&mut self, link: &Address) -> Result<Vec<u8>> {
+        self.user.export_signed_packet_proof(link).await
+    }
+
     /// Receive and process a tagged packet message.
     ///
     ///  # Arguments
@@ -333,7 +894,8 @@ impl<Trans: Transport> Author<Trans>
     }
 
     /// Receive and process a message of unknown type. Message will be handled appropriately and
-    /// the unwrapped contents returned
+    /// the unwrapped contents returned. Receiving a processed message again returns the same
+    /// contents, so failed calls can be safely retried.
     ///
     ///   # Arguments
     ///   * `link` - Address of the message to be processed
@@ -343,6 +905,16 @@ impl<Trans: Transport> Author<Trans>
         self.user.receive_message(link).await
     }
 
+    /// Unwrap a message of unknown type like `receive_msg` without processing it, the link
+    /// store and sequencing states are left as they are.
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be previewed
+    ///
+    pub async fn preview_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        self.user.peek_message(link).await
+    }
+
     // Unsubscribe a subscriber
     // pub async fn receive_unsubscribe(&mut self, link: Address) -> Result<()> {
     // self.user.handle_unsubscribe(link, MsgInfo::Unsubscribe).await
@@ -0,0 +1,678 @@
+//! Encrypted incremental checkpoints of tangle user state.
+//!
+//! Checkpoint consists of a snapshot of the whole user state exported with the checkpoint key
+//! and a journal of records with the changes of link store, sequencing cursors, pre-shared keys,
+//! latest keyloads and rekey progress made since the snapshot. Values in storage:
+//!
+//! * `checkpoint-kdf` - Argon2id parameters and salt the checkpoint key is derived with, in plain;
+//! * `checkpoint-head` - generation of the current snapshot;
+//! * `checkpoint-snapshot-<generation>` - snapshot, see `User::export_with_key`;
+//! * `checkpoint-journal-<generation>-<index>` - journal records, indices are contiguous from 0.
+//!
+//! The rest of user state (roles, certificates, admins, topics, etc.) is written with snapshots only.
+//! A new snapshot is written after committing a message which may change it, eg. an announcement
+//! or a role assignment, and once the journal grows to `SNAPSHOT_INTERVAL` records.
+//!
+//! Changed links, cursors and pre-shared keys are noted by their stores as they change, so writing
+//! a journal record costs as much as the changes themselves. Links evicted by the link store on its
+//! own are not noted, they're dropped from the checkpoint with the next snapshot.
+//!
+//! ```ddml
+//! message Sealed {
+//!     absorb u8 version;
+//!     absorb u8 nonce[16];
+//!     absorb bytes name;
+//!     absorb external u8 key[32];
+//!     Content content;
+//!     commit;
+//!     squeeze u8 mac[32];
+//! }
+//!
+//! message Head {
+//!     mask u64 generation;
+//! }
+//!
+//! message JournalRecord {
+//!     absorb repeated(n):
+//!         mask u8 link[12];
+//!         mask u8 spongos[capacity];
+//!         mask u8 info;
+//!     absorb repeated(n):
+//!         mask u8 erased_link[12];
+//!     absorb repeated(n):
+//!         mask u8 pk[32];
+//!         mask u8 link[12];
+//!         mask u32 branch_no;
+//!         mask u32 seq_no;
+//!     absorb repeated(n):
+//!         mask u8 pskid[16];
+//!         mask u8 psk[32];
+//!     absorb repeated(n):
+//!         mask u8 branch_root[12];
+//!         mask u8 keyload[12];
+//!     absorb u8 has_rekey;
+//!     if has_rekey {
+//!         RekeyState rekey;
+//!     }
+//! }
+//! ```
+//!
+//! Storage value name is absorbed into the record, so records can't be swapped in storage.
+
+use core::convert::TryFrom as _;
+
+use iota_streams_app::{
+    message::{
+        ContentSizeof,
+        ContentUnwrap,
+        ContentWrap,
+    },
+    transport::tangle::{
+        MsgId,
+        MsgIdSize,
+    },
+};
+use iota_streams_core::{
+    err,
+    prelude::{
+        Box,
+        HashMap,
+        String,
+        ToString,
+        Vec,
+    },
+    prng,
+    psk,
+    sponge::prp::{
+        Inner,
+        PRP,
+    },
+    try_or,
+    Errors::*,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore as _,
+    },
+    types::*,
+};
+
+use super::{
+    user::UserImp,
+    Cursor,
+    DefaultF,
+    MsgInfo,
+    Psk,
+    PskId,
+    TangleLinkStore,
+};
+use crate::api::{
+    kdf::KdfParams,
+    rekey::RekeyState,
+    pk_store::PublicKeyStore as _,
+    psk_store::PresharedKeyStore as _,
+    storage::Storage,
+};
+
+const VERSION: u8 = 0;
+
+/// Maximal number of journal records written before a new snapshot.
+pub const SNAPSHOT_INTERVAL: u64 = 64;
+
+const KDF_NAME: &str = "checkpoint-kdf";
+const HEAD_NAME: &str = "checkpoint-head";
+
+fn snapshot_name(generation: u64) -> String {
+    format!("checkpoint-snapshot-{:016x}", generation)
+}
+
+fn journal_name(generation: u64, index: u64) -> String {
+    format!("checkpoint-journal-{:016x}-{}", generation, index)
+}
+
+pub(super) type Capacity = <DefaultF as PRP>::CapacitySize;
+pub(super) type NoStore = EmptyLinkStore<DefaultF, MsgId, ()>;
+
+fn get(storage: &dyn Storage, name: &str) -> Result<Vec<u8>> {
+    match storage.get(name)? {
+        Some(bytes) => Ok(bytes),
+        None => err!(CheckpointNotFound(name.to_string())),
+    }
+}
+
+/// Encrypt and authenticate `content` stored under value `name` with `key`.
+pub(super) fn seal<C: ContentWrap<DefaultF, NoStore>>(key: &NBytes<U32>, name: &str, content: &C) -> Result<Vec<u8>> {
+    let nonce = NBytes::<U16>::from(prng::random_nonce());
+    let name = Bytes(name.as_bytes().to_vec());
+    let buf_size = {
+        let mut ctx = sizeof::Context::<DefaultF>::new();
+        ctx.absorb(Uint8(VERSION))?.absorb(&nonce)?.absorb(&name)?;
+        content.sizeof(&mut ctx)?.commit()?.squeeze(Mac(32))?;
+        ctx.get_size()
+    };
+
+    let mut buf = vec![0; buf_size];
+
+    {
+        let mut ctx = wrap::Context::<DefaultF, &mut [u8]>::new(&mut buf[..]);
+        ctx.absorb(Uint8(VERSION))?
+            .absorb(&nonce)?
+            .absorb(&name)?
+            .absorb(External(key))?;
+        content.wrap(&NoStore::default(), &mut ctx)?.commit()?.squeeze(Mac(32))?;
+        try_or!(
+            ctx.stream.is_empty(),
+            OutputStreamNotFullyConsumed(ctx.stream.len())
+        )?;
+    }
+
+    Ok(buf)
+}
+
+/// Decrypt `content` sealed under storage value `name`.
+pub(super) fn unseal<C: ContentUnwrap<DefaultF, NoStore>>(key: &NBytes<U32>, name: &str, bytes: &[u8], content: &mut C) -> Result<()> {
+    let mut ctx = unwrap::Context::new(bytes);
+    let mut version = Uint8(0);
+    let mut nonce = NBytes::<U16>::default();
+    let mut name2 = Bytes::default();
+    ctx.absorb(&mut version)?
+        .guard(version.0 == VERSION, UserVersionRecoveryFailure(VERSION, version.0))?
+        .absorb(&mut nonce)?
+        .absorb(&mut name2)?
+        .guard(name2.0 == name.as_bytes(), CheckpointNotFound(name.to_string()))?
+        .absorb(External(key))?;
+    content.unwrap(&NoStore::default(), &mut ctx)?.commit()?.squeeze(Mac(32))?;
+    try_or!(
+        ctx.stream.is_empty(),
+        InputStreamNotFullyConsumed(ctx.stream.len())
+    )?;
+    Ok(())
+}
+
+/// Encode checkpoint key derivation parameters, they are needed before the key is known.
+fn wrap_kdf(kdf: &KdfParams, salt: &NBytes<U16>) -> Result<Vec<u8>> {
+    let buf_size = {
+        let mut ctx = sizeof::Context::<DefaultF>::new();
+        ctx.absorb(Uint8(VERSION))?
+            .absorb(Uint32(kdf.m_cost))?
+            .absorb(Uint32(kdf.t_cost))?
+            .absorb(Uint32(kdf.p_cost))?
+            .absorb(salt)?;
+        ctx.get_size()
+    };
+
+    let mut buf = vec![0; buf_size];
+
+    {
+        let mut ctx = wrap::Context::<DefaultF, &mut [u8]>::new(&mut buf[..]);
+        ctx.absorb(Uint8(VERSION))?
+            .absorb(Uint32(kdf.m_cost))?
+            .absorb(Uint32(kdf.t_cost))?
+            .absorb(Uint32(kdf.p_cost))?
+            .absorb(salt)?;
+        try_or!(
+            ctx.stream.is_empty(),
+            OutputStreamNotFullyConsumed(ctx.stream.len())
+        )?;
+    }
+
+    Ok(buf)
+}
+
+fn unwrap_kdf(bytes: &[u8]) -> Result<(KdfParams, NBytes<U16>)> {
+    let mut ctx = unwrap::Context::<DefaultF, &[u8]>::new(bytes);
+    let mut version = Uint8(0);
+    let mut m_cost = Uint32(0);
+    let mut t_cost = Uint32(0);
+    let mut p_cost = Uint32(0);
+    let mut salt = NBytes::<U16>::default();
+    ctx.absorb(&mut version)?
+        .guard(version.0 == VERSION, UserVersionRecoveryFailure(VERSION, version.0))?
+        .absorb(&mut m_cost)?
+        .absorb(&mut t_cost)?
+        .absorb(&mut p_cost)?
+        .absorb(&mut salt)?;
+    try_or!(
+        ctx.stream.is_empty(),
+        InputStreamNotFullyConsumed(ctx.stream.len())
+    )?;
+    let kdf = KdfParams {
+        m_cost: m_cost.0,
+        t_cost: t_cost.0,
+        p_cost: p_cost.0,
+    };
+    kdf.check_limits()?;
+    Ok((kdf, salt))
+}
+
+#[derive(Default)]
+struct Head {
+    generation: u64,
+}
+
+impl ContentSizeof<DefaultF> for Head {
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<DefaultF>) -> Result<&'c mut sizeof::Context<DefaultF>> {
+        ctx.mask(&Uint64(self.generation))
+    }
+}
+
+impl ContentWrap<DefaultF, NoStore> for Head {
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        _store: &NoStore,
+        ctx: &'c mut wrap::Context<DefaultF, OS>,
+    ) -> Result<&'c mut wrap::Context<DefaultF, OS>> {
+        ctx.mask(&Uint64(self.generation))
+    }
+}
+
+impl ContentUnwrap<DefaultF, NoStore> for Head {
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        _store: &NoStore,
+        ctx: &'c mut unwrap::Context<DefaultF, IS>,
+    ) -> Result<&'c mut unwrap::Context<DefaultF, IS>> {
+        let mut generation = Uint64(0);
+        ctx.mask(&mut generation)?;
+        self.generation = generation.0;
+        Ok(ctx)
+    }
+}
+
+/// Changes of user state since the last checkpoint.
+#[derive(Default)]
+struct Changes {
+    links: Vec<(MsgId, Inner<DefaultF>, MsgInfo)>,
+    erased: Vec<MsgId>,
+    cursors: Vec<(ed25519::PublicKey, Cursor<MsgId>)>,
+    psks: Vec<(PskId, Psk)>,
+    keyloads: Vec<(MsgId, MsgId)>,
+    rekey: Option<RekeyState<MsgId>>,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.links.is_empty()
+            && self.erased.is_empty()
+            && self.cursors.is_empty()
+            && self.psks.is_empty()
+            && self.keyloads.is_empty()
+            && self.rekey.is_none()
+    }
+
+    /// Whether committed messages may have changed state which is written with snapshots only.
+    fn needs_snapshot(&self) -> bool {
+        self.links.iter().any(|(_, _, info)| {
+            !matches!(
+                info,
+                MsgInfo::Keyload
+                    | MsgInfo::KeyloadPart
+                    | MsgInfo::PrivateKeyload
+                    | MsgInfo::SignedPacket
+                    | MsgInfo::TaggedPacket
+                    | MsgInfo::DirectMessage
+                    | MsgInfo::Sequence
+            )
+        })
+    }
+
+    fn apply<LS: TangleLinkStore>(&self, user: &mut UserImp<LS>) -> Result<()> {
+        {
+            let mut link_store = user.link_store.borrow_mut();
+            for (link, inner, info) in &self.links {
+                link_store.insert(link, inner.clone(), *info)?;
+            }
+            for link in &self.erased {
+                link_store.try_erase(link)?;
+            }
+        }
+        for (pk, cursor) in &self.cursors {
+            user.pk_store.insert(*pk, cursor.clone())?;
+        }
+        for (pskid, psk) in &self.psks {
+            user.psk_store.insert(*pskid, *psk);
+        }
+        for (root, keyload) in &self.keyloads {
+            user.latest_keyloads.insert(root.clone(), keyload.clone());
+        }
+        if let Some(ref rekey) = self.rekey {
+            user.rekey = rekey.clone();
+        }
+        Ok(())
+    }
+}
+
+impl ContentSizeof<DefaultF> for Changes {
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<DefaultF>) -> Result<&'c mut sizeof::Context<DefaultF>> {
+        ctx.absorb(Size(self.links.len()))?
+            .repeated(self.links.iter(), |ctx, (link, inner, info)| {
+                ctx.mask(<&NBytes<MsgIdSize>>::from(link.as_ref()))?
+                    .mask(<&NBytes<Capacity>>::from(inner.arr()))?
+                    .mask(&Uint8(info.into()))
+            })?
+            .absorb(Size(self.erased.len()))?
+            .repeated(self.erased.iter(), |ctx, link| {
+                ctx.mask(<&NBytes<MsgIdSize>>::from(link.as_ref()))
+            })?
+            .absorb(Size(self.cursors.len()))?
+            .repeated(self.cursors.iter(), |ctx, (pk, cursor)| {
+                ctx.mask(pk)?
+                    .mask(<&NBytes<MsgIdSize>>::from(cursor.link.as_ref()))?
+                    .mask(&Uint32(cursor.branch_no))?
+                    .mask(&Uint32(cursor.seq_no))
+            })?
+            .absorb(Size(self.psks.len()))?
+            .repeated(self.psks.iter(), |ctx, (pskid, psk)| {
+                ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?
+                    .mask(<&NBytes<psk::PskSize>>::from(psk))
+            })?
+            .absorb(Size(self.keyloads.len()))?
+            .repeated(self.keyloads.iter(), |ctx, (root, keyload)| {
+                ctx.mask(<&NBytes<MsgIdSize>>::from(root.as_ref()))?
+                    .mask(<&NBytes<MsgIdSize>>::from(keyload.as_ref()))
+            })?
+            .absorb(Uint8(self.rekey.is_some() as u8))?;
+        if let Some(ref rekey) = self.rekey {
+            rekey.sizeof(ctx)?;
+        }
+        Ok(ctx)
+    }
+}
+
+impl ContentWrap<DefaultF, NoStore> for Changes {
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        _store: &NoStore,
+        ctx: &'c mut wrap::Context<DefaultF, OS>,
+    ) -> Result<&'c mut wrap::Context<DefaultF, OS>> {
+        ctx.absorb(Size(self.links.len()))?
+            .repeated(self.links.iter(), |ctx, (link, inner, info)| {
+                ctx.mask(<&NBytes<MsgIdSize>>::from(link.as_ref()))?
+                    .mask(<&NBytes<Capacity>>::from(inner.arr()))?
+                    .mask(&Uint8(info.into()))
+            })?
+            .absorb(Size(self.erased.len()))?
+            .repeated(self.erased.iter(), |ctx, link| {
+                ctx.mask(<&NBytes<MsgIdSize>>::from(link.as_ref()))
+            })?
+            .absorb(Size(self.cursors.len()))?
+            .repeated(self.cursors.iter(), |ctx, (pk, cursor)| {
+                ctx.mask(pk)?
+                    .mask(<&NBytes<MsgIdSize>>::from(cursor.link.as_ref()))?
+                    .mask(&Uint32(cursor.branch_no))?
+                    .mask(&Uint32(cursor.seq_no))
+            })?
+            .absorb(Size(self.psks.len()))?
+            .repeated(self.psks.iter(), |ctx, (pskid, psk)| {
+                ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?
+                    .mask(<&NBytes<psk::PskSize>>::from(psk))
+            })?
+            .absorb(Size(self.keyloads.len()))?
+            .repeated(self.keyloads.iter(), |ctx, (root, keyload)| {
+                ctx.mask(<&NBytes<MsgIdSize>>::from(root.as_ref()))?
+                    .mask(<&NBytes<MsgIdSize>>::from(keyload.as_ref()))
+            })?
+            .absorb(Uint8(self.rekey.is_some() as u8))?;
+        if let Some(ref rekey) = self.rekey {
+            rekey.wrap(_store, ctx)?;
+        }
+        Ok(ctx)
+    }
+}
+
+impl ContentUnwrap<DefaultF, NoStore> for Changes {
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        _store: &NoStore,
+        ctx: &'c mut unwrap::Context<DefaultF, IS>,
+    ) -> Result<&'c mut unwrap::Context<DefaultF, IS>> {
+        let mut repeated_links = Size(0);
+        let mut repeated_erased = Size(0);
+        let mut repeated_cursors = Size(0);
+        let mut repeated_psks = Size(0);
+        let mut repeated_keyloads = Size(0);
+        let mut has_rekey = Uint8(0);
+        ctx.absorb(&mut repeated_links)?
+            .repeated(repeated_links, |ctx| {
+                let mut link = NBytes::<MsgIdSize>::default();
+                let mut inner = NBytes::<Capacity>::default();
+                let mut info = Uint8(0);
+                ctx.mask(&mut link)?.mask(&mut inner)?.mask(&mut info)?;
+                match MsgInfo::try_from(info.0) {
+                    Ok(i) => self.links.push((MsgId::from(link), Inner::from(inner.0), i)),
+                    Err(()) => return err!(BadMessageInfo(info.0)),
+                }
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_erased)?
+            .repeated(repeated_erased, |ctx| {
+                let mut link = NBytes::<MsgIdSize>::default();
+                ctx.mask(&mut link)?;
+                self.erased.push(MsgId::from(link));
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_cursors)?
+            .repeated(repeated_cursors, |ctx| {
+                let mut pk = ed25519::PublicKey::default();
+                let mut link = NBytes::<MsgIdSize>::default();
+                let mut branch_no = Uint32(0);
+                let mut seq_no = Uint32(0);
+                ctx.mask(&mut pk)?
+                    .mask(&mut link)?
+                    .mask(&mut branch_no)?
+                    .mask(&mut seq_no)?;
+                self.cursors.push((pk, Cursor::new_at(MsgId::from(link), branch_no.0, seq_no.0)));
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_psks)?
+            .repeated(repeated_psks, |ctx| {
+                let mut pskid = NBytes::<psk::PskIdSize>::default();
+                let mut psk = NBytes::<psk::PskSize>::default();
+                ctx.mask(&mut pskid)?.mask(&mut psk)?;
+                self.psks.push((pskid.0, psk.0));
+                Ok(ctx)
+            })?
+            .absorb(&mut repeated_keyloads)?
+            .repeated(repeated_keyloads, |ctx| {
+                let mut root = NBytes::<MsgIdSize>::default();
+                let mut keyload = NBytes::<MsgIdSize>::default();
+                ctx.mask(&mut root)?.mask(&mut keyload)?;
+                self.keyloads.push((MsgId::from(root), MsgId::from(keyload)));
+                Ok(ctx)
+            })?
+            .absorb(&mut has_rekey)?;
+        if has_rekey.0 != 0 {
+            let mut rekey = RekeyState::default();
+            rekey.unwrap(_store, ctx)?;
+            self.rekey = Some(rekey);
+        }
+        Ok(ctx)
+    }
+}
+
+/// Latest keyloads and rekey progress as of the last checkpoint, there is at most one keyload
+/// per branch. Changes of links, sequencing cursors and pre-shared keys are noted by their stores instead.
+#[derive(Default)]
+struct State {
+    keyloads: HashMap<MsgId, MsgId>,
+    rekey: RekeyState<MsgId>,
+}
+
+impl State {
+    fn of<LS: TangleLinkStore>(user: &UserImp<LS>) -> Self {
+        Self {
+            keyloads: user.latest_keyloads.clone(),
+            rekey: user.rekey.clone(),
+        }
+    }
+
+    fn changes<LS: TangleLinkStore>(&self, user: &UserImp<LS>) -> Result<Changes> {
+        let mut changes = Changes::default();
+        for (link, value) in user.link_store.borrow().changed()? {
+            match value {
+                Some((inner, info)) => changes.links.push((link, inner, info)),
+                None => changes.erased.push(link),
+            }
+        }
+        for (pk, cursor) in user.pk_store.changed() {
+            changes.cursors.push((*pk, cursor.clone()));
+        }
+        for (pskid, psk) in user.psk_store.changed() {
+            changes.psks.push((*pskid, *psk));
+        }
+        for (root, keyload) in user.latest_keyloads.iter() {
+            if self.keyloads.get(root) != Some(keyload) {
+                changes.keyloads.push((root.clone(), keyload.clone()));
+            }
+        }
+        if self.rekey != user.rekey {
+            changes.rekey = Some(user.rekey.clone());
+        }
+        Ok(changes)
+    }
+}
+
+/// Forget the changes noted by the stores once they are written.
+fn clear_changes<LS: TangleLinkStore>(user: &mut UserImp<LS>) {
+    user.link_store.get_mut().clear_changed();
+    user.pk_store.clear_changed();
+    user.psk_store.clear_changed();
+}
+
+/// Checkpoint of user state in storage.
+pub(crate) struct Checkpoint {
+    storage: Box<dyn Storage>,
+    key: NBytes<U32>,
+    flag: u8,
+    generation: u64,
+    next: u64,
+    state: State,
+    failed: bool,
+}
+
+impl Checkpoint {
+    /// Start checkpointing `user` in `storage`, checkpoint key is derived from `pwd` with a new salt.
+    pub(crate) fn create<LS: TangleLinkStore>(mut storage: Box<dyn Storage>, flag: u8, pwd: &str, kdf: &KdfParams, user: &mut UserImp<LS>) -> Result<Self> {
+        let salt = NBytes::<U16>::from(prng::random_nonce());
+        let key = kdf.derive_key(pwd, salt.as_slice())?;
+        storage.put(KDF_NAME, &wrap_kdf(kdf, &salt)?)?;
+        let mut checkpoint = Self {
+            storage,
+            key,
+            flag,
+            generation: 0,
+            next: 0,
+            state: State::default(),
+            failed: false,
+        };
+        user.link_store.get_mut().track_changes();
+        checkpoint.write_snapshot(user)?;
+        Ok(checkpoint)
+    }
+
+    /// Restore user from the snapshot and journal records in `storage`.
+    pub(crate) fn open<LS: TangleLinkStore>(storage: Box<dyn Storage>, flag: u8, pwd: &str) -> Result<(Self, UserImp<LS>)> {
+        let (kdf, salt) = unwrap_kdf(&get(&*storage, KDF_NAME)?)?;
+        let key = kdf.derive_key(pwd, salt.as_slice())?;
+        let mut head = Head::default();
+        unseal(&key, HEAD_NAME, &get(&*storage, HEAD_NAME)?, &mut head)?;
+        let snapshot = get(&*storage, &snapshot_name(head.generation))?;
+        let mut user = UserImp::<LS>::import_with_key(&snapshot, flag, &key)?;
+
+        let mut next = 0;
+        loop {
+            let name = journal_name(head.generation, next);
+            match storage.get(&name)? {
+                Some(bytes) => {
+                    let mut changes = Changes::default();
+                    unseal(&key, &name, &bytes, &mut changes)?;
+                    changes.apply(&mut user)?;
+                    next += 1;
+                }
+                None => break,
+            }
+        }
+
+        user.pin_essential_links();
+        user.link_store.get_mut().track_changes();
+        clear_changes(&mut user);
+        let state = State::of(&user);
+        let checkpoint = Self {
+            storage,
+            key,
+            flag,
+            generation: head.generation,
+            next,
+            state,
+            failed: false,
+        };
+        Ok((checkpoint, user))
+    }
+
+    /// Write the changes made since the last checkpoint as a journal record, or a new snapshot
+    /// if needed. The changes are kept until they are written, so nothing is lost if storage fails.
+    pub(crate) fn update<LS: TangleLinkStore>(&mut self, user: &mut UserImp<LS>) -> Result<()> {
+        let result = self.try_update(user);
+        self.failed = result.is_err();
+        result
+    }
+
+    fn try_update<LS: TangleLinkStore>(&mut self, user: &mut UserImp<LS>) -> Result<()> {
+        let changes = self.state.changes(user)?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+        if changes.needs_snapshot() || SNAPSHOT_INTERVAL <= self.next + 1 {
+            return self.snapshot(user);
+        }
+
+        let name = journal_name(self.generation, self.next);
+        self.storage.put(&name, &seal(&self.key, &name, &changes)?)?;
+        self.next += 1;
+        self.state = State::of(user);
+        clear_changes(user);
+        Ok(())
+    }
+
+    /// Write a new snapshot and remove the previous one with its journal.
+    pub(crate) fn snapshot<LS: TangleLinkStore>(&mut self, user: &mut UserImp<LS>) -> Result<()> {
+        let (generation, next) = (self.generation, self.next);
+        let result = self.write_snapshot(user);
+        self.failed = result.is_err();
+        result?;
+        // Previous checkpoint is not referenced by the head anymore, a failure to remove it
+        // leaves unused values in storage only.
+        let _ = self.storage.remove(&snapshot_name(generation));
+        for index in 0..next {
+            let _ = self.storage.remove(&journal_name(generation, index));
+        }
+        Ok(())
+    }
+
+    /// Whether the last checkpoint failed to be written, the changes are written with the next one.
+    pub(crate) fn failed(&self) -> bool {
+        self.failed
+    }
+
+    fn write_snapshot<LS: TangleLinkStore>(&mut self, user: &mut UserImp<LS>) -> Result<()> {
+        let nonce = prng::random_nonce();
+        let mut bytes = [0_u8; 8];
+        bytes.copy_from_slice(&nonce[..8]);
+        let generation = u64::from_le_bytes(bytes);
+
+        let snapshot = user.export_with_key(self.flag, &self.key)?;
+        self.storage.put(&snapshot_name(generation), &snapshot)?;
+        self.storage.put(HEAD_NAME, &seal(&self.key, HEAD_NAME, &Head { generation })?)?;
+        self.generation = generation;
+        self.next = 0;
+        self.state = State::of(user);
+        clear_changes(user);
+        Ok(())
+    }
+}
@@ -19,6 +19,15 @@ use iota_streams_app::{
 };
 
 pub use message::Cursor;
+pub use super::kdf::KdfParams;
+pub use message::hdf::{
+    FLAG_ADMINS_MASK,
+    FLAG_ANONYMOUS_KEYLOAD_MASK,
+    FLAG_AUDITORS_MASK,
+    FLAG_BRANCHING_MASK,
+    FLAG_CERTIFIED_PUBLISHERS_MASK,
+    FLAG_RATCHET_MASK,
+};
 // Bring trait methods into scope publicly.
 pub use message::LinkGenerator as _;
 pub use transport::{
@@ -26,9 +35,15 @@ pub use transport::{
     TransportOptions as _,
 };
 
-use iota_streams_core::psk;
+use iota_streams_core::{
+    psk,
+    Result,
+};
 use iota_streams_core_keccak::sponge::prp::keccak::KeccakF1600;
-use iota_streams_ddml::link_store::DefaultLinkStore;
+use iota_streams_ddml::link_store::{
+    self,
+    DefaultLinkStore,
+};
 pub use iota_streams_ddml::types::Bytes;
 
 use iota_streams_core_edsig::signature::ed25519;
@@ -39,6 +54,12 @@ pub type DefaultF = KeccakF1600;
 /// Identifiers for Pre-Shared Keys
 pub type PskIds = psk::PskIds;
 
+/// Identifier of a Pre-Shared Key
+pub type PskId = psk::PskId;
+
+/// Pre-Shared Key
+pub type Psk = psk::Psk;
+
 /// Tangle Address Link type.
 pub type Address = TangleAddress;
 /// Tangle Address representing Channel Application Instance.
@@ -58,6 +79,21 @@ pub type WrapStateSequence = super::user::WrapStateSequence<DefaultF, Address>;
 /// Ed25519 Public Key
 pub type PublicKey = ed25519::PublicKey;
 
+/// Proof disclosing a single signed packet.
+pub type SignedPacketProof = super::proof::SignedPacketProof<DefaultF, Address>;
+
+/// Verify a signed packet proof exported by a channel participant, return packet public
+/// and masked payloads. Any participant can prove their own packets, so the proof is
+/// checked against the publisher the packet is expected from.
+///
+///  # Arguments
+///  * `proof` - Encoded proof
+///  * `publisher` - Public key of the expected publisher, eg. the Author from the announcement
+///
+pub fn verify_signed_packet_proof(proof: &[u8], publisher: &PublicKey) -> Result<(Bytes, Bytes)> {
+    super::proof::verify_signed_packet_proof::<DefaultF, Address>(proof, publisher)
+}
+
 /// Message type with parsed header.
 pub type Preparsed<'a> = message::PreparsedMessage<'a, DefaultF, Address>;
 
@@ -73,6 +109,18 @@ pub type LinkGen = DefaultTangleLinkGenerator<DefaultF>;
 
 /// Link Store.
 pub type LinkStore = DefaultLinkStore<DefaultF, MsgId, MsgInfo>;
+/// Link Store keeping a bounded number of the most recently used links.
+pub type LruLinkStore = link_store::LruLinkStore<DefaultF, MsgId, MsgInfo>;
+/// Link Store evicting links older than a number of messages or an amount of time.
+pub type AgedLinkStore = link_store::AgedLinkStore<DefaultF, MsgId, MsgInfo>;
+
+mod persistent;
+/// Link Store keeping spongos states encrypted in storage.
+pub use persistent::PersistentLinkStore;
+
+/// Link Store a tangle User can be parametrized with.
+pub trait TangleLinkStore: link_store::LinkStore<DefaultF, MsgId, Info = MsgInfo> + Default {}
+impl<T> TangleLinkStore for T where T: link_store::LinkStore<DefaultF, MsgId, Info = MsgInfo> + Default {}
 
 /// Test Transport.
 pub type BucketTransport = transport::BucketTransport<Address, Message>;
@@ -86,6 +134,7 @@ mod msginfo;
 pub use msginfo::MsgInfo;
 
 /// Message body returned as part of handle message routine.
+#[derive(Clone)]
 pub enum MessageContent {
     Announce,
     Keyload,
@@ -101,6 +150,15 @@ pub enum MessageContent {
     Sequence,
     Subscribe,
     Unsubscribe,
+    RoleAssignment,
+    Certificate,
+    AdminSet,
+    DirectMessage {
+        content: Option<(PublicKey, Bytes)>,
+    },
+    Topic {
+        name: Bytes,
+    },
 }
 
 impl MessageContent {
@@ -126,6 +184,26 @@ impl MessageContent {
             masked_payload,
         }
     }
+
+    pub fn new_role_assignment() -> Self {
+        Self::RoleAssignment
+    }
+
+    pub fn new_certificate() -> Self {
+        Self::Certificate
+    }
+
+    pub fn new_admin_set() -> Self {
+        Self::AdminSet
+    }
+
+    pub fn new_direct_message(content: Option<(PublicKey, Bytes)>) -> Self {
+        Self::DirectMessage { content }
+    }
+
+    pub fn new_topic(name: Bytes) -> Self {
+        Self::Topic { name }
+    }
 }
 
 /// Generic unwrapped message type containing possible message contents
@@ -134,6 +212,47 @@ pub type UnwrappedMessage = message::GenericMessage<Address, MessageContent>;
 mod user;
 /// User object storing the Auth/Sub implementation as well as the transport instance
 pub use user::User;
+/// User with a capacity-bounded link store, see `User::link_store_mut` to change the capacity.
+pub type LruUser<Trans> = User<Trans, LruLinkStore>;
+/// User with a link store evicting old links, see `User::link_store_mut` to change the limits.
+pub type AgedUser<Trans> = User<Trans, AgedLinkStore>;
+/// User with links kept in storage, see `PersistentLinkStore::attach`. Exports don't
+/// contain the links kept in attached storage.
+pub type PersistentUser<Trans> = User<Trans, PersistentLinkStore>;
+
+mod transaction;
+/// Link Store wrapper undoing the changes of failed User operations.
+pub use transaction::StagedLinkStore;
+
+mod processed;
+/// Maximal number of handled messages a User recognizes when they are handled again.
+pub use processed::PROCESSED_CAPACITY;
+
+mod checkpoint;
+/// Maximal number of checkpoint journal records written before a new snapshot.
+pub use checkpoint::SNAPSHOT_INTERVAL;
+
+/// Storage backends for user state checkpoints.
+pub use super::storage::{
+    MemoryStorage,
+    Storage,
+};
+#[cfg(feature = "std")]
+pub use super::storage::FileStorage;
+#[cfg(feature = "db-storage")]
+pub use super::storage::DatabaseStorage;
+
+/// Automatic rekeying policy for Author.
+pub use super::rekey::RekeyPolicy;
+
+/// Subscription approval policy for Author.
+pub use super::subscription::SubscriptionPolicy;
+
+/// Permissions of channel participants.
+pub use super::role::Role;
+
+/// Author's statement allowing a key to publish signed packets.
+pub type Certificate = super::certificate::Certificate<MsgId>;
 
 mod author;
 /// Tangle-specific Channel Author type.
@@ -9,8 +9,10 @@ use iota_streams_ddml::{
 use iota_streams_core::{err, LOCATION_LOG, Errors::BadMessageInfo, Result};
 
 /// Message associated info stored internally in User context, just message type indicator.
-#[derive(Copy, Clone)]
+// Default instance is required by ddml unwrap logic as unwrap modifies/updates an existing object rather producing a new one.
+#[derive(Copy, Clone, Default)]
 pub enum MsgInfo {
+    #[default]
     Announce,
     Keyload,
     SignedPacket,
@@ -18,13 +20,15 @@ pub enum MsgInfo {
     Subscribe,
     Unsubscribe,
     Sequence,
-}
-
-// Default instance is required by ddml unwrap logic as unwrap modifies/updates an existing object rather producing a new one.
-impl Default for MsgInfo {
-    fn default() -> Self {
-        MsgInfo::Announce
-    }
+    TreeKeyload,
+    KeyloadPart,
+    RoleAssignment,
+    Certificate,
+    AdminSet,
+    PrivateKeyload,
+    DirectMessage,
+    HiddenAnnounce,
+    Topic,
 }
 
 impl From<MsgInfo> for u8 {
@@ -37,6 +41,15 @@ impl From<MsgInfo> for u8 {
             MsgInfo::Subscribe => 4,
             MsgInfo::Unsubscribe => 5,
             MsgInfo::Sequence => 6,
+            MsgInfo::TreeKeyload => 7,
+            MsgInfo::KeyloadPart => 8,
+            MsgInfo::RoleAssignment => 9,
+            MsgInfo::Certificate => 10,
+            MsgInfo::AdminSet => 11,
+            MsgInfo::PrivateKeyload => 12,
+            MsgInfo::DirectMessage => 13,
+            MsgInfo::HiddenAnnounce => 14,
+            MsgInfo::Topic => 15,
         }
     }
 }
@@ -58,6 +71,15 @@ impl TryFrom<u8> for MsgInfo {
             4 => Ok(MsgInfo::Subscribe),
             5 => Ok(MsgInfo::Unsubscribe),
             6 => Ok(MsgInfo::Sequence),
+            7 => Ok(MsgInfo::TreeKeyload),
+            8 => Ok(MsgInfo::KeyloadPart),
+            9 => Ok(MsgInfo::RoleAssignment),
+            10 => Ok(MsgInfo::Certificate),
+            11 => Ok(MsgInfo::AdminSet),
+            12 => Ok(MsgInfo::PrivateKeyload),
+            13 => Ok(MsgInfo::DirectMessage),
+            14 => Ok(MsgInfo::HiddenAnnounce),
+            15 => Ok(MsgInfo::Topic),
             _ => Err(()),
         }
     }
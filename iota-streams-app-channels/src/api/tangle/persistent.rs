@@ -0,0 +1,218 @@
+//! Link store keeping spongos states in storage encrypted at rest.
+//!
+//! Each link is stored as a separate value `link-<msgid>` sealed with the store key,
+//! see `checkpoint::seal`, eg. in a single `DatabaseStorage` file. Message identifiers
+//! are unique within a channel only, so a storage should not be shared by users of
+//! different channels.
+//!
+//! ```ddml
+//! message LinkValue {
+//!     mask u8 spongos[capacity];
+//!     mask u8 info;
+//! }
+//! ```
+
+use core::convert::TryFrom as _;
+
+use iota_streams_app::{
+    message::{
+        ContentSizeof,
+        ContentUnwrap,
+        ContentWrap,
+    },
+    transport::tangle::MsgId,
+};
+use iota_streams_core::{
+    err,
+    prelude::{
+        Box,
+        HashMap,
+        String,
+        ToString,
+        Vec,
+    },
+    sponge::{
+        prp::Inner,
+        spongos::Spongos,
+    },
+    Errors::*,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::LinkStore,
+    types::*,
+};
+
+use super::{
+    checkpoint::{
+        seal,
+        unseal,
+        Capacity,
+        NoStore,
+    },
+    DefaultF,
+    MsgInfo,
+};
+use crate::api::storage::Storage;
+
+fn value_name(link: &MsgId) -> String {
+    format!("link-{}", link)
+}
+
+#[derive(Default)]
+struct LinkValue {
+    inner: Inner<DefaultF>,
+    info: MsgInfo,
+}
+
+impl ContentSizeof<DefaultF> for LinkValue {
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<DefaultF>) -> Result<&'c mut sizeof::Context<DefaultF>> {
+        ctx.mask(<&NBytes<Capacity>>::from(self.inner.arr()))?
+            .mask(&Uint8(self.info.into()))
+    }
+}
+
+impl ContentWrap<DefaultF, NoStore> for LinkValue {
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        _store: &NoStore,
+        ctx: &'c mut wrap::Context<DefaultF, OS>,
+    ) -> Result<&'c mut wrap::Context<DefaultF, OS>> {
+        ctx.mask(<&NBytes<Capacity>>::from(self.inner.arr()))?
+            .mask(&Uint8(self.info.into()))
+    }
+}
+
+impl ContentUnwrap<DefaultF, NoStore> for LinkValue {
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        _store: &NoStore,
+        ctx: &'c mut unwrap::Context<DefaultF, IS>,
+    ) -> Result<&'c mut unwrap::Context<DefaultF, IS>> {
+        let mut inner = NBytes::<Capacity>::default();
+        let mut info = Uint8(0);
+        ctx.mask(&mut inner)?.mask(&mut info)?;
+        self.inner = Inner::from(inner.0);
+        self.info = match MsgInfo::try_from(info.0) {
+            Ok(i) => i,
+            Err(()) => return err!(BadMessageInfo(info.0)),
+        };
+        Ok(ctx)
+    }
+}
+
+/// Link store writing spongos states to storage, eg. `DatabaseStorage`, so that memory use
+/// doesn't grow with the number of messages.
+///
+/// A default store is detached: links are kept in memory until storage is attached,
+/// this way the store can be used as a `User` link store and filled by `User::import`.
+/// Only links kept in memory are iterated, so exports and checkpoints of a user with
+/// attached store don't contain its links, they are already persisted.
+#[derive(Default)]
+pub struct PersistentLinkStore {
+    storage: Option<(Box<dyn Storage>, NBytes<U32>)>,
+    pending: HashMap<MsgId, (Inner<DefaultF>, MsgInfo)>,
+}
+
+impl PersistentLinkStore {
+    /// Create store attached to `storage`.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend, links already stored there are available
+    ///   * `key` - Key the links are encrypted with
+    ///
+    pub fn new<S: Storage + 'static>(storage: S, key: NBytes<U32>) -> Self {
+        Self {
+            storage: Some((Box::new(storage), key)),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Attach storage and move the links kept in memory to it.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend, links already stored there are available
+    ///   * `key` - Key the links are encrypted with
+    ///
+    pub fn attach<S: Storage + 'static>(&mut self, storage: S, key: NBytes<U32>) -> Result<()> {
+        self.storage = Some((Box::new(storage), key));
+        let pending: Vec<_> = self.pending.drain().collect();
+        for (link, (inner, info)) in pending {
+            self.insert(&link, inner, info)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.storage.is_some()
+    }
+}
+
+impl LinkStore<DefaultF, MsgId> for PersistentLinkStore {
+    type Info = MsgInfo;
+
+    fn lookup(&self, link: &MsgId) -> Result<(Spongos<DefaultF>, MsgInfo)> {
+        match self.storage {
+            Some((ref storage, ref key)) => {
+                let name = value_name(link);
+                match storage.get(&name)? {
+                    Some(bytes) => {
+                        let mut value = LinkValue::default();
+                        unseal(key, &name, &bytes, &mut value)?;
+                        Ok(((&value.inner).into(), value.info))
+                    }
+                    None => err!(MessageLinkNotFoundInTangle(link.to_string())),
+                }
+            }
+            None => match self.pending.get(link) {
+                Some((inner, info)) => Ok((inner.into(), *info)),
+                None => err!(MessageLinkNotFoundInTangle(link.to_string())),
+            },
+        }
+    }
+
+    fn update(&mut self, link: &MsgId, spongos: Spongos<DefaultF>, info: MsgInfo) -> Result<()> {
+        let inner = spongos.to_inner()?;
+        self.insert(link, inner, info)
+    }
+
+    fn insert(&mut self, link: &MsgId, inner: Inner<DefaultF>, info: MsgInfo) -> Result<()> {
+        match self.storage {
+            Some((ref mut storage, ref key)) => {
+                let name = value_name(link);
+                let bytes = seal(key, &name, &LinkValue { inner, info })?;
+                storage.put(&name, &bytes)
+            }
+            None => {
+                self.pending.insert(link.clone(), (inner, info));
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove link, failures to remove it from storage are ignored, see `try_erase`.
+    fn erase(&mut self, link: &MsgId) {
+        let _ = self.try_erase(link);
+    }
+
+    fn try_erase(&mut self, link: &MsgId) -> Result<()> {
+        match self.storage {
+            Some((ref mut storage, _)) => storage.remove(&value_name(link)),
+            None => {
+                self.pending.remove(link);
+                Ok(())
+            }
+        }
+    }
+
+    /// Links kept in memory, none once storage is attached.
+    ///
+    /// Links written to storage are not iterated: `User::prune_link_store` doesn't see them
+    /// and exports leave them out, they are kept by the storage itself.
+    fn iter(&self) -> Vec<(&MsgId, &(Inner<DefaultF>, MsgInfo))> {
+        self.pending.iter().collect()
+    }
+}
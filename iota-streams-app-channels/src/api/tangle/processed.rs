@@ -0,0 +1,176 @@
+//! Results of messages already handled by a tangle User.
+//!
+//! A message is identified by its link together with a hash of its content, so a different
+//! message found at a known link is not mistaken for the processed one. Results are kept in
+//! memory only, they are neither exported nor checkpointed.
+
+use iota_streams_app::transport::tangle::MsgId;
+use iota_streams_core::{
+    prelude::{
+        Box,
+        HashMap,
+        Vec,
+    },
+    sponge::spongos,
+};
+
+use super::{
+    Bytes,
+    Certificate,
+    DefaultF,
+    Message,
+    MessageContent,
+    PublicKey,
+    UnwrappedMessage,
+};
+
+/// Maximal number of processed message results kept by a User.
+pub const PROCESSED_CAPACITY: usize = 256;
+
+type Digest = [u8; 32];
+
+/// Link and content hash of a message.
+pub(crate) struct ProcessedKey(MsgId, Digest);
+
+impl ProcessedKey {
+    pub(crate) fn of(msg: &Message) -> Self {
+        let mut digest = Digest::default();
+        spongos::hash_data::<DefaultF>(&msg.binary.body.bytes, &mut digest);
+        Self(msg.binary.link.msgid.clone(), digest)
+    }
+}
+
+/// Result details returned by the receive methods, but left out of `MessageContent`.
+#[derive(Clone)]
+pub(crate) enum Detail {
+    None,
+
+    /// Whether the user is a recipient of a keyload.
+    Keyload(bool),
+
+    Certificate(Box<Certificate>),
+}
+
+/// Result of a processed message.
+#[derive(Clone)]
+pub(crate) struct Processed {
+    pub(crate) message: UnwrappedMessage,
+    pub(crate) detail: Detail,
+}
+
+impl Processed {
+    pub(crate) fn with_detail(message: UnwrappedMessage, detail: Detail) -> Self {
+        Self { message, detail }
+    }
+
+    // Results returned by the receive methods, `None` if the message is of another type.
+
+    pub(crate) fn announcement(self) -> Option<()> {
+        match self.message.body {
+            MessageContent::Announce => Some(()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn subscribe(self) -> Option<()> {
+        match self.message.body {
+            MessageContent::Subscribe => Some(()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn signed_packet(self) -> Option<(PublicKey, Bytes, Bytes)> {
+        match self.message.body {
+            MessageContent::SignedPacket {
+                pk,
+                public_payload,
+                masked_payload,
+            } => Some((pk, public_payload, masked_payload)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn tagged_packet(self) -> Option<(Bytes, Bytes)> {
+        match self.message.body {
+            MessageContent::TaggedPacket {
+                public_payload,
+                masked_payload,
+            } => Some((public_payload, masked_payload)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn keyload(self) -> Option<bool> {
+        match self.detail {
+            Detail::Keyload(allowed) => Some(allowed),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn role_assignment(self) -> Option<()> {
+        match self.message.body {
+            MessageContent::RoleAssignment => Some(()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn certificate(self) -> Option<Certificate> {
+        match self.detail {
+            Detail::Certificate(certificate) => Some(*certificate),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn admin_set(self) -> Option<()> {
+        match self.message.body {
+            MessageContent::AdminSet => Some(()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn direct_message(self) -> Option<Option<(PublicKey, Bytes)>> {
+        match self.message.body {
+            MessageContent::DirectMessage { content } => Some(content),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn topic(self) -> Option<Bytes> {
+        match self.message.body {
+            MessageContent::Topic { name } => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl From<UnwrappedMessage> for Processed {
+    fn from(message: UnwrappedMessage) -> Self {
+        Self::with_detail(message, Detail::None)
+    }
+}
+
+/// Processed messages results, the oldest results are dropped first.
+#[derive(Default)]
+pub(crate) struct ProcessedMessages {
+    results: HashMap<MsgId, (Digest, Processed)>,
+    order: Vec<MsgId>,
+}
+
+impl ProcessedMessages {
+    pub(crate) fn get(&self, key: &ProcessedKey) -> Option<Processed> {
+        match self.results.get(&key.0) {
+            Some((digest, result)) if *digest == key.1 => Some(result.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: ProcessedKey, result: Processed) {
+        if self.results.insert(key.0.clone(), (key.1, result)).is_none() {
+            self.order.push(key.0);
+            if self.order.len() > PROCESSED_CAPACITY {
+                let oldest = self.order.remove(0);
+                self.results.remove(&oldest);
+            }
+        }
+    }
+}
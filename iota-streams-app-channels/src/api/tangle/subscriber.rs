@@ -10,7 +10,10 @@ use crate::api::tangle::{
 };
 
 use iota_streams_core::prelude::Vec;
-use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_core_edsig::{
+    key_exchange::x25519,
+    signature::ed25519,
+};
 
 /// Subscriber Object. Contains User API.
 pub struct Subscriber<T> {
@@ -57,6 +60,91 @@ impl<Trans> Subscriber<Trans>
         self.user.is_multi_branching()
     }
 
+    /// Return boolean representing whether keyloads hide recipient identities
+    pub fn is_anonymous_keyload(&self) -> bool {
+        self.user.is_anonymous_keyload()
+    }
+
+    /// Return boolean representing whether packets ratchet the session key forward
+    pub fn is_ratchet(&self) -> bool {
+        self.user.is_ratchet()
+    }
+
+    /// Return boolean representing whether signed packets require a certificate issued by Author
+    pub fn is_certified_publishers(&self) -> bool {
+        self.user.is_certified_publishers()
+    }
+
+    /// Return boolean representing whether channel address and announcement are hidden with a pre-shared key
+    pub fn is_hidden(&self) -> bool {
+        self.user.is_hidden()
+    }
+
+    /// Fetch the Address of the topic root message, topic keyloads are attached to it.
+    ///
+    ///   # Arguments
+    ///   * `name` - Topic name
+    ///
+    pub fn topic(&self, name: &Bytes) -> Option<Address> {
+        self.user.topic(name)
+    }
+
+    /// Fetch the names of the known topics.
+    pub fn topics(&self) -> Vec<Bytes> {
+        self.user.topics()
+    }
+
+    /// Follow only the given topics, `fetch_next_msgs` skips messages of other topics
+    /// without fetching them. Messages outside of topic branches are always fetched. Topics
+    /// require a multi-branching channel, so this fails once registered to a single-branch channel.
+    ///
+    ///   # Arguments
+    ///   * `names` - Names of the followed topics
+    ///
+    pub fn follow_topics(&mut self, names: Vec<Bytes>) -> Result<()> {
+        self.user.follow_topics(names)
+    }
+
+    /// Follow all the topics.
+    pub fn follow_all_topics(&mut self) {
+        self.user.follow_all_topics()
+    }
+
+    /// Store a pre-shared key. Hidden announcement can only be received with the key it's masked with.
+    ///
+    ///   # Arguments
+    ///   * `pskid` - Identifier of the pre-shared key
+    ///   * `psk` - Pre-shared key
+    ///
+    pub fn store_psk(&mut self, pskid: PskId, psk: Psk) {
+        self.user.store_psk(pskid, psk)
+    }
+
+    /// Fetch the role assigned to a participant by the last processed role assignment.
+    pub fn role(&self, pk: &ed25519::PublicKey) -> Option<Role> {
+        self.user.role(pk)
+    }
+
+    /// Fetch the latest certificate Author issued for a publisher.
+    pub fn certificate(&self, pk: &ed25519::PublicKey) -> Option<&Certificate> {
+        self.user.certificate(pk)
+    }
+
+    /// Fetch administrators of the channel besides Author.
+    pub fn admins(&self) -> &[ed25519::PublicKey] {
+        self.user.admins()
+    }
+
+    /// Fetch auditors announced by Author, every keyload is shared with them.
+    pub fn auditors(&self) -> (&[ed25519::PublicKey], &[PskId]) {
+        self.user.auditors()
+    }
+
+    /// Return boolean representing whether the subscriber is a channel administrator
+    pub fn is_admin(&self) -> bool {
+        self.user.is_admin()
+    }
+
     /// Stores the provided link to the internal sequencing state for the provided participant
     /// [Used for multi-branching sequence state updates]
     ///
@@ -66,7 +154,7 @@ impl<Trans> Subscriber<Trans>
     ///
     pub fn store_state(&mut self, pk: ed25519::PublicKey, link: &Address) -> Result<()> {
         // TODO: assert!(link.appinst == self.appinst.unwrap());
-        Ok(self.user.store_state(pk, link)?)
+        self.user.store_state(pk, link)
     }
 
     /// Stores the provided link and sequence number to the internal sequencing state for all participants
@@ -78,7 +166,7 @@ impl<Trans> Subscriber<Trans>
     ///
     pub fn store_state_for_all(&mut self, link: &Address, seq_num: u32) -> Result<()> {
         // TODO: assert!(link.appinst == self.appinst.unwrap());
-        Ok(self.user.store_state_for_all(link, seq_num)?)
+        self.user.store_state_for_all(link, seq_num)
     }
 
     /// Generate a vector containing the next sequenced message identifier for each publishing
@@ -100,6 +188,36 @@ impl<Trans> Subscriber<Trans>
         self.user.export(1, pwd)
     }
 
+    /// Serialize user state and encrypt it with a key derived from password with the given
+    /// Argon2id parameters, they are stored with the state.
+    ///
+    ///   # Arguments
+    ///   * `pwd` - Encryption password
+    ///   * `kdf` - Argon2id memory, time and parallelism costs
+    ///
+    pub fn export_with_kdf(&self, pwd: &str, kdf: &KdfParams) -> Result<Vec<u8>> {
+        self.user.export_with_kdf(1, pwd, kdf)
+    }
+
+    /// Erase link store entries not needed to continue in the channel: the announcement,
+    /// branch roots, latest keyload of each branch and messages referenced by sequencing states
+    /// are kept. Messages linked to erased entries can't be received anymore.
+    ///
+    /// Return the number of erased entries.
+    pub fn prune_link_store(&mut self) -> Result<usize> {
+        self.user.prune_link_store()
+    }
+
+    /// Prune the link store, then serialize subscriber state and encrypt it with password.
+    ///
+    ///   # Arguments
+    ///   * `pwd` - Encryption password
+    ///
+    /// Return the serialized state and the number of erased link store entries.
+    pub fn export_compact(&mut self, pwd: &str) -> Result<(Vec<u8>, usize)> {
+        self.user.export_compact(1, pwd)
+    }
+
     /// Deserialize user state and decrypt it with password.
     ///
     ///   # Arguments
@@ -110,6 +228,75 @@ impl<Trans> Subscriber<Trans>
     pub fn import(bytes: &[u8], pwd: &str, tsp: Trans) -> Result<Self> {
         User::<Trans>::import(bytes, 1, pwd, tsp).map(|user| Self { user })
     }
+
+    /// Serialize user state and encrypt it to a recipient public key, eg. for a backup escrowed
+    /// to an organizational recovery key.
+    ///
+    ///   # Arguments
+    ///   * `ke_pk` - X25519 public key of the recipient
+    ///
+    pub fn export_to(&self, ke_pk: &x25519::PublicKey) -> Result<Vec<u8>> {
+        self.user.export_to(1, ke_pk)
+    }
+
+    /// Deserialize user state encrypted with `export_to` and decrypt it with the recipient secret key.
+    ///
+    ///   # Arguments
+    ///   * `bytes` - Encrypted serialized user state
+    ///   * `ke_sk` - X25519 secret key of the recipient
+    ///   * `tsp` - Transport object
+    ///
+    pub fn import_with(bytes: &[u8], ke_sk: &x25519::StaticSecret, tsp: Trans) -> Result<Self> {
+        User::<Trans>::import_with(bytes, 1, ke_sk, tsp).map(|user| Self { user })
+    }
+
+    /// Write user state to `storage` and keep it up to date, an encrypted checkpoint is written
+    /// after each sent or received message. Resume the subscriber with `open`.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend, eg. `FileStorage`
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///
+    pub fn attach_storage<S: Storage + 'static>(&mut self, storage: S, pwd: &str) -> Result<()> {
+        self.user.attach_storage(storage, 1, pwd, &KdfParams::default())
+    }
+
+    /// Attach storage with checkpoint key derived with the given Argon2id parameters.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///   * `kdf` - Argon2id memory, time and parallelism costs
+    ///
+    pub fn attach_storage_with_kdf<S: Storage + 'static>(&mut self, storage: S, pwd: &str, kdf: &KdfParams) -> Result<()> {
+        self.user.attach_storage(storage, 1, pwd, kdf)
+    }
+
+    /// Resume subscriber from the checkpoint in storage.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend the checkpoint was written to
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///   * `tsp` - Transport object
+    ///
+    pub fn open<S: Storage + 'static>(storage: S, pwd: &str, tsp: Trans) -> Result<Self> {
+        User::<Trans>::open(storage, 1, pwd, tsp).map(|user| Self { user })
+    }
+
+    /// Write the pending changes of link store, sequencing states and pre-shared keys to storage.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        self.user.checkpoint()
+    }
+
+    /// Write a snapshot of the whole subscriber state to storage.
+    pub fn checkpoint_snapshot(&mut self) -> Result<()> {
+        self.user.checkpoint_snapshot()
+    }
+
+    /// Whether the last checkpoint failed to be written, call `checkpoint` to retry.
+    pub fn checkpoint_failed(&self) -> bool {
+        self.user.checkpoint_failed()
+    }
 }
 
 #[cfg(not(feature = "async"))]
@@ -124,6 +311,71 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.send_subscribe(link_to)
     }
 
+    /// Create and Send a Subscribe message handled by a channel administrator instead of Author.
+    ///
+    /// # Arguments
+    /// * `link_to` - Address of the Channel Announcement message
+    /// * `admin_pk` - ed25519 Public Key of the administrator
+    ///
+    pub fn send_subscribe_to(&mut self, link_to: &Address, admin_pk: &ed25519::PublicKey) -> Result<Address> {
+        self.user.send_subscribe_to(link_to, admin_pk)
+    }
+
+    /// Create and send a new keyload for a list of subscribers [Admin].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `psk_ids` - Vector of Pre-shared key ids to be included in message
+    ///  * `ke_pks`  - Vector of Public Keys to be included in message
+    ///
+    pub fn send_keyload(
+        &mut self,
+        link_to: &Address,
+        psk_ids: &PskIds,
+        ke_pks: &[ed25519::PublicKey],
+    ) -> Result<(Address, Option<Address>)> {
+        self.user.send_keyload(link_to, psk_ids, ke_pks)
+    }
+
+    /// Create and send keyload for all the subscribers known to the administrator [Admin].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///
+    pub fn send_keyload_for_everyone(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_keyload_for_everyone(link_to)
+    }
+
+    /// Create and send a private keyload, messages linked to it are readable by the given
+    /// participants only. Recipients must be known to the subscriber, eg. from a keyload.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `ke_pks`  - Vector of Public Keys to be included in message
+    ///
+    pub fn send_private_keyload(&mut self, link_to: &Address, ke_pks: &[ed25519::PublicKey]) -> Result<(Address, Option<Address>)> {
+        self.user.send_private_keyload(link_to, ke_pks)
+    }
+
+    /// Create and send a direct message, the payload is readable by Author only.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the direct message will be attached to
+    ///  * `masked_payload` - Payload encrypted for Author
+    ///
+    pub fn send_direct_message(&mut self, link_to: &Address, masked_payload: &Bytes) -> Result<(Address, Option<Address>)> {
+        self.user.send_direct_message(link_to, masked_payload)
+    }
+
+    /// Receive and process a subscribe message sent to the administrator [Admin].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_subscribe(&mut self, link: &Address) -> Result<()> {
+        self.user.receive_subscribe(link)
+    }
+
     /// Create and send a signed packet.
     ///
     ///  # Arguments
@@ -171,7 +423,7 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_announcement(link)
     }
 
-    /// Receive and process a keyload message.
+    /// Receive and process a keyload message, including private keyloads of other subscribers.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
@@ -180,6 +432,60 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_keyload(link)
     }
 
+    /// Receive and process a tree keyload message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_tree_keyload(&mut self, link: &Address) -> Result<bool> {
+        self.user.receive_tree_keyload(link)
+    }
+
+    /// Receive and process a role assignment message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_role_assignment(&mut self, link: &Address) -> Result<()> {
+        self.user.receive_role_assignment(link)
+    }
+
+    /// Receive and process a certificate message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_certificate(&mut self, link: &Address) -> Result<Certificate> {
+        self.user.receive_certificate(link)
+    }
+
+    /// Receive and process an admin set message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_admin_set(&mut self, link: &Address) -> Result<()> {
+        self.user.receive_admin_set(link)
+    }
+
+    /// Receive a direct message, subscribers can't read it and always get `None`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_direct_message(&mut self, link: &Address) -> Result<Option<(ed25519::PublicKey, Bytes)>> {
+        self.user.receive_direct_message(link)
+    }
+
+    /// Receive and process a topic declaration, returns the topic name.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_topic(&mut self, link: &Address) -> Result<Bytes> {
+        self.user.receive_topic(link)
+    }
+
     /// Receive and process a signed packet message.
     ///
     ///  # Arguments
@@ -189,6 +495,16 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_signed_packet(link)
     }
 
+    /// Export a proof of a received signed packet, it discloses the packet to an outside party
+    /// without sharing the state. Proof is checked with `verify_signed_packet_proof`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the signed packet
+    ///
+    pub fn export_signed_packet_proof(&mut self, link: &Address) -> Result<Vec<u8>> {
+        self.user.export_signed_packet_proof(link)
+    }
+
     /// Receive and process a tagged packet message.
     ///
     ///  # Arguments
@@ -213,7 +529,8 @@ impl<Trans: Transport> Subscriber<Trans>
     }
 
     /// Receive and process a message of unknown type. Message will be handled appropriately and
-    /// the unwrapped contents returned
+    /// the unwrapped contents returned. Receiving a processed message again returns the same
+    /// contents, so failed calls can be safely retried.
     ///
     ///   # Arguments
     ///   * `link` - Address of the message to be processed
@@ -223,6 +540,16 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_message(link)
     }
 
+    /// Unwrap a message of unknown type like `receive_msg` without processing it, the link
+    /// store and sequencing states are left as they are.
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be previewed
+    ///
+    pub fn preview_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        self.user.peek_message(link)
+    }
+
 }
 
 #[cfg(feature = "async")]
@@ -237,6 +564,71 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.send_subscribe(link_to).await
     }
 
+    /// Create and Send a Subscribe message handled by a channel administrator instead of Author.
+    ///
+    /// # Arguments
+    /// * `link_to` - Address of the Channel Announcement message
+    /// * `admin_pk` - ed25519 Public Key of the administrator
+    ///
+    pub async fn send_subscribe_to(&mut self, link_to: &Address, admin_pk: &ed25519::PublicKey) -> Result<Address> {
+        self.user.send_subscribe_to(link_to, admin_pk).await
+    }
+
+    /// Create and send a new keyload for a list of subscribers [Admin].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `psk_ids` - Vector of Pre-shared key ids to be included in message
+    ///  * `ke_pks`  - Vector of Public Keys to be included in message
+    ///
+    pub async fn send_keyload(
+        &mut self,
+        link_to: &Address,
+        psk_ids: &PskIds,
+        ke_pks: &[ed25519::PublicKey],
+    ) -> Result<(Address, Option<Address>)> {
+        self.user.send_keyload(link_to, psk_ids, ke_pks).await
+    }
+
+    /// Create and send keyload for all the subscribers known to the administrator [Admin].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///
+    pub async fn send_keyload_for_everyone(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_keyload_for_everyone(link_to).await
+    }
+
+    /// Create and send a private keyload, messages linked to it are readable by the given
+    /// participants only. Recipients must be known to the subscriber, eg. from a keyload.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `ke_pks`  - Vector of Public Keys to be included in message
+    ///
+    pub async fn send_private_keyload(&mut self, link_to: &Address, ke_pks: &[ed25519::PublicKey]) -> Result<(Address, Option<Address>)> {
+        self.user.send_private_keyload(link_to, ke_pks).await
+    }
+
+    /// Create and send a direct message, the payload is readable by Author only.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the direct message will be attached to
+    ///  * `masked_payload` - Payload encrypted for Author
+    ///
+    pub async fn send_direct_message(&mut self, link_to: &Address, masked_payload: &Bytes) -> Result<(Address, Option<Address>)> {
+        self.user.send_direct_message(link_to, masked_payload).await
+    }
+
+    /// Receive and process a subscribe message sent to the administrator [Admin].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_subscribe(&mut self, link: &Address) -> Result<()> {
+        self.user.receive_subscribe(link).await
+    }
+
     /// Create and send a signed packet.
     ///
     ///  # Arguments
@@ -284,7 +676,7 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_announcement(link).await
     }
 
-    /// Receive and process a keyload message.
+    /// Receive and process a keyload message, including private keyloads of other subscribers.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
@@ -293,6 +685,60 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_keyload(link).await
     }
 
+    /// Receive and process a tree keyload message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_tree_keyload(&mut self, link: &Address) -> Result<bool> {
+        self.user.receive_tree_keyload(link).await
+    }
+
+    /// Receive and process a role assignment message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_role_assignment(&mut self, link: &Address) -> Result<()> {
+        self.user.receive_role_assignment(link).await
+    }
+
+    /// Receive and process a certificate message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_certificate(&mut self, link: &Address) -> Result<Certificate> {
+        self.user.receive_certificate(link).await
+    }
+
+    /// Receive and process an admin set message.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_admin_set(&mut self, link: &Address) -> Result<()> {
+        self.user.receive_admin_set(link).await
+    }
+
+    /// Receive a direct message, subscribers can't read it and always get `None`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_direct_message(&mut self, link: &Address) -> Result<Option<(ed25519::PublicKey, Bytes)>> {
+        self.user.receive_direct_message(link).await
+    }
+
+    /// Receive and process a topic declaration, returns the topic name.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_topic(&mut self, link: &Address) -> Result<Bytes> {
+        self.user.receive_topic(link).await
+    }
+
     /// Receive and process a signed packet message.
     ///
     ///  # Arguments
@@ -302,6 +748,16 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_signed_packet(link).await
     }
 
+    /// Export a proof of a received signed packet, it discloses the packet to an outside party
+    /// without sharing the state. Proof is checked with `verify_signed_packet_proof`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the signed packet
+    ///
+    pub async fn export_signed_packet_proof(&mut self, link: &Address) -> Result<Vec<u8>> {
+        self.user.export_signed_packet_proof(link).await
+    }
+
     /// Receive and process a tagged packet message.
     ///
     ///  # Arguments
@@ -326,7 +782,8 @@ impl<Trans: Transport> Subscriber<Trans>
     }
 
     /// Receive and process a message of unknown type. Message will be handled appropriately and
-    /// the unwrapped contents returned
+    /// the unwrapped contents returned. Receiving a processed message again returns the same
+    /// contents, so failed calls can be safely retried.
     ///
     ///   # Arguments
     ///   * `link` - Address of the message to be processed
@@ -336,6 +793,16 @@ impl<Trans: Transport> Subscriber<Trans>
         self.user.receive_message(link).await
     }
 
+    /// Unwrap a message of unknown type like `receive_msg` without processing it, the link
+    /// store and sequencing states are left as they are.
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be previewed
+    ///
+    pub async fn preview_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        self.user.peek_message(link).await
+    }
+
 }
 
 impl<T: Transport> fmt::Display for Subscriber<T> {
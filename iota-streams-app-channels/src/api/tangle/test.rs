@@ -11,9 +11,17 @@ use iota_streams_app::{
 use iota_streams_core::{
     prelude::string::ToString,
     println,
-    try_or,
     Result,
     ensure,
+};
+#[cfg(not(feature = "async"))]
+use iota_streams_core::{
+    prelude::{
+        Box,
+        HashMap,
+        Vec,
+    },
+    try_or,
     LOCATION_LOG,
     Errors::*,
 };
@@ -63,7 +71,7 @@ pub fn example<T: Transport + Clone>(transport: T) -> Result<()>
         ensure!(
             subscriberA
                 .channel_address()
-                .map_or(false, |appinst| appinst == announcement_link.base()),
+                .is_some_and(|appinst| appinst == announcement_link.base()),
             "bad announcement address"
         );
     }
@@ -188,7 +196,7 @@ pub async fn example<T: Transport + Clone>(transport: T) -> Result<()> where
         ensure!(
             subscriberA
                 .channel_address()
-                .map_or(false, |appinst| appinst == announcement_link.base()),
+                .is_some_and(|appinst| appinst == announcement_link.base()),
             "bad announcement address"
         );
     }
@@ -278,3 +286,1972 @@ fn run_basic_scenario() {
         crate::api::tangle::BucketTransport::new());
     assert!(dbg!(smol::block_on(example(transport))).is_ok());
 }
+
+#[cfg(not(feature = "async"))]
+pub fn example_anonymous_keyload<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new_with_flags(
+        "AUTHOR9SEED",
+        encoding,
+        PAYLOAD_BYTES,
+        FLAG_ANONYMOUS_KEYLOAD_MASK,
+        transport.clone(),
+    );
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    subscriberB.receive_announcement(&announcement_link)?;
+    ensure!(subscriberB.is_anonymous_keyload(), "anonymous keyload flag not announced");
+
+    let subscribeB_link = subscriberB.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribeB_link)?;
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(!subscriberA.receive_keyload(&keyload_link)?, "subscriberA unwrapped anonymous keyload");
+    ensure!(subscriberB.receive_keyload(&keyload_link)?, "subscriberB failed to unwrap anonymous keyload");
+
+    let (tagged_packet_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberA.receive_tagged_packet(&tagged_packet_link).is_err(), "subscriberA unwrapped tagged packet");
+    let (unwrapped_public, unwrapped_masked) = subscriberB.receive_tagged_packet(&tagged_packet_link)?;
+    ensure!(public_payload == unwrapped_public, "bad unwrapped public payload");
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    // Publishers are disclosed to the recipients of anonymous keyloads, so subscribers fetch
+    // each other's packets.
+    let flags = FLAG_BRANCHING_MASK | FLAG_ANONYMOUS_KEYLOAD_MASK;
+    let mut author = Author::new_with_flags("AUTHOR9SEED9MULTI", encoding, PAYLOAD_BYTES, flags, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberB, &mut subscriberC].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    for subscriber in [&mut subscriberB, &mut subscriberC].iter_mut() {
+        ensure!(subscriber.fetch_next_msgs().len() == 1, "subscriber failed to fetch anonymous keyload");
+    }
+
+    let (packetC_link, _) = subscriberC.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let msgs = subscriberB.fetch_next_msgs();
+    ensure!(msgs.len() == 1 && msgs[0].link == packetC_link, "subscriberB failed to fetch subscriberC packet");
+    match &msgs[0].body {
+        MessageContent::SignedPacket { pk, masked_payload: unwrapped_masked, .. } => {
+            ensure!(pk == subscriberC.get_pk(), "bad packet publisher");
+            ensure!(masked_payload == *unwrapped_masked, "bad unwrapped masked payload");
+        }
+        _ => ensure!(false, "expected signed packet"),
+    }
+    ensure!(author.fetch_next_msgs().len() == 1, "Author failed to fetch subscriberC packet");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_anonymous_keyload_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_anonymous_keyload(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_tree_keyload<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscribers: Vec<Subscriber<T>> = ["SUBSCRIBERA9SEED", "SUBSCRIBERB9SEED", "SUBSCRIBERC9SEED"]
+        .iter()
+        .map(|seed| Subscriber::new(seed, encoding, PAYLOAD_BYTES, transport.clone()))
+        .collect();
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in subscribers.iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+        author.add_tree_member(subscriber.get_pk())?;
+    }
+
+    // Tree keyload may be split into parts, they are fetched in order.
+    let (keyload_link, _) = author.send_tree_keyload(&announcement_link)?;
+    let (tagged_packet_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    for subscriber in subscribers.iter_mut() {
+        while !subscriber.fetch_next_msgs().is_empty() {}
+        ensure!(subscriber.receive_tagged_packet(&tagged_packet_link).is_ok(), "subscriber failed to unwrap tree keyload");
+    }
+
+    let removed_pk = *subscribers[1].get_pk();
+    author.remove_tree_member(&removed_pk)?;
+    let (keyload_link, _) = author.send_tree_keyload(&tagged_packet_link)?;
+    let (tagged_packet_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    for (i, subscriber) in subscribers.iter_mut().enumerate() {
+        while !subscriber.fetch_next_msgs().is_empty() {}
+        let unwrapped = subscriber.receive_tagged_packet(&tagged_packet_link);
+        ensure!(unwrapped.is_ok() == (i != 1), "bad tagged packet access");
+    }
+
+    let authordump = author.export("pwdAuthor")?;
+    let _author2 = Author::import(authordump.as_ref(), "pwdAuthor", transport.clone())?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_tree_keyload_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_tree_keyload(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_tree_keyload_split<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscribers: Vec<Subscriber<T>> = (0..40)
+        .map(|i| Subscriber::new(&format!("SUBSCRIBER{}9SEED", i), encoding, PAYLOAD_BYTES, transport.clone()))
+        .collect();
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in subscribers.iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+        author.add_tree_member(subscriber.get_pk())?;
+    }
+
+    let mut keyload_link = announcement_link;
+    for removed in [None, Some(7)].iter() {
+        if let Some(i) = removed {
+            author.remove_tree_member(&subscribers[*i].get_pk().clone())?;
+        }
+        keyload_link = author.send_tree_keyload(&keyload_link)?.0;
+        let msg = transport.clone().recv_message(&keyload_link)?;
+        ensure!(msg.binary.body.bytes.len() <= PAYLOAD_BYTES, "tree keyload exceeds payload length");
+        let (tagged_packet_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+        for (i, subscriber) in subscribers.iter_mut().enumerate() {
+            let mut parts = 0;
+            loop {
+                let msgs = subscriber.fetch_next_msgs();
+                if msgs.is_empty() {
+                    break;
+                }
+                parts += msgs.iter().filter(|msg| msg.link != tagged_packet_link).count();
+            }
+            ensure!(parts > 1, "tree keyload has not been split");
+            let unwrapped = subscriber.receive_tagged_packet(&tagged_packet_link);
+            ensure!(unwrapped.is_ok() == (Some(i) != *removed), "bad tagged packet access");
+        }
+        keyload_link = tagged_packet_link;
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_tree_keyload_split_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_tree_keyload_split(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_keyload_split<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    // Small payload length so that keyload for all the subscribers doesn't fit into one message.
+    let payload_length = 512;
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, payload_length, false, transport.clone());
+    let mut subscribers: Vec<Subscriber<T>> = (0..8)
+        .map(|i| Subscriber::new(&format!("SUBSCRIBER{}9SEED", i), encoding, payload_length, transport.clone()))
+        .collect();
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in subscribers.iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    let (tagged_packet_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    for subscriber in subscribers.iter_mut() {
+        let mut keyloads = 0;
+        loop {
+            let msgs = subscriber.fetch_next_msgs();
+            if msgs.is_empty() {
+                break;
+            }
+            keyloads += msgs.iter().filter(|msg| msg.link != tagged_packet_link).count();
+        }
+        ensure!(keyloads > 1, "keyload has not been split");
+        let (unwrapped_public, unwrapped_masked) = subscriber.receive_tagged_packet(&tagged_packet_link)?;
+        ensure!(public_payload == unwrapped_public, "bad unwrapped public payload");
+        ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_keyload_split_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_keyload_split(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_ratchet<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new_with_flags("AUTHOR9SEED", encoding, PAYLOAD_BYTES, FLAG_RATCHET_MASK, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriber.receive_announcement(&announcement_link)?;
+    ensure!(subscriber.is_ratchet(), "ratchet flag not announced");
+    let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriber.receive_keyload(&keyload_link)?, "subscriber failed to unwrap keyload");
+
+    let (packet1_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (packet2_link, _) = author.send_signed_packet(&packet1_link, &public_payload, &masked_payload)?;
+    ensure!(
+        author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload).is_err(),
+        "keyload state has not been erased"
+    );
+
+    let (unwrapped_public, unwrapped_masked) = subscriber.receive_tagged_packet(&packet1_link)?;
+    ensure!(public_payload == unwrapped_public, "bad unwrapped public payload");
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    let (_, _, unwrapped_masked) = subscriber.receive_signed_packet(&packet2_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    // Ratcheted packet is returned from the processed messages, its link is not unwrapped again.
+    let (again_public, again_masked) = subscriber.receive_tagged_packet(&packet1_link)?;
+    ensure!(
+        public_payload == again_public && masked_payload == again_masked,
+        "ratcheted packet not returned again"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_ratchet_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_ratchet(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_rekey_policy<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    // Packets are linked to the branches of different keyloads.
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    author.set_rekey_policy(RekeyPolicy::default().every_packets(2).on_subscriber_change(true));
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    subscriberB.receive_announcement(&announcement_link)?;
+    let subscribeA_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribeA_link)?;
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    let (packet1_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (packet2_link, _) = author.send_tagged_packet(&packet1_link, &public_payload, &masked_payload)?;
+    // Third packet is preceded by a keyload as two packets have been sent.
+    let (packet3_link, _) = author.send_tagged_packet(&packet2_link, &public_payload, &masked_payload)?;
+
+    // New subscriber gets a keyload with the next packet.
+    let subscribeB_link = subscriberB.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribeB_link)?;
+    let (packet4_link, _) = author.send_tagged_packet(&packet3_link, &public_payload, &masked_payload)?;
+
+    let mut keyload_links = Vec::new();
+    let mut packets = 0;
+    loop {
+        let msgs = subscriberA.fetch_next_msgs();
+        if msgs.is_empty() {
+            break;
+        }
+        for msg in msgs {
+            match msg.body {
+                MessageContent::Keyload => keyload_links.push(msg.link),
+                MessageContent::TaggedPacket { .. } => packets += 1,
+                _ => {}
+            }
+        }
+    }
+    ensure!(keyload_links.len() == 3 && packets == 4, "rekey policy has not fired");
+
+    ensure!(subscriberB.receive_keyload(&keyload_links[2])?, "subscriberB failed to unwrap keyload");
+    let (_, unwrapped_masked) = subscriberB.receive_tagged_packet(&packet4_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    // Progress is exported with the author, the second packet after import is preceded by a keyload.
+    let authordump = author.export("pwdAuth")?;
+    let mut author = Author::import(authordump.as_ref(), "pwdAuth", transport.clone())?;
+    let (packet5_link, _) = author.send_tagged_packet(&packet4_link, &public_payload, &masked_payload)?;
+    let (packet6_link, _) = author.send_tagged_packet(&packet5_link, &public_payload, &masked_payload)?;
+
+    // Keyload for some of the subscribers starts a branch which is not rekeyed, nor does it restart counting.
+    let pksA = vec![*subscriberA.get_pk()];
+    let (partial_link, _) = author.send_keyload(&packet6_link, &Vec::new(), &pksA)?;
+    let (packet7_link, _) = author.send_tagged_packet(&partial_link, &public_payload, &masked_payload)?;
+    let (packet8_link, _) = author.send_tagged_packet(&packet6_link, &public_payload, &masked_payload)?;
+    ensure!(
+        author.send_tagged_packet(&packet7_link, &public_payload, &masked_payload).is_err(),
+        "branch of some subscribers rekeyed for everyone"
+    );
+
+    // New subscriber gets a keyload with the next packet, packets linked to the branch of the preceding
+    // keyload for everyone are linked to the new one.
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    subscriberC.receive_announcement(&announcement_link)?;
+    let subscribeC_link = subscriberC.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribeC_link)?;
+    let (packet9_link, _) = author.send_tagged_packet(&packet8_link, &public_payload, &masked_payload)?;
+    let (packet10_link, _) = author.send_tagged_packet(&packet6_link, &public_payload, &masked_payload)?;
+
+    // Tree keyload for everyone restarts counting as well.
+    author.add_tree_member(subscriberA.get_pk())?;
+    author.add_tree_member(subscriberB.get_pk())?;
+    author.add_tree_member(subscriberC.get_pk())?;
+    let (tree_keyload_link, _) = author.send_tree_keyload(&packet10_link)?;
+    let (packet11_link, _) = author.send_tagged_packet(&tree_keyload_link, &public_payload, &masked_payload)?;
+
+    // Message fetched before each of the packets.
+    let mut preceding = HashMap::new();
+    let mut last = packet4_link.clone();
+    loop {
+        let msgs = subscriberA.fetch_next_msgs();
+        if msgs.is_empty() {
+            break;
+        }
+        for msg in msgs {
+            if let MessageContent::TaggedPacket { .. } = msg.body {
+                preceding.insert(msg.link.clone(), last.clone());
+            }
+            last = msg.link;
+        }
+    }
+    ensure!(preceding.get(&packet5_link) == Some(&packet4_link), "keyload sent before packet5");
+    ensure!(preceding.get(&packet6_link) != Some(&packet5_link), "rekey progress lost with export");
+    ensure!(preceding.get(&packet7_link) == Some(&partial_link), "keyload sent before packet7");
+    ensure!(preceding.get(&packet8_link) == Some(&packet7_link), "keyload for some subscribers restarted counting");
+    ensure!(
+        preceding.get(&packet9_link).is_some_and(|link| *link != packet8_link),
+        "new subscriber has not got a keyload"
+    );
+    let rekey_link = preceding[&packet9_link].clone();
+    ensure!(preceding.get(&packet10_link) == Some(&packet9_link), "keyload sent before packet10");
+    ensure!(subscriberC.receive_keyload(&rekey_link)?, "subscriberC failed to unwrap keyload");
+    let (_, unwrapped_masked) = subscriberC.receive_tagged_packet(&packet10_link)?;
+    ensure!(masked_payload == unwrapped_masked, "packet10 not linked to the latest keyload");
+    ensure!(preceding.get(&packet11_link) == Some(&tree_keyload_link), "tree keyload not counted");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_rekey_policy_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_rekey_policy(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_subscription_approval<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    author.set_subscription_policy(SubscriptionPolicy::Allowlist(vec![*subscriberA.get_pk()]));
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberA, &mut subscriberB, &mut subscriberC].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    ensure!(author.pending_subscribers().len() == 2, "subscribers B and C are not pending");
+
+    author.approve_subscriber(subscriberB.get_pk())?;
+    author.reject_subscriber(subscriberC.get_pk())?;
+    ensure!(author.pending_subscribers().is_empty(), "pending queue is not empty");
+    ensure!(author.approve_subscriber(subscriberC.get_pk()).is_err(), "rejected subscriber approved");
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberA.receive_keyload(&keyload_link)?, "subscriberA failed to unwrap keyload");
+    ensure!(subscriberB.receive_keyload(&keyload_link)?, "subscriberB failed to unwrap keyload");
+    ensure!(!subscriberC.receive_keyload(&keyload_link)?, "rejected subscriberC unwrapped keyload");
+
+    let (packet_link, _) = subscriberB.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = author.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    let (packet_link, _) = subscriberC.send_signed_packet(&announcement_link, &public_payload, &masked_payload)?;
+    ensure!(author.receive_signed_packet(&packet_link).is_err(), "packet from rejected subscriber accepted");
+
+    // Policy is kept across export, a callback fails closed until it's set again.
+    let mut subscriberD = Subscriber::new("SUBSCRIBERD9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    subscriberD.receive_announcement(&announcement_link)?;
+    let subscribeD_link = subscriberD.send_subscribe(&announcement_link)?;
+    let authordump = author.export("pwdAuth")?;
+    let mut author = Author::import(authordump.as_ref(), "pwdAuth", transport.clone())?;
+    author.receive_subscribe(&subscribeD_link)?;
+    ensure!(author.pending_subscribers() == [*subscriberD.get_pk()], "allowlist not restored");
+    author.reject_subscriber(subscriberD.get_pk())?;
+
+    author.set_subscription_policy(SubscriptionPolicy::Callback(Box::new(|_| true)));
+    let authordump = author.export("pwdAuth")?;
+    let mut author = Author::import(authordump.as_ref(), "pwdAuth", transport.clone())?;
+    ensure!(author.receive_subscribe(&subscribeD_link).is_err(), "lost callback policy not reported");
+    ensure!(author.pending_subscribers() == [*subscriberD.get_pk()], "subscriber approved without callback");
+    author.set_subscription_policy(SubscriptionPolicy::MaxSubscribers(4));
+    let mut subscriberE = Subscriber::new("SUBSCRIBERE9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    subscriberE.receive_announcement(&announcement_link)?;
+    let subscribeE_link = subscriberE.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribeE_link)?;
+    ensure!(author.pending_subscribers().len() == 1, "subscriberE not approved");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_subscription_approval_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_subscription_approval(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_roles<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberA, &mut subscriberB].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    subscriberA.receive_keyload(&keyload_link)?;
+    subscriberB.receive_keyload(&keyload_link)?;
+
+    author.set_role(subscriberA.get_pk(), Role::Writer)?;
+    author.set_role(subscriberB.get_pk(), Role::Reader)?;
+    let (roles_link, _) = author.send_role_assignment(&keyload_link)?;
+    subscriberA.receive_role_assignment(&roles_link)?;
+    subscriberB.receive_role_assignment(&roles_link)?;
+    ensure!(subscriberB.role(subscriberA.get_pk()) == Some(Role::Writer), "bad role of subscriberA");
+
+    // Only Author assigns roles.
+    let mut reader = User::<T>::new("READER9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    reader.receive_announcement(&announcement_link)?;
+    ensure!(reader.set_role(subscriberB.get_pk(), Role::Writer).is_err(), "role set by subscriber");
+    ensure!(reader.send_role_assignment(&announcement_link).is_err(), "role assignment sent by subscriber");
+
+    let (packet_link, _) = subscriberA.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberB.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    let (packet_link, _) = subscriberA.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, unwrapped_masked) = author.receive_tagged_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    let (packet_link, _) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    subscriberB.receive_tagged_packet(&packet_link)?;
+
+    let (packet_link, _) = subscriberB.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(author.receive_signed_packet(&packet_link).is_err(), "signed packet from reader accepted");
+    ensure!(subscriberA.receive_signed_packet(&packet_link).is_err(), "signed packet from reader accepted");
+    let (packet_link, _) = subscriberB.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberA.receive_tagged_packet(&packet_link).is_err(), "tagged packet from reader accepted");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_roles_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_roles(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_certificates<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let flags = FLAG_BRANCHING_MASK | FLAG_CERTIFIED_PUBLISHERS_MASK;
+    let mut author = Author::new_with_flags("AUTHOR9SEED", encoding, PAYLOAD_BYTES, flags, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberA, &mut subscriberB].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        ensure!(subscriber.is_certified_publishers(), "channel flags not recovered");
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    subscriberA.receive_keyload(&keyload_link)?;
+    subscriberB.receive_keyload(&keyload_link)?;
+
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    subscriberB.receive_signed_packet(&packet_link)?;
+    let (packet_link, _) = subscriberA.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberB.receive_signed_packet(&packet_link).is_err(), "packet without certificate accepted");
+
+    let (cert_link, _) = author.send_certificate(&keyload_link, subscriberA.get_pk(), Some(&keyload_link), 0, 0)?;
+    let cert = subscriberB.receive_certificate(&cert_link)?;
+    ensure!(cert.branch == Some(keyload_link.msgid.clone()), "bad certificate branch");
+
+    let (packet_link, _) = subscriberA.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberB.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    author.receive_signed_packet(&packet_link)?;
+    // Packets of certified channels carry publication time, proofs unwrap it too.
+    let proof = subscriberB.export_signed_packet_proof(&packet_link)?;
+    let (_, unwrapped_masked) = verify_signed_packet_proof(&proof, subscriberA.get_pk())?;
+    ensure!(masked_payload == unwrapped_masked, "bad proof masked payload");
+    let subBdump = subscriberB.export("pwdSubB")?;
+    let mut subscriberB = Subscriber::import(subBdump.as_ref(), "pwdSubB", transport.clone())?;
+    let (packet_link, _) = subscriberA.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    subscriberB.receive_signed_packet(&packet_link)?;
+    let (packet_link, _) = subscriberA.send_signed_packet(&announcement_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberB.receive_signed_packet(&packet_link).is_err(), "packet outside of certified branch accepted");
+
+    // Retire the publisher by reissuing an expired certificate.
+    let (cert_link, _) = author.send_certificate(&keyload_link, subscriberA.get_pk(), None, 0, 1)?;
+    subscriberB.receive_certificate(&cert_link)?;
+    let (packet_link, _) = subscriberA.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberB.receive_signed_packet(&packet_link).is_err(), "packet with expired certificate accepted");
+    ensure!(author.receive_signed_packet(&packet_link).is_err(), "packet with expired certificate accepted");
+
+    // Validity is checked at the time the packet is received, not the one it's relayed with.
+    let msg = transport.clone().recv_message(&packet_link)?;
+    let mut relay = iota_streams_app::transport::new_shared_transport(crate::api::tangle::BucketTransport::new());
+    relay.send_message(&Message::with_timestamp(msg.binary, 1))?;
+    let subBdump = subscriberB.export("pwdSubB")?;
+    let mut relayed = Subscriber::import(subBdump.as_ref(), "pwdSubB", relay)?;
+    ensure!(relayed.receive_signed_packet(&packet_link).is_err(), "packet with forged timestamp accepted");
+
+    // Nor at the publication time signed in the packet: a retired publisher can't backdate packets.
+    let mut publisher = User::<T>::new("PUBLISHER9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    publisher.receive_announcement(&announcement_link)?;
+    let subscribe_link = publisher.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (cert_link, _) = author.send_certificate(&announcement_link, publisher.get_pk(), None, 0, 1)?;
+    subscriberB.receive_certificate(&cert_link)?;
+    publisher.receive_certificate(&cert_link)?;
+    let backdated = publisher.user.sign_packet(&cert_link.msgid, &public_payload, &masked_payload, 0)?;
+    let packet_link = backdated.message.link.clone();
+    transport.clone().send_message(&Message::new(backdated.message))?;
+    ensure!(subscriberB.receive_signed_packet(&packet_link).is_err(), "backdated packet of expired publisher accepted");
+    ensure!(author.receive_signed_packet(&packet_link).is_err(), "backdated packet of expired publisher accepted");
+
+    // Revoked publishers are rejected whatever the time.
+    let (cert_link, _) = author.send_certificate(&keyload_link, publisher.get_pk(), None, 0, 0)?;
+    subscriberB.receive_certificate(&cert_link)?;
+    let (packet_link, _) = publisher.send_signed_packet(&announcement_link, &public_payload, &masked_payload)?;
+    subscriberB.receive_signed_packet(&packet_link)?;
+    let (cert_link, _) = author.revoke_publisher(&keyload_link, publisher.get_pk())?;
+    let cert = subscriberB.receive_certificate(&cert_link)?;
+    ensure!(cert.is_revoked(), "revocation not recovered");
+    let (packet_link, _) = publisher.send_signed_packet(&announcement_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberB.receive_signed_packet(&packet_link).is_err(), "packet of revoked publisher accepted");
+    ensure!(author.receive_signed_packet(&packet_link).is_err(), "packet of revoked publisher accepted");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_certificates_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_certificates(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_admins<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut admin = Subscriber::new("ADMIN9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    author.add_admin(admin.get_pk())?;
+    let announcement_link = author.send_announce()?;
+    admin.receive_announcement(&announcement_link)?;
+    ensure!(admin.is_admin(), "admin declared in announcement not recovered");
+    subscriberA.receive_announcement(&announcement_link)?;
+    ensure!(subscriberA.admins() == author.admins(), "bad admin set");
+    // Only Author manages administrators.
+    let mut reader = User::<T>::new("READER9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    reader.receive_announcement(&announcement_link)?;
+    let reader_pk = *reader.get_pk();
+    ensure!(reader.add_admin(&reader_pk).is_err(), "admin added by subscriber");
+    ensure!(reader.remove_admin(admin.get_pk()).is_err(), "admin removed by subscriber");
+    ensure!(reader.send_admin_set(&announcement_link).is_err(), "admin set sent by subscriber");
+    ensure!(reader.admins() == author.admins(), "admin set changed by subscriber");
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(admin.receive_keyload(&keyload_link)?, "admin not found in keyload");
+    ensure!(subscriberA.receive_keyload(&keyload_link)?, "subscriberA not found in keyload");
+
+    // Admin accepts a subscription and shares a session key while Author is offline.
+    subscriberB.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberB.send_subscribe_to(&announcement_link, admin.get_pk())?;
+    admin.receive_subscribe(&subscribe_link)?;
+    let (admin_keyload_link, _) = admin.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberB.receive_keyload(&admin_keyload_link)?, "subscriberB not found in keyload");
+    ensure!(subscriberA.receive_keyload(&admin_keyload_link)?, "subscriberA not found in keyload");
+    ensure!(author.receive_keyload(&admin_keyload_link)?, "author not found in keyload");
+
+    let (packet_link, _) = subscriberB.send_signed_packet(&admin_keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    author.receive_signed_packet(&packet_link)?;
+
+    // A subscriber promoted to administrator later.
+    subscriberC.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberC.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberC.receive_keyload(&keyload_link)?, "subscriberC not found in keyload");
+    author.add_admin(subscriberC.get_pk())?;
+    let (admins_link, _) = author.send_admin_set(&announcement_link)?;
+    for subscriber in [&mut subscriberA, &mut subscriberC].iter_mut() {
+        subscriber.receive_admin_set(&admins_link)?;
+    }
+    ensure!(subscriberC.is_admin(), "admin added later not recovered");
+    let (keyload_link, _) = subscriberC.send_keyload(&announcement_link, &Vec::new(), &[*subscriberA.get_pk()])?;
+    ensure!(subscriberA.receive_keyload(&keyload_link)?, "subscriberA not found in keyload");
+
+    // Keyloads of a removed administrator are rejected.
+    author.remove_admin(admin.get_pk())?;
+    let (admins_link, _) = author.send_admin_set(&announcement_link)?;
+    subscriberA.receive_admin_set(&admins_link)?;
+    let (keyload_link, _) = admin.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberA.receive_keyload(&keyload_link).is_err(), "keyload of removed admin accepted");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_admins_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_admins(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_legacy_messages<T: Transport + Clone>(mut transport: T) -> Result<()>
+{
+    use iota_streams_app::message::BinaryMessage;
+
+    // Announcement, keyload for SUBSCRIBERA9SEED and a signed packet of a channel without
+    // administrators and auditors, sent before they were introduced.
+    const APPINST: &str = "657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27e0240000000000000000";
+    const ANNOUNCE_ID: &str = "196753aa70924e009b6c4def";
+    const ANNOUNCE: &str = concat!(
+        "000000010400000000000000000000000e000001657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27",
+        "e024003f1ea8201f38b9a86b1b9b4d30efb84de162bd779f16dfc91319c09118bc9a1b057520de555727406861eca5d5ff71",
+        "926f25a30bd105a7137c0771246710e008",
+    );
+    const KEYLOAD_ID: &str = "ff95815a0f103f4f950bff82";
+    const KEYLOAD: &str = concat!(
+        "000010010400000000000000000000020e000001196753aa70924e009b6c4defd26e5898b812fe636024d3a0a41da8420001",
+        "02657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27e024b805a96dd2a44404ac405c9a7f86a3f3fc",
+        "e9abca12d4cee39a2aedea5f69d0687bc968ebbebc69ea8bbc12d9e507d5dbc5e2ecd6338ba7fbb505e8b20a3b2880b87432",
+        "846f6f9d1b5f94516ab4fe80d1a1297e830debadbd688984721ba14892ac550fc4f97e5186cd327279c04e9e674d976340c6",
+        "4917d65a6c0ab517548e3a75b24f6844e8d49d67a337a3fd92208210a0f2d383363d25a944cd623730b262102a03da2a3acc",
+        "f1fc82a33e477ba4a04c229d3a20766f76276860e9951fc5764cc93117e309357bf96dba06b816bc55a608f152a52d734e7c",
+        "79b2dcc515d807",
+    );
+    const PACKET_ID: &str = "93a008520e1d956cd9cf258f";
+    const PACKET: &str = concat!(
+        "000030010400000000000000000000030e000001ff95815a0f103f4f950bff82657f9751cb972555032119bdff0ba4a675c8",
+        "2ff55aee3a552ae6aeccad27e024010d5055424c49435041594c4f4144010d4d41534b45445041594c4f41449824cc1c6b0a",
+        "64b54b48b29b68bac1a1e5efc663b56f3d6db204ab710761879a339d75578638dae41faab4f734d53e001324e8e74201bb70",
+        "73c724fc07189003",
+    );
+
+    let mut links = Vec::new();
+    for (msgid, body) in [(ANNOUNCE_ID, ANNOUNCE), (KEYLOAD_ID, KEYLOAD), (PACKET_ID, PACKET)].iter() {
+        let link = Address::from_str(APPINST, msgid).unwrap();
+        transport.send_message(&Message::new(BinaryMessage::new(link.clone(), hex::decode(body).unwrap().into())))?;
+        links.push(link);
+    }
+
+    let encoding = "utf-8";
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    subscriberA.receive_announcement(&links[0])?;
+    ensure!(subscriberA.receive_keyload(&links[1])?, "subscriberA not found in keyload");
+    let (_, unwrapped_public, unwrapped_masked) = subscriberA.receive_signed_packet(&links[2])?;
+    ensure!(unwrapped_public.0 == b"PUBLICPAYLOAD".to_vec(), "bad unwrapped public payload");
+    ensure!(unwrapped_masked.0 == b"MASKEDPAYLOAD".to_vec(), "bad unwrapped masked payload");
+
+    // Channel without administrators and auditors is still announced in the original layout.
+    // It is sent to the same link, next to the stored one.
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let announcement_link = author.send_announce()?;
+    ensure!(announcement_link == links[0], "announcement link changed");
+    let announcements = transport.recv_messages(&announcement_link)?;
+    ensure!(announcements.len() == 2, "expected two announcements, found {}", announcements.len());
+    ensure!(
+        announcements.iter().all(|m| hex::encode(&m.binary.body.bytes) == ANNOUNCE),
+        "announcement layout changed"
+    );
+    ensure!(author.add_admin(subscriberA.get_pk()).is_err(), "admin added to channel announced without admins");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_legacy_messages_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_legacy_messages(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_private_keyload<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberD = Subscriber::new("SUBSCRIBERD9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberA, &mut subscriberB, &mut subscriberC].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    for subscriber in [&mut subscriberA, &mut subscriberB, &mut subscriberC].iter_mut() {
+        subscriber.receive_keyload(&keyload_link)?;
+    }
+
+    // Subscribers learnt each other from Author's keyload.
+    let (private_link, _) = subscriberA.send_private_keyload(&keyload_link, &[*subscriberB.get_pk()])?;
+    ensure!(subscriberB.receive_keyload(&private_link)?, "subscriberB not found in private keyload");
+    ensure!(!subscriberC.receive_keyload(&private_link)?, "subscriberC found in private keyload");
+    ensure!(!author.receive_keyload(&private_link)?, "author found in private keyload");
+
+    let (packet_link, _) = subscriberA.send_tagged_packet(&private_link, &public_payload, &masked_payload)?;
+    let (_, unwrapped_masked) = subscriberB.receive_tagged_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    ensure!(subscriberC.receive_tagged_packet(&packet_link).is_err(), "private branch readable by subscriberC");
+    let (packet_link, _) = subscriberB.send_signed_packet(&private_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    // Private keyload of a participant unknown to the recipient is rejected.
+    subscriberD.receive_announcement(&announcement_link)?;
+    let (private_link, _) = subscriberD.send_private_keyload(&announcement_link, &[*author.get_pk()])?;
+    ensure!(author.receive_keyload(&private_link).is_err(), "private keyload of unknown subscriber accepted");
+    ensure!(
+        subscriberD.send_private_keyload(&announcement_link, &[*subscriberA.get_pk()]).is_err(),
+        "private keyload for unknown subscriber sent"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_private_keyload_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_private_keyload(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_direct_message<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberA, &mut subscriberB].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+
+    let (dm_link, _) = subscriberA.send_direct_message(&announcement_link, &masked_payload)?;
+    let unwrapped = author.receive_direct_message(&dm_link)?;
+    ensure!(
+        unwrapped == Some((*subscriberA.get_pk(), masked_payload.clone())),
+        "bad unwrapped direct message"
+    );
+    // Other subscribers skip direct messages without failing.
+    ensure!(subscriberB.receive_direct_message(&dm_link)?.is_none(), "direct message readable by subscriberB");
+
+    let (_, _) = subscriberA.send_direct_message(&announcement_link, &masked_payload)?;
+    let msgs = author.fetch_next_msgs();
+    ensure!(
+        msgs.iter().any(|msg| match &msg.body {
+            MessageContent::DirectMessage { content: Some((pk, masked)) } =>
+                pk == subscriberA.get_pk() && *masked == masked_payload,
+            _ => false,
+        }),
+        "direct message not fetched by author"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_direct_message_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_direct_message(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_hidden_channel<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let pskid = PskId::from([1_u8; 16]);
+    let psk = Psk::from([2_u8; 32]);
+    let mut author = Author::new_hidden(
+        "AUTHOR9SEED", encoding, PAYLOAD_BYTES, FLAG_BRANCHING_MASK, pskid, psk, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    ensure!(author.is_hidden(), "channel is not hidden");
+    ensure!(
+        *author.channel_address().unwrap() != ChannelAddress::new(author.get_pk(), 0),
+        "hidden channel address is derived from author public key"
+    );
+    let announcement_link = author.send_announce()?;
+
+    // Announcement can't be read without the pre-shared key.
+    subscriberA.store_psk(PskId::from([3_u8; 16]), Psk::from([4_u8; 32]));
+    ensure!(subscriberA.receive_announcement(&announcement_link).is_err(), "hidden announcement read without psk");
+
+    subscriberB.store_psk(PskId::from([3_u8; 16]), Psk::from([4_u8; 32]));
+    subscriberB.store_psk(pskid, psk);
+    subscriberB.receive_announcement(&announcement_link)?;
+    ensure!(subscriberB.is_hidden(), "subscriberB doesn't know the channel is hidden");
+    let subscribe_link = subscriberB.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+
+    subscriberC.store_psk(pskid, psk);
+    subscriberC.receive_announcement(&announcement_link)?;
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberB.receive_keyload(&keyload_link)?, "subscriberB not found in keyload");
+    ensure!(subscriberC.receive_keyload(&keyload_link)?, "psk holder not found in keyload");
+
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberB.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    let authordump = author.export("pwdAuthor")?;
+    let author2 = Author::import(authordump.as_ref(), "pwdAuthor", transport.clone())?;
+    ensure!(author2.is_hidden(), "hidden channel not recovered");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_hidden_channel_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_hidden_channel(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_topics<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let telemetry = Bytes("TELEMETRY".as_bytes().to_vec());
+    let alerts = Bytes("ALERTS".as_bytes().to_vec());
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    for subscriber in [&mut subscriberA, &mut subscriberB].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    subscriberA.follow_topics(vec![alerts.clone()])?;
+
+    let mut topic_keyloads = Vec::new();
+    for name in [&telemetry, &alerts].iter() {
+        author.send_topic(&announcement_link, name)?;
+        let topic_link = author.topic(name).unwrap();
+        ensure!(author.send_topic(&announcement_link, name).is_err(), "topic declared twice");
+        let (keyload_link, _) = author.send_keyload_for_everyone(&topic_link)?;
+        author.send_signed_packet(&keyload_link, &public_payload, name)?;
+        topic_keyloads.push(keyload_link);
+    }
+
+    let fetch_all = |subscriber: &mut Subscriber<T>| {
+        let mut all = Vec::new();
+        loop {
+            let msgs = subscriber.fetch_next_msgs();
+            if msgs.is_empty() {
+                break all;
+            }
+            all.extend(msgs);
+        }
+    };
+    let packets = |msgs: &Vec<UnwrappedMessage>| -> Vec<Bytes> {
+        msgs.iter()
+            .filter_map(|msg| match &msg.body {
+                MessageContent::SignedPacket { masked_payload, .. } => Some(masked_payload.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    // subscriberA follows alerts only, telemetry messages are not fetched
+    let msgs = fetch_all(&mut subscriberA);
+    ensure!(subscriberA.topics() == vec![telemetry.clone(), alerts.clone()], "bad topics of subscriberA");
+    ensure!(packets(&msgs) == vec![alerts.clone()], "bad packets of subscriberA");
+
+    let msgs = fetch_all(&mut subscriberB);
+    ensure!(packets(&msgs) == vec![telemetry.clone(), alerts.clone()], "bad packets of subscriberB");
+
+    // Followed topics survive export
+    let subAdump = subscriberA.export("pwdSubA")?;
+    let mut subscriberA2 = Subscriber::import(subAdump.as_ref(), "pwdSubA", transport.clone())?;
+    for (name, keyload_link) in [&telemetry, &alerts].iter().zip(topic_keyloads.iter()) {
+        author.send_signed_packet(keyload_link, &public_payload, name)?;
+    }
+    let msgs = fetch_all(&mut subscriberA2);
+    ensure!(packets(&msgs) == vec![alerts.clone()], "bad packets of imported subscriberA");
+
+    // Single-branch messages are fetched without sequence messages, so they have no topics
+    let mut author_single = Author::new("AUTHOR9SINGLE9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriber_single = Subscriber::new("SUBSCRIBERC9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let single_announcement_link = author_single.send_announce()?;
+    subscriber_single.receive_announcement(&single_announcement_link)?;
+    ensure!(
+        author_single.send_topic(&single_announcement_link, &alerts).is_err(),
+        "topic declared in single-branch channel"
+    );
+    ensure!(
+        subscriber_single.follow_topics(vec![alerts.clone()]).is_err(),
+        "topic followed in single-branch channel"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_topics_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_topics(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_auditors<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let pskid = PskId::from([1_u8; 16]);
+    let psk = Psk::from([2_u8; 32]);
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut auditor = Subscriber::new("AUDITOR9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let mut auditor_psk = Subscriber::new("AUDITORPSK9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    ensure!(author.add_auditor_psk(pskid).is_err(), "unknown psk added as auditor");
+    author.store_psk(pskid, psk);
+    author.add_auditor_psk(pskid)?;
+    author.add_auditor(auditor.get_pk())?;
+    let announcement_link = author.send_announce()?;
+    ensure!(author.add_auditor(subscriberB.get_pk()).is_err(), "auditor added after announcement");
+
+    for subscriber in [&mut subscriberA, &mut subscriberB].iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    auditor.receive_announcement(&announcement_link)?;
+    auditor_psk.store_psk(pskid, psk);
+    auditor_psk.receive_announcement(&announcement_link)?;
+
+    let (auditor_pks, auditor_psks) = subscriberA.auditors();
+    ensure!(auditor_pks == [*auditor.get_pk()] && auditor_psks == [pskid], "auditors not announced");
+
+    // Keyload for subscriberA only is still readable by the auditors.
+    let (keyload_link, _) = author.send_keyload(&announcement_link, &vec![], &[*subscriberA.get_pk()])?;
+    ensure!(subscriberA.receive_keyload(&keyload_link)?, "subscriberA not found in keyload");
+    ensure!(!subscriberB.receive_keyload(&keyload_link)?, "subscriberB found in keyload");
+    ensure!(auditor.receive_keyload(&keyload_link)?, "auditor not found in keyload");
+    ensure!(auditor_psk.receive_keyload(&keyload_link)?, "auditor psk not found in keyload");
+
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = auditor.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    let authordump = author.export("pwdAuthor")?;
+    let author2 = Author::import(authordump.as_ref(), "pwdAuthor", transport.clone())?;
+    ensure!(author2.auditors().0 == author.auditors().0, "auditors not recovered");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_auditors_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_auditors(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_signed_packet_proof<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    subscriberA.receive_keyload(&keyload_link)?;
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    subscriberA.receive_signed_packet(&packet_link)?;
+    let (tagged_link, _) = author.send_tagged_packet(&packet_link, &public_payload, &masked_payload)?;
+    subscriberA.receive_tagged_packet(&tagged_link)?;
+
+    let proof = subscriberA.export_signed_packet_proof(&packet_link)?;
+    let (unwrapped_public, unwrapped_masked) = verify_signed_packet_proof(&proof, author.get_pk())?;
+    ensure!(public_payload == unwrapped_public, "bad proof public payload");
+    ensure!(masked_payload == unwrapped_masked, "bad proof masked payload");
+    ensure!(
+        verify_signed_packet_proof(&proof, subscriberA.get_pk()).is_err(),
+        "proof verified against another publisher"
+    );
+
+    // Subscriber proves its own packet, it's not taken for the Author's one.
+    let (own_link, _) = subscriberA.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let own_proof = subscriberA.export_signed_packet_proof(&own_link)?;
+    ensure!(
+        verify_signed_packet_proof(&own_proof, author.get_pk()).is_err(),
+        "subscriber packet verified as Author's"
+    );
+    verify_signed_packet_proof(&own_proof, subscriberA.get_pk())?;
+
+    let mut tampered = proof.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    ensure!(verify_signed_packet_proof(&tampered, author.get_pk()).is_err(), "tampered proof verified");
+    ensure!(subscriberA.export_signed_packet_proof(&tagged_link).is_err(), "proof of a tagged packet exported");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_signed_packet_proof_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_signed_packet_proof(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_export_kdf<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    // Author state exported in version 0 format with password "pwdAuthor" after the announcement
+    // and a signed packet linked to it.
+    const DUMP_V0: &str = concat!(
+        "0000468eadb36cb38f5a22bd69f25e4c5efa09419cfa849d36850cff111d64f6845a0001057574662d38000000",
+        "000000044201657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27e0240000000000000000196753aa70924e00",
+        "9b6c4def01657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27e0240102196753aa70924e009b6c4def738030",
+        "ffc61f63716df67a8f9ab4eaa2d6d7ff3647890a25eec5e2fdf543b1d200ff95815a0f103f4f950bff8299508dace127d8c5075fb6bb",
+        "f72b082497de1b84db65d64417cd97d039c2bb7202000101657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27",
+        "e024196753aa70924e009b6c4def0000000000000003a03b6499809a4bd4dbac323855e7c5b84a9236f82c65b0cc98ed250da2010c56",
+    );
+    const APPINST_V0: &str = "657f9751cb972555032119bdff0ba4a675c82ff55aee3a552ae6aeccad27e0240000000000000000";
+    const ANNOUNCE_V0: &str = "196753aa70924e009b6c4def";
+    const PACKET_V0: &str = "ff95815a0f103f4f950bff82";
+
+    let encoding = "utf-8";
+    let mut user = User::<T>::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    user.user.create_channel(0)?;
+    user.send_announce()?;
+    let kdf = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    let dump1 = user.export_with_kdf(0, "pwdAuthor", &kdf)?;
+    let dump2 = user.export_with_kdf(0, "pwdAuthor", &kdf)?;
+    ensure!(dump1[1..] != dump2[1..], "exports under the same password are equal");
+    let user2 = User::<T>::import(&dump1, 0, "pwdAuthor", transport.clone())?;
+    ensure!(user2.channel_address() == user.channel_address(), "channel address not recovered");
+    ensure!(User::<T>::import(&dump1, 0, "pwdWrong", transport.clone()).is_err(), "imported with wrong password");
+    // KDF parameters are read from the header, oversized ones are rejected before deriving the key.
+    let mut crafted = dump1.clone();
+    crafted[3..7].copy_from_slice(&[0xff; 4]);
+    ensure!(User::<T>::import(&crafted, 0, "pwdAuthor", transport.clone()).is_err(), "imported with oversized memory cost");
+
+    // State exported in version 0 format is still imported.
+    let dump0 = hex::decode(DUMP_V0).unwrap();
+    ensure!(Author::import(&dump0, "pwdWrong", transport.clone()).is_err(), "version 0 imported with wrong password");
+    let mut author0 = Author::import(&dump0, "pwdAuthor", transport.clone())?;
+    ensure!(author0.channel_address().map(|appinst| appinst.to_string()) == Some(APPINST_V0.to_string()),
+            "version 0 channel address not recovered");
+    let announcement_link = Address::from_str(APPINST_V0, ANNOUNCE_V0).unwrap();
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let (packet_link, _) = author0.send_signed_packet(&announcement_link, &public_payload, &Bytes::default())?;
+    ensure!(packet_link.msgid.to_string() != PACKET_V0, "version 0 sequencing state not recovered");
+
+    // And exported again in the current format.
+    let dump0_1 = author0.export("pwdAuthor")?;
+    let author0_1 = Author::import(&dump0_1, "pwdAuthor", transport.clone())?;
+    ensure!(author0_1.channel_address() == author0.channel_address(), "channel address not recovered after reexport");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_export_kdf_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_export_kdf(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_export_to<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use iota_streams_core_edsig::key_exchange::x25519;
+
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    let recovery_sk = x25519::StaticSecret::from([7_u8; 32]);
+    let recovery_pk = x25519::PublicKey::from(&recovery_sk);
+    let other_sk = x25519::StaticSecret::from([8_u8; 32]);
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+
+    let authordump = author.export_to(&recovery_pk)?;
+    ensure!(Author::import_with(&authordump, &other_sk, transport.clone()).is_err(), "imported with wrong key");
+    ensure!(Author::import(&authordump, "pwdAuthor", transport.clone()).is_err(), "imported with password");
+    ensure!(Subscriber::import_with(&authordump, &recovery_sk, transport.clone()).is_err(), "imported as subscriber");
+    let mut author2 = Author::import_with(&authordump, &recovery_sk, transport.clone())?;
+
+    // Recovered Author keeps publishing in the channel.
+    let (packet_link, _) = author2.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    subscriberA.receive_keyload(&keyload_link)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_export_to_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_export_to(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_checkpoint<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use core::cell::RefCell;
+    use iota_streams_core::prelude::Rc;
+
+    let encoding = "utf-8";
+    let kdf = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+    let storage = Rc::new(RefCell::new(MemoryStorage::new()));
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    author.attach_storage_with_kdf(storage.clone(), "pwdAuthor", &kdf)?;
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    subscriberA.receive_keyload(&keyload_link)?;
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+
+    // Author is resumed from the checkpoint without an explicit export.
+    drop(author);
+    ensure!(Author::open(storage.clone(), "pwdWrong", transport.clone()).is_err(), "opened with wrong password");
+    ensure!(Subscriber::open(storage.clone(), "pwdAuthor", transport.clone()).is_err(), "opened as subscriber");
+    let mut author = Author::open(storage.clone(), "pwdAuthor", transport.clone())?;
+    let (packet_link2, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(packet_link2 != packet_link, "sequencing state not recovered");
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link2)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+
+    // Journal is compacted into a new snapshot.
+    let mut last_link = packet_link2;
+    for _ in 0..SNAPSHOT_INTERVAL + 2 {
+        last_link = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?.0;
+    }
+    let values = storage.borrow().len();
+    ensure!(values <= 2 + SNAPSHOT_INTERVAL as usize, "journal not compacted: {} values", values);
+    drop(author);
+    let mut author = Author::open(storage.clone(), "pwdAuthor", transport.clone())?;
+    let (packet_link3, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(packet_link3 != last_link, "sequencing state not recovered after compaction");
+
+    // A failed checkpoint doesn't fail the sent message and is retried with the next one.
+    struct FailingStorage(Rc<RefCell<MemoryStorage>>, Rc<core::cell::Cell<bool>>);
+    impl Storage for FailingStorage {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            self.0.get(key)
+        }
+        fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+            ensure!(!self.1.get(), "storage unavailable");
+            self.0.put(key, value)
+        }
+        fn remove(&mut self, key: &str) -> Result<()> {
+            self.0.remove(key)
+        }
+    }
+    let failing = Rc::new(core::cell::Cell::new(false));
+    let storageB = Rc::new(RefCell::new(MemoryStorage::new()));
+    let mut authorB = Author::new("AUTHORB9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    authorB.attach_storage_with_kdf(FailingStorage(storageB.clone(), failing.clone()), "pwdAuthorB", &kdf)?;
+    let announcementB_link = authorB.send_announce()?;
+    failing.set(true);
+    let (packetB_link, _) = authorB.send_signed_packet(&announcementB_link, &public_payload, &masked_payload)?;
+    ensure!(authorB.checkpoint_failed(), "checkpoint failure not reported");
+    failing.set(false);
+    let (packetB_link2, _) = authorB.send_signed_packet(&announcementB_link, &public_payload, &masked_payload)?;
+    ensure!(!authorB.checkpoint_failed(), "checkpoint not retried");
+    drop(authorB);
+    let mut authorB = Author::open(storageB.clone(), "pwdAuthorB", transport.clone())?;
+    let (packetB_link3, _) = authorB.send_signed_packet(&announcementB_link, &public_payload, &masked_payload)?;
+    ensure!(packetB_link3 != packetB_link && packetB_link3 != packetB_link2, "sequencing state lost with failed checkpoint");
+
+    // Subscriber is checkpointed to the filesystem.
+    let dir = std::env::temp_dir().join(format!("iota-streams-checkpoint-{}", std::process::id()));
+    subscriberA.attach_storage_with_kdf(FileStorage::new(dir.clone())?, "pwdSubscriber", &kdf)?;
+    subscriberA.receive_signed_packet(&packet_link3)?;
+    drop(subscriberA);
+    let mut subscriberA = Subscriber::open(FileStorage::new(dir.clone())?, "pwdSubscriber", transport.clone())?;
+    let (packet_link4, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link4)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload after open");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_checkpoint_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_checkpoint(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_compact_export<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use core::cell::RefCell;
+    use iota_streams_core::prelude::Rc;
+
+    let encoding = "utf-8";
+    let kdf = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+    let storage = Rc::new(RefCell::new(MemoryStorage::new()));
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    author.attach_storage_with_kdf(storage.clone(), "pwdAuthor", &kdf)?;
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+
+    let mut keyload_link = announcement_link.clone();
+    for _ in 0..3 {
+        keyload_link = author.send_keyload_for_everyone(&announcement_link)?.0;
+        subscriberA.receive_keyload(&keyload_link)?;
+        for _ in 0..3 {
+            let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+            subscriberA.receive_signed_packet(&packet_link)?;
+        }
+    }
+
+    // Latest keyload is journaled and survives pruning after the author is resumed.
+    drop(author);
+    let mut author = Author::open(storage.clone(), "pwdAuthor", transport.clone())?;
+    let erased = author.prune_link_store()?;
+    ensure!(erased > 0, "nothing erased from author link store");
+    ensure!(author.prune_link_store()? == 0, "pruning is not idempotent");
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload after pruning");
+
+    let full = subscriberA.export("pwdSubscriber")?;
+    let (compact, erased) = subscriberA.export_compact("pwdSubscriber")?;
+    ensure!(erased > 0, "nothing erased from subscriber link store");
+    ensure!(compact.len() < full.len(), "compact export is not smaller");
+
+    let mut subscriberA = Subscriber::import(&compact, "pwdSubscriber", transport.clone())?;
+    let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload after compact import");
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    subscriberA.receive_keyload(&keyload_link)?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_compact_export_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_compact_export(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_bounded_link_store<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use core::{
+        sync::atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        time::Duration,
+    };
+    use iota_streams_ddml::link_store::LinkStore as _;
+
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriberA = LruUser::<T>::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut subscriberB = AgedUser::<T>::new("SUBSCRIBERB9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    subscriberA.link_store_mut().set_capacity(4);
+    subscriberB.link_store_mut().set_max_messages(Some(3));
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    subscriberB.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let subscribe_link = subscriberB.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberA.receive_keyload(&keyload_link)?, "subscriberA can't access keyload");
+    ensure!(subscriberB.receive_keyload(&keyload_link)?, "subscriberB can't access keyload");
+
+    // Keyload is used by every packet, so it's kept in the LRU store.
+    let (first_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    subscriberA.receive_signed_packet(&first_link)?;
+    subscriberB.receive_signed_packet(&first_link)?;
+    for _ in 0..6 {
+        let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+        let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+        ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+        ensure!(subscriberA.link_store_mut().len() <= 4, "LRU link store over capacity");
+    }
+    for _ in 0..3 {
+        let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+        subscriberB.receive_signed_packet(&packet_link)?;
+    }
+    // The 3 latest links, the pinned announcement and keyload
+    ensure!(subscriberB.link_store_mut().len() <= 5, "aged link store over limit");
+
+    // Messages linked to evicted messages can't be unwrapped.
+    let (reply_link, _) = author.send_signed_packet(&first_link, &public_payload, &masked_payload)?;
+    ensure!(subscriberA.receive_signed_packet(&reply_link).is_err(), "evicted link found in LRU store");
+    ensure!(subscriberB.receive_signed_packet(&reply_link).is_err(), "evicted link found in aged store");
+
+    // The announcement and the latest keyload are pinned, so new keyloads and packets
+    // are unwrapped however old they are.
+    let (keyload_link2, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberA.receive_keyload(&keyload_link2)?, "announcement evicted from LRU store");
+    ensure!(subscriberB.receive_keyload(&keyload_link2)?, "announcement evicted from aged store");
+    for _ in 0..6 {
+        let (packet_link, _) = author.send_signed_packet(&keyload_link2, &public_payload, &masked_payload)?;
+        subscriberA.receive_signed_packet(&packet_link)?;
+        subscriberB.receive_signed_packet(&packet_link)?;
+    }
+    let (packet_link, _) = author.send_signed_packet(&keyload_link2, &public_payload, &masked_payload)?;
+    ensure!(subscriberA.receive_signed_packet(&packet_link).is_ok(), "keyload evicted from LRU store");
+    ensure!(subscriberB.receive_signed_packet(&packet_link).is_ok(), "keyload evicted from aged store");
+    let (keyload_link3, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(subscriberB.receive_keyload(&keyload_link3)?, "announcement evicted from aged store");
+
+    // Time limit with a manual clock.
+    static NOW: AtomicU64 = AtomicU64::new(0);
+    fn clock() -> Duration {
+        Duration::from_secs(NOW.load(Ordering::SeqCst))
+    }
+    let mut store = AgedLinkStore::new(None, Some(Duration::from_secs(10))).with_clock(clock);
+    let (old_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (new_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let (inner, info) = subscriberA.link_store_mut().iter().into_iter().next().map(|(_, v)| v.clone()).unwrap();
+    store.insert(&old_link.msgid, inner.clone(), info)?;
+    NOW.store(5, Ordering::SeqCst);
+    store.insert(&new_link.msgid, inner, info)?;
+    NOW.store(12, Ordering::SeqCst);
+    ensure!(store.lookup(&old_link.msgid).is_err(), "expired link found");
+    ensure!(store.lookup(&new_link.msgid).is_ok(), "live link not found");
+    ensure!(store.iter().len() == 1, "expired link iterated");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_bounded_link_store_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_bounded_link_store(transport)).is_ok());
+}
+
+#[cfg(all(not(feature = "async"), feature = "db-storage"))]
+pub fn example_persistent_link_store<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use iota_streams_core::prng;
+    use iota_streams_ddml::{
+        link_store::LinkStore as _,
+        types::*,
+    };
+
+    let encoding = "utf-8";
+    let key = NBytes::<U32>::from(prng::random_key());
+    let path = std::env::temp_dir().join(format!("iota-streams-links-{}.redb", std::process::id()));
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    let mut reader = PersistentUser::<T>::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+    reader.link_store_mut().attach(DatabaseStorage::new(&path)?, key)?;
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    reader.receive_announcement(&announcement_link)?;
+    let subscribe_link = reader.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    ensure!(reader.receive_keyload(&keyload_link)?, "reader can't access keyload");
+    let (first_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    reader.receive_signed_packet(&first_link)?;
+    for _ in 0..3 {
+        let (packet_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+        reader.receive_signed_packet(&packet_link)?;
+    }
+    ensure!(reader.link_store_mut().iter().is_empty(), "links kept in memory");
+    ensure!(path.is_file(), "links not kept in a single file");
+
+    // Links are not exported, they are found in storage once it's attached again.
+    let exported = reader.export(1, "pwdReader")?;
+    drop(reader);
+    let mut reader = PersistentUser::<T>::import(&exported, 1, "pwdReader", transport.clone())?;
+    reader.link_store_mut().attach(DatabaseStorage::new(&path)?, key)?;
+    let (reply_link, _) = author.send_signed_packet(&first_link, &public_payload, &masked_payload)?;
+    let (_, _, unwrapped_masked) = reader.receive_signed_packet(&reply_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    drop(reader);
+
+    let mut reader = PersistentUser::<T>::import(&exported, 1, "pwdReader", transport.clone())?;
+    let wrong_key = NBytes::<U32>::from(prng::random_key());
+    reader.link_store_mut().attach(DatabaseStorage::new(&path)?, wrong_key)?;
+    let (reply_link, _) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(reader.receive_signed_packet(&reply_link).is_err(), "link decrypted with wrong key");
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(all(not(feature = "async"), feature = "db-storage"))]
+fn run_persistent_link_store_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_persistent_link_store(transport)).is_ok());
+}
+
+/// Transport failing on demand, the settings are shared between the clones.
+#[cfg(not(feature = "async"))]
+#[derive(Clone)]
+struct FlakyTransport<T> {
+    transport: T,
+    // Number of messages sent before sending fails, unlimited if None.
+    sends_left: iota_streams_core::prelude::Rc<core::cell::Cell<Option<usize>>>,
+    fail_recv: iota_streams_core::prelude::Rc<core::cell::RefCell<Option<Address>>>,
+}
+
+#[cfg(not(feature = "async"))]
+impl<T> FlakyTransport<T> {
+    fn new(transport: T) -> Self {
+        Self {
+            transport,
+            sends_left: iota_streams_core::prelude::Rc::new(core::cell::Cell::new(None)),
+            fail_recv: iota_streams_core::prelude::Rc::new(core::cell::RefCell::new(None)),
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: Transport> iota_streams_app::transport::TransportOptions for FlakyTransport<T> {
+    type SendOptions = T::SendOptions;
+    fn get_send_options(&self) -> Self::SendOptions {
+        self.transport.get_send_options()
+    }
+    fn set_send_options(&mut self, opt: Self::SendOptions) {
+        self.transport.set_send_options(opt)
+    }
+
+    type RecvOptions = T::RecvOptions;
+    fn get_recv_options(&self) -> Self::RecvOptions {
+        self.transport.get_recv_options()
+    }
+    fn set_recv_options(&mut self, opt: Self::RecvOptions) {
+        self.transport.set_recv_options(opt)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: Transport> iota_streams_app::transport::Transport<Address, Message> for FlakyTransport<T> {
+    fn send_message(&mut self, msg: &Message) -> Result<()> {
+        if let Some(n) = self.sends_left.get() {
+            try_or!(n > 0, TransportNotAvailable)?;
+            self.sends_left.set(Some(n - 1));
+        }
+        self.transport.send_message(msg)
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        try_or!(self.fail_recv.borrow().as_ref() != Some(link), TransportNotAvailable)?;
+        self.transport.recv_messages(link)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn fetch_all<T: Transport>(user: &mut User<T>) -> Vec<UnwrappedMessage> {
+    let mut msgs = Vec::new();
+    loop {
+        let next = user.fetch_next_msgs();
+        if next.is_empty() {
+            return msgs;
+        }
+        msgs.extend(next);
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn cursor_states(ids: Vec<(PublicKey, Cursor<Address>)>) -> Vec<(Vec<u8>, iota_streams_core::prelude::String, u32)> {
+    let mut states: Vec<_> = ids
+        .into_iter()
+        .map(|(pk, cursor)| (pk.as_bytes().to_vec(), cursor.link.to_string(), cursor.seq_no))
+        .collect();
+    states.sort();
+    states
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_transaction<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use iota_streams_ddml::link_store::LinkStore as _;
+
+    // Small payload length so that keyload for all the subscribers is split into parts.
+    let payload_length = 512;
+    let encoding = "utf-8";
+    let author_transport = FlakyTransport::new(transport.clone());
+    let reader_transport = FlakyTransport::new(transport.clone());
+    let mut author = Author::new("AUTHOR9SEED", encoding, payload_length, true, author_transport.clone());
+    let mut reader = User::<FlakyTransport<T>>::new("READER9SEED", encoding, payload_length, false, reader_transport.clone());
+    let mut subscribers: Vec<Subscriber<T>> = (0..7)
+        .map(|i| Subscriber::new(&format!("SUBSCRIBER{}9SEED", i), encoding, payload_length, transport.clone()))
+        .collect();
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    reader.receive_announcement(&announcement_link)?;
+    let subscribe_link = reader.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    for subscriber in subscribers.iter_mut() {
+        subscriber.receive_announcement(&announcement_link)?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link)?;
+        author.receive_subscribe(&subscribe_link)?;
+    }
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    while !reader.fetch_next_msgs().is_empty() {}
+
+    // Packet which can't be sent leaves the author state unchanged and can be sent again.
+    let before = cursor_states(author.gen_next_msg_ids(true));
+    author_transport.sends_left.set(Some(0));
+    ensure!(
+        author.send_signed_packet(&keyload_link, &public_payload, &masked_payload).is_err(),
+        "signed packet sent"
+    );
+    author_transport.sends_left.set(None);
+    ensure!(before == cursor_states(author.gen_next_msg_ids(true)), "author state changed by failed packet");
+    let (packet_link, packet_seq) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+
+    // Sequence is unwrapped, but the sequenced message can't be fetched.
+    let before = (cursor_states(reader.gen_next_msg_ids(true)), reader.link_store_mut().iter().len());
+    *reader_transport.fail_recv.borrow_mut() = Some(packet_link.clone());
+    ensure!(reader.receive_message(&packet_seq.unwrap()).is_err(), "sequenced message fetched");
+    ensure!(reader.fetch_next_msgs().is_empty(), "sequenced message fetched");
+    let after = (cursor_states(reader.gen_next_msg_ids(true)), reader.link_store_mut().iter().len());
+    ensure!(before == after, "reader state changed by failed receive");
+    *reader_transport.fail_recv.borrow_mut() = None;
+    let msgs = reader.fetch_next_msgs();
+    ensure!(msgs.len() == 1, "expected one message, found {}", msgs.len());
+    match &msgs[0].body {
+        MessageContent::SignedPacket { masked_payload: m, .. } => ensure!(*m == masked_payload, "bad unwrapped masked payload"),
+        _ => ensure!(false, "expected signed packet"),
+    }
+
+    // Packet sent without its sequence message keeps the author state, the sequence message is
+    // sent again with the next packet.
+    let before = cursor_states(author.gen_next_msg_ids(true));
+    author_transport.sends_left.set(Some(1));
+    ensure!(
+        author.send_signed_packet(&keyload_link, &public_payload, &masked_payload).is_err(),
+        "sequence message sent"
+    );
+    author_transport.sends_left.set(None);
+    ensure!(before != cursor_states(author.gen_next_msg_ids(true)), "author state of sent packet undone");
+    ensure!(reader.fetch_next_msgs().is_empty(), "packet fetched without sequence message");
+    let masked_payload2 = Bytes("MASKEDPAYLOAD2".as_bytes().to_vec());
+    author.send_signed_packet(&keyload_link, &public_payload, &masked_payload2)?;
+    let msgs = fetch_all(&mut reader);
+    ensure!(msgs.len() == 2, "expected two messages, found {}", msgs.len());
+    for (msg, payload) in msgs.iter().zip([&masked_payload, &masked_payload2].iter()) {
+        match &msg.body {
+            MessageContent::SignedPacket { masked_payload: m, .. } => ensure!(m == *payload, "bad unwrapped masked payload"),
+            _ => ensure!(false, "expected signed packet"),
+        }
+    }
+
+    // Keyload interrupted after its first part was sent keeps the part, the keyload is sent
+    // again after it.
+    let before = cursor_states(author.gen_next_msg_ids(true));
+    author_transport.sends_left.set(Some(2));
+    ensure!(author.send_keyload_for_everyone(&keyload_link).is_err(), "keyload sent");
+    author_transport.sends_left.set(None);
+    ensure!(before != cursor_states(author.gen_next_msg_ids(true)), "author state of sent keyload part undone");
+    let (keyload_link2, _) = author.send_keyload_for_everyone(&keyload_link)?;
+    let (packet_link, _) = author.send_signed_packet(&keyload_link2, &public_payload, &masked_payload)?;
+    let msgs = fetch_all(&mut reader);
+    ensure!(msgs.iter().any(|msg| msg.link == keyload_link2), "keyload not fetched");
+    match msgs.iter().find(|msg| msg.link == packet_link).map(|msg| &msg.body) {
+        Some(MessageContent::SignedPacket { masked_payload: m, .. }) => ensure!(*m == masked_payload, "bad unwrapped masked payload"),
+        _ => ensure!(false, "packet after keyload not fetched"),
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_transaction_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_transaction(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_duplicate_messages<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut reader = User::<T>::new("READER9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    reader.receive_announcement(&announcement_link)?;
+    let subscribe_link = reader.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, keyload_seq) = author.send_keyload_for_everyone(&announcement_link)?;
+    reader.receive_message(&keyload_seq.unwrap())?;
+    let (first_link, first_seq) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let first_seq = first_seq.unwrap();
+    let first = reader.receive_message(&first_seq)?;
+    let (_, second_seq) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    reader.receive_message(&second_seq.unwrap())?;
+
+    // Handling the first sequence again doesn't move the cursor back.
+    let states = cursor_states(reader.gen_next_msg_ids(true));
+    for link in [&first_seq, &first_link].iter() {
+        let again = reader.receive_message(link)?;
+        ensure!(again.link == first.link, "different message returned for {}", link);
+        match (&first.body, &again.body) {
+            (
+                MessageContent::SignedPacket { masked_payload: m1, .. },
+                MessageContent::SignedPacket { masked_payload: m2, .. },
+            ) => ensure!(m1 == m2, "different payload returned for {}", link),
+            _ => ensure!(false, "expected signed packet for {}", link),
+        }
+        ensure!(states == cursor_states(reader.gen_next_msg_ids(true)), "state changed by duplicate {}", link);
+    }
+
+    // Typed receives return the processed packets again and leave the cursors as they are.
+    let (tagged_link, tagged_seq) = author.send_tagged_packet(&keyload_link, &public_payload, &masked_payload)?;
+    ensure!(reader.receive_sequence(&tagged_seq.unwrap())? == tagged_link, "bad sequenced link");
+    let states = cursor_states(reader.gen_next_msg_ids(true));
+    for _ in 0..2 {
+        let (_, unwrapped_masked) = reader.receive_tagged_packet(&tagged_link)?;
+        ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+        let (_, _, unwrapped_masked) = reader.receive_signed_packet(&first_link)?;
+        ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+        ensure!(reader.receive_sequence(&first_seq)? == first_link, "bad sequenced link");
+        ensure!(states == cursor_states(reader.gen_next_msg_ids(true)), "state changed by typed receive");
+    }
+
+    // Message seen on another transport is recognized, a different one at the same link is not.
+    let mut msg = transport.clone().recv_message(&first_link)?;
+    reader.handle_message(msg.clone())?;
+    let last = msg.binary.body.bytes.len() - 1;
+    msg.binary.body.bytes[last] ^= 1;
+    ensure!(reader.handle_message(msg).is_err(), "altered message accepted");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_duplicate_messages_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_duplicate_messages(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_preview<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use iota_streams_ddml::link_store::LinkStore as _;
+
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut reader = User::<T>::new("READER9SEED", encoding, PAYLOAD_BYTES, false, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    reader.receive_announcement(&announcement_link)?;
+    let subscribe_link = reader.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, keyload_seq) = author.send_keyload_for_everyone(&announcement_link)?;
+    let keyload_seq = keyload_seq.unwrap();
+
+    // Keyload and the packet attached to it are previewed, nothing is stored.
+    let state = |reader: &mut User<T>| (cursor_states(reader.gen_next_msg_ids(true)), reader.link_store_mut().iter().len());
+    let before = state(&mut reader);
+    let keyload = reader.peek_message(&keyload_seq)?;
+    ensure!(keyload.link == keyload_link, "bad previewed keyload link");
+    ensure!(before == state(&mut reader), "state changed by keyload preview");
+
+    reader.receive_message(&keyload_seq)?;
+    let (packet_link, packet_seq) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let packet_seq = packet_seq.unwrap();
+    let before = state(&mut reader);
+    for _ in 0..2 {
+        let packet = reader.peek_message(&packet_seq)?;
+        ensure!(packet.link == packet_link, "bad previewed packet link");
+        match packet.body {
+            MessageContent::SignedPacket { masked_payload: m, .. } => ensure!(m == masked_payload, "bad previewed masked payload"),
+            _ => ensure!(false, "expected signed packet"),
+        }
+        ensure!(before == state(&mut reader), "state changed by packet preview");
+    }
+
+    // Message is processed normally after the preview.
+    let packet = reader.receive_message(&packet_seq)?;
+    ensure!(packet.link == packet_link, "bad received packet link");
+    ensure!(before != state(&mut reader), "state not changed by received packet");
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+    subscriberA.receive_announcement(&announcement_link)?;
+    ensure!(subscriberA.preview_msg(&packet_seq).is_err(), "unsubscribed preview of masked packet");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_preview_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_preview(transport)).is_ok());
+}
+
+#[cfg(not(feature = "async"))]
+pub fn example_inspect<T: Transport + Clone>(transport: T) -> Result<()>
+{
+    use iota_streams_app::message::inspect;
+
+    let encoding = "utf-8";
+    let mut author = Author::new("AUTHOR9SEED", encoding, PAYLOAD_BYTES, true, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9SEED", encoding, PAYLOAD_BYTES, transport.clone());
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+
+    let announcement_link = author.send_announce()?;
+    subscriberA.receive_announcement(&announcement_link)?;
+    let subscribe_link = subscriberA.send_subscribe(&announcement_link)?;
+    author.receive_subscribe(&subscribe_link)?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link)?;
+    let (packet_link, packet_seq) = author.send_signed_packet(&keyload_link, &public_payload, &masked_payload)?;
+    let packet_seq = packet_seq.unwrap();
+
+    // Headers are read by anyone with access to the transport.
+    let mut transport = transport;
+    for (link, content_type) in [
+        (&announcement_link, crate::message::ANNOUNCE),
+        (&keyload_link, crate::message::KEYLOAD),
+        (&packet_link, crate::message::SIGNED_PACKET),
+        (&packet_seq, crate::message::SEQUENCE),
+    ]
+    .iter()
+    {
+        let msg = transport.recv_message(link)?;
+        let info = inspect(&msg)?;
+        ensure!(info.content_type == *content_type, "bad content type of {}: {}", link, info.content_type);
+        ensure!(info.appinst == link.appinst && info.msgid == link.msgid, "bad link of {}", link);
+        ensure!(info.timestamp == msg.timestamp, "bad timestamp of {}", link);
+        ensure!(info.payload_length <= PAYLOAD_BYTES, "bad payload length of {}", link);
+    }
+
+    // Inspection doesn't need a user, the messages are still received afterwards.
+    subscriberA.receive_keyload(&keyload_link)?;
+    let (_, _, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link)?;
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped masked payload");
+    let mut msg = transport.recv_message(&packet_link)?;
+    msg.binary.body.bytes[1] = 1;
+    ensure!(inspect(&msg).is_err(), "unknown version inspected");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "async"))]
+fn run_inspect_scenario() {
+    let transport = iota_streams_app::transport::new_shared_transport(
+        crate::api::tangle::BucketTransport::new());
+    assert!(dbg!(example_inspect(transport)).is_ok());
+}
@@ -0,0 +1,292 @@
+//! Staging of user state changes made by a tangle User operation.
+//!
+//! Before an operation the user state is saved, except the link store which records
+//! the values replaced during the operation instead. The staged changes are kept
+//! if the operation succeeds and undone otherwise. Changes staged before a message is
+//! sent are kept once it's sent, so that a failed operation doesn't reuse the state
+//! of a published message.
+//!
+//! Previewed messages are not written to the link store at all, its changes are kept
+//! apart and dropped once the preview is done.
+//!
+//! Links changed otherwise are also noted when tracking is enabled, so that a checkpoint
+//! writes them without going through the whole store.
+
+use iota_streams_app::transport::tangle::MsgId;
+use iota_streams_core::{
+    err,
+    prelude::{
+        HashMap,
+        HashSet,
+        ToString,
+        Vec,
+    },
+    sponge::{
+        prp::Inner,
+        spongos::Spongos,
+    },
+    Errors::*,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    link_store::LinkStore,
+    types::*,
+};
+
+use super::{
+    user::UserImp,
+    Address,
+    Certificate,
+    DefaultF,
+    LinkGen,
+    MsgInfo,
+    PkStore,
+    PskId,
+    PskIds,
+    PskStore,
+    PublicKey,
+    Role,
+    TangleLinkStore,
+};
+use crate::api::{
+    key_tree::KeyTree,
+    rekey::RekeyState,
+};
+
+/// Value of a link, `None` if the link is not kept.
+type LinkValue = Option<(Inner<DefaultF>, MsgInfo)>;
+type Undo = Vec<(MsgId, LinkValue)>;
+
+/// Link store wrapper recording the replaced values while an operation is staged.
+/// Links evicted by the wrapped store on its own are not restored.
+#[derive(Default)]
+pub struct StagedLinkStore<LS> {
+    store: LS,
+    undo: Option<Undo>,
+    /// Changes made during a preview, erased links are `None`.
+    preview: Option<HashMap<MsgId, LinkValue>>,
+    /// Links inserted, updated or erased since the changes were last cleared, if tracked.
+    changed: Option<HashSet<MsgId>>,
+}
+
+impl<LS: TangleLinkStore> StagedLinkStore<LS> {
+    pub fn get_ref(&self) -> &LS {
+        &self.store
+    }
+
+    pub fn get_mut(&mut self) -> &mut LS {
+        &mut self.store
+    }
+
+    /// Start recording, return false if already recording.
+    pub(crate) fn begin(&mut self) -> bool {
+        if self.undo.is_some() {
+            false
+        } else {
+            self.undo = Some(Vec::new());
+            true
+        }
+    }
+
+    pub(crate) fn is_staging(&self) -> bool {
+        self.undo.is_some()
+    }
+
+    /// Keep the changes and stop recording.
+    pub(crate) fn commit(&mut self) {
+        self.undo = None;
+    }
+
+    /// Restore the replaced values in reverse order and stop recording.
+    pub(crate) fn rollback(&mut self) -> Result<()> {
+        if let Some(undo) = self.undo.take() {
+            for (link, old) in undo.into_iter().rev() {
+                match old {
+                    Some((inner, info)) => self.store.insert(&link, inner, info)?,
+                    None => self.store.try_erase(&link)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Start noting changed links, eg. for checkpoints.
+    pub(crate) fn track_changes(&mut self) {
+        if self.changed.is_none() {
+            self.changed = Some(HashSet::new());
+        }
+    }
+
+    /// Links changed since `clear_changed` with their current values, `None` for erased links.
+    pub(crate) fn changed(&self) -> Result<Vec<(MsgId, LinkValue)>> {
+        let mut links = Vec::new();
+        if let Some(ref changed) = self.changed {
+            for link in changed {
+                let value = match self.store.lookup(link) {
+                    Ok((spongos, info)) => Some((spongos.to_inner()?, info)),
+                    Err(_) => None,
+                };
+                links.push((link.clone(), value));
+            }
+        }
+        Ok(links)
+    }
+
+    pub(crate) fn clear_changed(&mut self) {
+        if let Some(ref mut changed) = self.changed {
+            changed.clear();
+        }
+    }
+
+    /// Keep the following changes apart from the wrapped store.
+    pub(crate) fn begin_preview(&mut self) {
+        self.preview = Some(HashMap::new());
+    }
+
+    /// Drop the changes kept since `begin_preview`.
+    pub(crate) fn end_preview(&mut self) {
+        self.preview = None;
+    }
+
+    fn record(&mut self, link: &MsgId) -> Result<()> {
+        if let Some(ref mut changed) = self.changed {
+            changed.insert(link.clone());
+        }
+        if let Some(ref mut undo) = self.undo {
+            let old = match self.store.lookup(link) {
+                Ok((spongos, info)) => Some((spongos.to_inner()?, info)),
+                Err(_) => None,
+            };
+            undo.push((link.clone(), old));
+        }
+        Ok(())
+    }
+}
+
+impl<LS: TangleLinkStore> LinkStore<DefaultF, MsgId> for StagedLinkStore<LS> {
+    type Info = MsgInfo;
+
+    fn lookup(&self, link: &MsgId) -> Result<(Spongos<DefaultF>, MsgInfo)> {
+        match self.preview.as_ref().and_then(|preview| preview.get(link)) {
+            Some(Some((inner, info))) => Ok((inner.into(), *info)),
+            Some(None) => err!(MessageLinkNotFoundInTangle(link.to_string())),
+            None => self.store.lookup(link),
+        }
+    }
+
+    fn update(&mut self, link: &MsgId, spongos: Spongos<DefaultF>, info: MsgInfo) -> Result<()> {
+        if self.preview.is_some() {
+            return self.insert(link, spongos.to_inner()?, info);
+        }
+        self.record(link)?;
+        self.store.update(link, spongos, info)
+    }
+
+    fn insert(&mut self, link: &MsgId, inner: Inner<DefaultF>, info: MsgInfo) -> Result<()> {
+        if let Some(ref mut preview) = self.preview {
+            preview.insert(link.clone(), Some((inner, info)));
+            return Ok(());
+        }
+        self.record(link)?;
+        self.store.insert(link, inner, info)
+    }
+
+    /// Remove link, failures of the wrapped store are ignored, see `try_erase`.
+    fn erase(&mut self, link: &MsgId) {
+        let _ = self.try_erase(link);
+    }
+
+    fn try_erase(&mut self, link: &MsgId) -> Result<()> {
+        if let Some(ref mut preview) = self.preview {
+            preview.insert(link.clone(), None);
+            return Ok(());
+        }
+        self.record(link)?;
+        self.store.try_erase(link)
+    }
+
+    fn pin(&mut self, links: Vec<MsgId>) {
+        self.store.pin(links)
+    }
+
+    /// Links of the wrapped store, changes made during a preview are not included.
+    fn iter(&self) -> Vec<(&MsgId, &(Inner<DefaultF>, MsgInfo))> {
+        self.store.iter()
+    }
+}
+
+/// User state besides the link store saved before an operation.
+pub(crate) struct Saved {
+    psk_store: PskStore,
+    pk_store: PkStore,
+    key_tree: KeyTree,
+    keyload_part_key: Option<(MsgId, NBytes<U32>)>,
+    pending_subscribers: Vec<PublicKey>,
+    roles: HashMap<ed25519::PublicKeyWrap, Role>,
+    certificates: HashMap<ed25519::PublicKeyWrap, Certificate>,
+    author_sig_pk: Option<PublicKey>,
+    admins: Vec<PublicKey>,
+    auditors: Vec<PublicKey>,
+    auditor_psks: PskIds,
+    topics: Vec<(Bytes, MsgId)>,
+    followed_topics: Option<Vec<Bytes>>,
+    topic_links: HashMap<MsgId, MsgId>,
+    latest_keyloads: HashMap<MsgId, MsgId>,
+    rekey: RekeyState<MsgId>,
+    link_gen: LinkGen,
+    appinst: Option<Address>,
+    announce_pskid: Option<PskId>,
+    flags: u8,
+}
+
+impl Saved {
+    pub(crate) fn of<LS: TangleLinkStore>(user: &UserImp<LS>) -> Self {
+        Self {
+            psk_store: user.psk_store.clone(),
+            pk_store: user.pk_store.clone(),
+            key_tree: user.key_tree.clone(),
+            keyload_part_key: user.keyload_part_key.clone(),
+            pending_subscribers: user.pending_subscribers.clone(),
+            roles: user.roles.clone(),
+            certificates: user.certificates.clone(),
+            author_sig_pk: user.author_sig_pk,
+            admins: user.admins.clone(),
+            auditors: user.auditors.clone(),
+            auditor_psks: user.auditor_psks.clone(),
+            topics: user.topics.clone(),
+            followed_topics: user.followed_topics.clone(),
+            topic_links: user.topic_links.clone(),
+            latest_keyloads: user.latest_keyloads.clone(),
+            rekey: user.rekey.clone(),
+            link_gen: user.link_gen.clone(),
+            appinst: user.appinst.clone(),
+            announce_pskid: user.announce_pskid,
+            flags: user.flags,
+        }
+    }
+
+    pub(crate) fn restore<LS: TangleLinkStore>(self, user: &mut UserImp<LS>) {
+        user.psk_store = self.psk_store;
+        user.pk_store = self.pk_store;
+        user.key_tree = self.key_tree;
+        user.keyload_part_key = self.keyload_part_key;
+        user.pending_subscribers = self.pending_subscribers;
+        user.roles = self.roles;
+        user.certificates = self.certificates;
+        user.author_sig_pk = self.author_sig_pk;
+        user.admins = self.admins;
+        user.auditors = self.auditors;
+        user.auditor_psks = self.auditor_psks;
+        user.topics = self.topics;
+        user.followed_topics = self.followed_topics;
+        user.topic_links = self.topic_links;
+        user.latest_keyloads = self.latest_keyloads;
+        user.rekey = self.rekey;
+        user.link_gen = self.link_gen;
+        user.appinst = self.appinst;
+        user.announce_pskid = self.announce_pskid;
+        user.flags = self.flags;
+    }
+}
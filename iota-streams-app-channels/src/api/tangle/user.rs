@@ -1,24 +1,51 @@
-use iota_streams_app::message::{
-    HasLink as _,
-    LinkGenerator,
+use iota_streams_app::{
+    message::{
+        HasLink as _,
+        LinkGenerator,
+    },
+    transport::tangle::timestamp_now,
+};
+use iota_streams_core::{prelude::{Box, Vec, string::ToString}, prng, {err, Result, LOCATION_LOG}, Errors::{RekeyBranchNotEveryone, UserNotRegistered, UnknownMsgType}, panic_if_not};
+use iota_streams_ddml::types::NBytes;
+use iota_streams_core_edsig::key_exchange::x25519;
+
+use super::{
+    checkpoint::Checkpoint,
+    processed::{
+        Detail,
+        Processed,
+        ProcessedKey,
+        ProcessedMessages,
+    },
+    transaction::Saved,
+    *,
 };
-use iota_streams_core::{prelude::Vec, prng, {err, Result, LOCATION_LOG}, Errors::{UserNotRegistered, UnknownMsgType}, panic_if_not};
-
-use super::*;
 use crate::{
-    api,
+    api::{
+        self,
+        rekey::Rekey,
+        storage::Storage,
+    },
     message,
 };
 
-type UserImp = api::user::User<DefaultF, Address, LinkGen, LinkStore, PkStore, PskStore>;
+pub(crate) type UserImp<LS = LinkStore> = api::user::User<DefaultF, Address, LinkGen, StagedLinkStore<LS>, PkStore, PskStore>;
 
 /// Baseline User api object. Contains the api user implementation as well as the transport object
-pub struct User<Trans> {
-    pub user: UserImp,
+pub struct User<Trans, LS = LinkStore> {
+    pub user: UserImp<LS>,
     pub transport: Trans,
+    /// Checkpoint of user state in storage, updated after each committed message.
+    checkpoint: Option<Checkpoint>,
+    /// Results of handled messages returned when a message is handled again.
+    processed: ProcessedMessages,
+    /// User state after the last message sent by the operation in progress, kept if it fails.
+    sent: Option<Saved>,
+    /// Sequence message which failed to be sent after its message, sent before the next message.
+    unsent_sequence: Option<Message>,
 }
 
-impl<Trans> User<Trans>
+impl<Trans, LS: TangleLinkStore> User<Trans, LS>
 {
     /// Create a new User instance.
     ///
@@ -30,15 +57,38 @@ impl<Trans> User<Trans>
     /// * `transport` - Transport object used for sending and receiving
     ///
     pub fn new(seed: &str, encoding: &str, payload_length: usize, multi_branching: bool, transport: Trans) -> Self {
+        let flags = if multi_branching { FLAG_BRANCHING_MASK } else { 0 };
+        Self::new_with_flags(seed, encoding, payload_length, flags, transport)
+    }
+
+    /// Create a new User instance with the given channel flags.
+    ///
+    /// # Arguments
+    /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
+    /// * `encoding` - A string slice representing the encoding type for the message [supported: utf-8]
+    /// * `payload_length` - Maximum size in bytes of payload per message chunk [1-1024],
+    /// * `flags` - Channel flags bit field, see `FLAG_BRANCHING_MASK`, `FLAG_ANONYMOUS_KEYLOAD_MASK`,
+    ///   `FLAG_RATCHET_MASK`, `FLAG_CERTIFIED_PUBLISHERS_MASK`, `FLAG_ADMINS_MASK` and `FLAG_AUDITORS_MASK`.
+    ///   Anonymous keyloads disclose publishers to recipients only, see `FLAG_ANONYMOUS_KEYLOAD_MASK`
+    /// * `transport` - Transport object used for sending and receiving
+    ///
+    pub fn new_with_flags(seed: &str, encoding: &str, payload_length: usize, flags: u8, transport: Trans) -> Self {
         let nonce = "TANGLEUSERNONCE".as_bytes().to_vec();
-        let user = UserImp::gen(
+        let user = UserImp::<LS>::gen(
             prng::from_seed("IOTA Streams Channels user sig keypair", seed),
             nonce,
-            if multi_branching { 1 } else { 0 },
+            flags,
             encoding.as_bytes().to_vec(),
             payload_length,
         );
-        Self { user, transport }
+        Self {
+            user,
+            transport,
+            checkpoint: None,
+            processed: ProcessedMessages::default(),
+            sent: None,
+            unsent_sequence: None,
+        }
     }
 
     // Attributes
@@ -53,6 +103,69 @@ impl<Trans> User<Trans>
         self.user.is_multi_branching()
     }
 
+    /// Return boolean representing whether keyloads hide recipient identities
+    pub fn is_anonymous_keyload(&self) -> bool {
+        self.user.is_anonymous_keyload()
+    }
+
+    /// Return boolean representing whether packets ratchet the session key forward
+    pub fn is_ratchet(&self) -> bool {
+        self.user.is_ratchet()
+    }
+
+    /// Return boolean representing whether signed packets require a certificate issued by Author
+    pub fn is_certified_publishers(&self) -> bool {
+        self.user.is_certified_publishers()
+    }
+
+    /// Return boolean representing whether channel address and announcement are hidden with a pre-shared key
+    pub fn is_hidden(&self) -> bool {
+        self.user.is_hidden()
+    }
+
+    /// Fetch the Address of the topic root message, topic keyloads are attached to it.
+    ///
+    ///   # Arguments
+    ///   * `name` - Topic name
+    ///
+    pub fn topic(&self, name: &Bytes) -> Option<Address> {
+        match (&self.user.appinst, self.user.topic(name)) {
+            (Some(appinst), Some(root)) => Some(Address::from_base_rel(&appinst.appinst, root)),
+            _ => None,
+        }
+    }
+
+    /// Fetch the names of the known topics.
+    pub fn topics(&self) -> Vec<Bytes> {
+        self.user.topics().iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Follow only the given topics, messages of other topics are skipped by `fetch_next_msgs`.
+    /// Messages outside of topic branches are always fetched. Topics require a multi-branching
+    /// channel, so this fails once registered to a single-branch channel.
+    ///
+    ///   # Arguments
+    ///   * `names` - Names of the followed topics
+    ///
+    pub fn follow_topics(&mut self, names: Vec<Bytes>) -> Result<()> {
+        self.user.follow_topics(names)
+    }
+
+    /// Follow all the topics.
+    pub fn follow_all_topics(&mut self) {
+        self.user.follow_all_topics()
+    }
+
+    /// Store a pre-shared key, it's used in keyloads and to unmask hidden announcements.
+    ///
+    ///   # Arguments
+    ///   * `pskid` - Identifier of the pre-shared key
+    ///   * `psk` - Pre-shared key
+    ///
+    pub fn store_psk(&mut self, pskid: PskId, psk: Psk) {
+        self.user.store_psk(pskid, psk)
+    }
+
     /// Fetch the user ed25519 public key
     pub fn get_pk(&self) -> &PublicKey {
         &self.user.sig_kp.public
@@ -79,7 +192,8 @@ impl<Trans> User<Trans>
     ///
    pub fn store_state(&mut self, pk: PublicKey, link: &Address) -> Result<()> {
         // TODO: assert!(link.appinst == self.appinst.unwrap());
-        self.user.store_state(pk, link.msgid.clone())
+        self.user.store_state(pk, link.msgid.clone())?;
+        self.checkpoint()
     }
 
     /// Stores the provided link and sequence number to the internal sequencing state for all participants
@@ -92,7 +206,8 @@ impl<Trans> User<Trans>
     ///
     pub fn store_state_for_all(&mut self, link: &Address, seq_num: u32) -> Result<()> {
         // TODO: assert!(link.appinst == self.appinst.unwrap());
-        self.user.store_state_for_all(link.msgid.clone(), seq_num)
+        self.user.store_state_for_all(link.msgid.clone(), seq_num)?;
+        self.checkpoint()
     }
 
     /// Generate a vector containing the next sequenced message identifier for each publishing
@@ -106,16 +221,322 @@ impl<Trans> User<Trans>
         self.user.gen_next_msg_ids(branching)
     }
 
+    /// Add a subscriber to the key tree, its leaf key is sent with the next tree keyload [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of a subscribed participant
+    ///
+    pub fn add_tree_member(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.add_tree_member(pk)
+    }
+
+    /// Remove a subscriber from the key tree, it can't read messages attached to the following
+    /// tree keyloads [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the removed participant
+    ///
+    pub fn remove_tree_member(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.remove_tree_member(pk)
+    }
+
+    /// Refresh key tree group key without changing members [Author].
+    pub fn rekey_tree(&mut self) {
+        self.user.rekey_tree()
+    }
+
+    /// Assign a role to a subscriber, roles are distributed with the next role assignment [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of a subscribed participant
+    ///   * `role` - Role of the participant
+    ///
+    pub fn set_role(&mut self, pk: &PublicKey, role: Role) -> Result<()> {
+        self.user.set_role(pk, role)
+    }
+
+    /// Fetch the role assigned to a participant [Author, Subscriber].
+    pub fn role(&self, pk: &PublicKey) -> Option<Role> {
+        self.user.role(pk)
+    }
+
+    /// Fetch the latest certificate issued for a publisher [Author, Subscriber].
+    pub fn certificate(&self, pk: &PublicKey) -> Option<&Certificate> {
+        self.user.certificate(pk)
+    }
+
+    /// Add a channel administrator, it's announced with the channel or with the next admin set [Author].
+    /// Channels announced without administrators and without `FLAG_ADMINS_MASK` can't have them.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the administrator
+    ///
+    pub fn add_admin(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.add_admin(pk)
+    }
+
+    /// Remove a channel administrator, subscribers learn it with the next admin set [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the administrator
+    ///
+    pub fn remove_admin(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.remove_admin(pk)
+    }
+
+    /// Fetch administrators of the channel besides Author [Author, Subscriber].
+    pub fn admins(&self) -> &[PublicKey] {
+        self.user.admins()
+    }
+
+    /// Add an auditor, the session key of every keyload is shared with it. Auditors are recorded
+    /// in the announcement and must be added before the channel is announced [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the auditor
+    ///
+    pub fn add_auditor(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.add_auditor(pk)
+    }
+
+    /// Add a pre-shared key to every keyload, it's recorded in the announcement and must be added
+    /// before the channel is announced [Author].
+    ///
+    ///   # Arguments
+    ///   * `pskid` - Identifier of a stored pre-shared key
+    ///
+    pub fn add_auditor_psk(&mut self, pskid: PskId) -> Result<()> {
+        self.user.add_auditor_psk(pskid)
+    }
+
+    /// Fetch auditor public keys and pre-shared key identifiers included in every keyload [Author, Subscriber].
+    pub fn auditors(&self) -> (&[PublicKey], &[PskId]) {
+        self.user.auditors()
+    }
+
+    /// Return boolean representing whether the user can send keyloads and accept subscriptions
+    pub fn is_admin(&self) -> bool {
+        self.user.is_admin(&self.user.sig_kp.public)
+    }
+
+    /// Set the policy applied to incoming subscriptions [Author].
+    ///
+    ///   # Arguments
+    ///   * `policy` - Subscription policy, subscribers it doesn't approve are kept pending
+    ///
+    pub fn set_subscription_policy(&mut self, policy: SubscriptionPolicy) {
+        self.user.set_subscription_policy(policy)
+    }
+
+    /// Fetch the public keys of subscribers awaiting approval [Author].
+    pub fn pending_subscribers(&self) -> &[PublicKey] {
+        self.user.pending_subscribers()
+    }
+
+    /// Approve a pending subscriber [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the pending subscriber
+    ///
+    pub fn approve_subscriber(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.approve_subscriber(pk)
+    }
+
+    /// Reject a pending subscriber [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 Public Key of the pending subscriber
+    ///
+    pub fn reject_subscriber(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.reject_subscriber(pk)
+    }
+
+    /// Serialize user state and encrypt it with password. Links are exported as iterated by
+    /// the link store, so a `PersistentLinkStore` with attached storage exports none of them:
+    /// import the state into a `PersistentUser` and attach the same storage again.
+    ///
+    ///   # Arguments
+    ///   * `flag` - User type flag checked on import
+    ///   * `pwd` - Encryption password
+    ///
     pub fn export(&self, flag: u8, pwd: &str) -> Result<Vec<u8>> {
         self.user.export(flag, pwd)
     }
+    /// Serialize user state like `export` with custom Argon2id parameters.
+    pub fn export_with_kdf(&self, flag: u8, pwd: &str, kdf: &KdfParams) -> Result<Vec<u8>> {
+        self.user.export_with_kdf(flag, pwd, kdf)
+    }
+
+    /// Link store of the user, eg. to change limits of `LruLinkStore` or `AgedLinkStore`.
+    pub fn link_store_mut(&mut self) -> &mut LS {
+        self.user.link_store.get_mut().get_mut()
+    }
+
+    /// Erase link store entries not needed to continue in the channel and write a new
+    /// checkpoint snapshot if storage is attached. Return the number of erased entries.
+    /// Only the iterated entries are erased, so links in storage attached to
+    /// `PersistentLinkStore` are kept.
+    pub fn prune_link_store(&mut self) -> Result<usize> {
+        let erased = self.user.prune_link_store()?;
+        self.checkpoint_snapshot()?;
+        Ok(erased)
+    }
+    pub fn export_compact(&mut self, flag: u8, pwd: &str) -> Result<(Vec<u8>, usize)> {
+        let erased = self.prune_link_store()?;
+        Ok((self.user.export(flag, pwd)?, erased))
+    }
     pub fn import(bytes: &[u8], flag: u8, pwd: &str, tsp: Trans) -> Result<Self> {
-        UserImp::import(bytes, flag, pwd).map(|u| Self { user: u, transport: tsp, })
+        UserImp::<LS>::import(bytes, flag, pwd).map(|u| Self {
+            user: u,
+            transport: tsp,
+            checkpoint: None,
+            processed: ProcessedMessages::default(),
+            sent: None,
+            unsent_sequence: None,
+        })
+    }
+    pub fn export_to(&self, flag: u8, ke_pk: &x25519::PublicKey) -> Result<Vec<u8>> {
+        self.user.export_to(flag, ke_pk)
+    }
+    pub fn import_with(bytes: &[u8], flag: u8, ke_sk: &x25519::StaticSecret, tsp: Trans) -> Result<Self> {
+        UserImp::<LS>::import_with(bytes, flag, ke_sk).map(|u| Self {
+            user: u,
+            transport: tsp,
+            checkpoint: None,
+            processed: ProcessedMessages::default(),
+            sent: None,
+            unsent_sequence: None,
+        })
+    }
+
+    /// Write user state to `storage` and keep it up to date: an encrypted incremental checkpoint
+    /// is written after each sent or received message. Resume the user with `open`.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend, previous checkpoint in it is overwritten
+    ///   * `flag` - User type flag checked on open
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///   * `kdf` - Argon2id parameters
+    ///
+    pub fn attach_storage<S: Storage + 'static>(&mut self, storage: S, flag: u8, pwd: &str, kdf: &KdfParams) -> Result<()> {
+        self.checkpoint = Some(Checkpoint::create(Box::new(storage), flag, pwd, kdf, &mut self.user)?);
+        Ok(())
+    }
+
+    /// Resume user from the checkpoint in `storage`, the checkpoint is kept up to date afterwards.
+    ///
+    ///   # Arguments
+    ///   * `storage` - Storage backend
+    ///   * `flag` - User type flag
+    ///   * `pwd` - Password the checkpoint key is derived from
+    ///   * `tsp` - Transport object used for sending and receiving
+    ///
+    pub fn open<S: Storage + 'static>(storage: S, flag: u8, pwd: &str, tsp: Trans) -> Result<Self> {
+        let (checkpoint, user) = Checkpoint::open(Box::new(storage), flag, pwd)?;
+        Ok(Self {
+            user,
+            transport: tsp,
+            checkpoint: Some(checkpoint),
+            processed: ProcessedMessages::default(),
+            sent: None,
+            unsent_sequence: None,
+        })
+    }
+
+    /// Write changes of link store, sequencing states and pre-shared keys made since the last
+    /// checkpoint. It's done automatically after each message, call it after `store_psk`.
+    /// Does nothing if no storage is attached or while an operation is in progress.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        if self.user.link_store.get_mut().is_staging() {
+            return Ok(());
+        }
+        match self.checkpoint {
+            Some(ref mut checkpoint) => checkpoint.update(&mut self.user),
+            None => Ok(()),
+        }
+    }
+
+    /// Write a snapshot of the whole user state, eg. after changing roles, admins or topics locally.
+    /// Does nothing if no storage is attached.
+    pub fn checkpoint_snapshot(&mut self) -> Result<()> {
+        match self.checkpoint {
+            Some(ref mut checkpoint) => checkpoint.snapshot(&mut self.user),
+            None => Ok(()),
+        }
+    }
+
+    /// Start staging state changes of an operation, `None` if an enclosing operation is staged already.
+    fn begin(&mut self) -> Option<Saved> {
+        if self.user.link_store.get_mut().begin() {
+            Some(Saved::of(&self.user))
+        } else {
+            None
+        }
+    }
+
+    /// Keep the changes staged by `begin` if the operation succeeded, otherwise restore the state
+    /// saved before it. Nested operations leave it to the enclosing one. Links needed to continue
+    /// in the channel are pinned afterwards, see `LinkStore::pin`.
+    fn end<R>(&mut self, saved: Option<Saved>, result: Result<R>) -> Result<R> {
+        if let Some(saved) = saved {
+            let sent = self.sent.take();
+            if result.is_ok() {
+                self.user.link_store.get_mut().commit();
+            } else {
+                sent.unwrap_or(saved).restore(&mut self.user);
+                self.user.link_store.get_mut().rollback()?;
+            }
+            self.user.pin_essential_links();
+        }
+        result
+    }
+
+    /// Keep the changes staged so far, the operation in progress has sent a message and
+    /// the state it was sent from can't be reused. A failure later in the operation only
+    /// undoes the changes made after it.
+    fn sent(&mut self) {
+        let link_store = self.user.link_store.get_mut();
+        if link_store.is_staging() {
+            link_store.commit();
+            link_store.begin();
+            self.sent = Some(Saved::of(&self.user));
+        }
+    }
+
+    /// Start a preview, results of processed messages are put aside to be left as they are.
+    fn begin_preview(&mut self) -> (Saved, ProcessedMessages) {
+        self.user.link_store.get_mut().begin_preview();
+        (Saved::of(&self.user), core::mem::take(&mut self.processed))
+    }
+
+    /// Drop the changes made since `begin_preview`.
+    fn end_preview(&mut self, (saved, processed): (Saved, ProcessedMessages)) {
+        self.user.link_store.get_mut().end_preview();
+        saved.restore(&mut self.user);
+        self.processed = processed;
+    }
+
+    /// Whether the last checkpoint failed to be written. Operations don't fail when the message
+    /// was sent but the checkpoint after it wasn't written, call `checkpoint` to retry.
+    pub fn checkpoint_failed(&self) -> bool {
+        self.checkpoint.as_ref().is_some_and(|checkpoint| checkpoint.failed())
+    }
+
+    /// End the operation and checkpoint the changes kept, including those of the messages sent
+    /// by a failed operation.
+    fn finish<R>(&mut self, saved: Option<Saved>, result: Result<R>) -> Result<R> {
+        let outermost = saved.is_some();
+        let result = self.end(saved, result);
+        if outermost {
+            // Changes are kept until they are written, so a failed checkpoint is retried with the next one.
+            let _ = self.checkpoint();
+        }
+        result
     }
 }
 
 #[cfg(not(feature = "async"))]
-impl<Trans: Transport> User<Trans>
+impl<Trans: Transport, LS: TangleLinkStore> User<Trans, LS>
 {
     // Send
 
@@ -126,21 +547,33 @@ impl<Trans: Transport> User<Trans>
     /// * `wrapped` - A wrapped sequence object containing the sequence message and state
     ///
     fn send_sequence(&mut self, wrapped: WrappedSequence) -> Result<Option<Address>> {
-        if let Some(seq_msg) = wrapped.0 {
-            self.transport.send_message(&Message::new(seq_msg))?;
-        }
+        let link = match wrapped.1 {
+            Some(wrap_state) => self.user.commit_sequence(wrap_state, MsgInfo::Sequence)?,
+            None => None,
+        };
+        // The sequenced message is sent already, so its sequence message is sent again before
+        // the next message if it fails now.
+        self.unsent_sequence = wrapped.0.map(Message::new);
+        self.sent();
+        self.send_unsent_sequence()?;
+        Ok(link)
+    }
 
-        if let Some(wrap_state) = wrapped.1 {
-            self.user.commit_sequence(wrap_state, MsgInfo::Sequence)
-        } else {
-            Ok(None)
+    /// Send the sequence message which failed to be sent after its message, if any.
+    fn send_unsent_sequence(&mut self) -> Result<()> {
+        if let Some(ref seq_msg) = self.unsent_sequence {
+            self.transport.send_message(seq_msg)?;
         }
+        self.unsent_sequence = None;
+        Ok(())
     }
 
     /// Send a message without using sequencing logic. Reserved for Announce and Subscribe messages
     fn send_message(&mut self, msg: WrappedMessage, info: MsgInfo) -> Result<Address> {
         self.transport.send_message(&Message::new(msg.message))?;
-        self.user.commit_wrapped(msg.wrapped, info)
+        let link = self.user.commit_wrapped(msg.wrapped, info)?;
+        self.sent();
+        Ok(link)
     }
 
     /// Send a message using sequencing logic.
@@ -156,17 +589,55 @@ impl<Trans: Transport> User<Trans>
         ref_link: &MsgId,
         info: MsgInfo,
     ) -> Result<(Address, Option<Address>)> {
+        self.send_unsent_sequence()?;
         let seq = self.user.wrap_sequence(ref_link)?;
         self.transport.send_message(&Message::new(msg.message))?;
+        let msg_link = match info {
+            MsgInfo::SignedPacket | MsgInfo::TaggedPacket => self.user.commit_packet(msg.wrapped, ref_link, info)?,
+            _ => self.user.commit_wrapped(msg.wrapped, info)?,
+        };
+        self.user.track_topic(msg_link.msgid.clone(), ref_link);
         let seq_link = self.send_sequence(seq)?;
-        let msg_link = self.user.commit_wrapped(msg.wrapped, info)?;
         Ok((msg_link, seq_link))
     }
 
     /// Send an announcement message, generating a channel [Author].
     pub fn send_announce(&mut self) -> Result<Address> {
-        let msg = self.user.announce()?;
-        self.send_message(msg, MsgInfo::Announce)
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.announce()?;
+            self.send_message(msg, MsgInfo::Announce)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Apply Author's rekey policy before a packet linked to `link_to` is sent, see `RekeyPolicy`.
+    /// Returns the address the packet should be linked to. A keyload for everyone is attached to
+    /// the announcement so that new subscribers can read it, and sequenced after `link_to`.
+    /// Packets of branches for some of the recipients are not rekeyed, `RekeyBranchNotEveryone`
+    /// is returned when rekey is due.
+    fn rekey_if_due(&mut self, link_to: &Address) -> Result<Address> {
+        let everyone = self.user.keyload_recipients_for_everyone();
+        match self.user.rekey.check(&link_to.msgid, &everyone, timestamp_now()) {
+            Rekey::Keep => Ok(link_to.clone()),
+            Rekey::Follow(keyload) => Ok(Address {
+                appinst: link_to.appinst.clone(),
+                msgid: keyload,
+            }),
+            Rekey::After(prev) => {
+                let announcement = match &self.user.appinst {
+                    Some(appinst) => appinst.clone(),
+                    None => return err!(UserNotRegistered),
+                };
+                let prev = Address {
+                    appinst: link_to.appinst.clone(),
+                    msgid: prev,
+                };
+                let (keyload_link, _) = self.send_keyload_after(&announcement, &prev, &everyone.0, &everyone.1)?;
+                Ok(keyload_link)
+            }
+            Rekey::Restricted => err!(RekeyBranchNotEveryone(link_to.msgid.to_string())),
+        }
     }
 
     /// Create and send a signed packet [Author, Subscriber].
@@ -182,8 +653,15 @@ impl<Trans: Transport> User<Trans>
         public_payload: &Bytes,
         masked_payload: &Bytes,
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.sign_packet(&link_to.msgid, public_payload, masked_payload)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::SignedPacket)
+        let saved = self.begin();
+        let result = (|| {
+            let link_to = &self.rekey_if_due(link_to)?;
+            let msg = self.user.sign_packet(&link_to.msgid, public_payload, masked_payload, timestamp_now())?;
+            let links = self.send_message_sequenced(msg, link_to.rel(), MsgInfo::SignedPacket)?;
+            self.user.rekey.packet_sent(&links.0.msgid);
+            Ok(links)
+        })();
+        self.finish(saved, result)
     }
 
     /// Create and send a tagged packet [Author, Subscriber].
@@ -199,8 +677,15 @@ impl<Trans: Transport> User<Trans>
         public_payload: &Bytes,
         masked_payload: &Bytes,
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.tag_packet(&link_to.msgid, public_payload, masked_payload)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::TaggedPacket)
+        let saved = self.begin();
+        let result = (|| {
+            let link_to = &self.rekey_if_due(link_to)?;
+            let msg = self.user.tag_packet(&link_to.msgid, public_payload, masked_payload)?;
+            let links = self.send_message_sequenced(msg, link_to.rel(), MsgInfo::TaggedPacket)?;
+            self.user.rekey.packet_sent(&links.0.msgid);
+            Ok(links)
+        })();
+        self.finish(saved, result)
     }
 
     /// Create and send a new keyload for a list of subscribers [Author].
@@ -214,10 +699,42 @@ impl<Trans: Transport> User<Trans>
         &mut self,
         link_to: &Address,
         psk_ids: &PskIds,
-        ke_pks: &Vec<PublicKey>,
+        ke_pks: &[PublicKey],
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.share_keyload(&link_to.msgid, psk_ids, ke_pks)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload)
+        self.send_keyload_after(link_to, link_to, psk_ids, ke_pks)
+    }
+
+    /// Create and send a new keyload attached to `link_to`, but sequenced after `prev` [Author].
+    pub(crate) fn send_keyload_after(
+        &mut self,
+        link_to: &Address,
+        prev: &Address,
+        psk_ids: &PskIds,
+        ke_pks: &[PublicKey],
+    ) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let parts = self.user.split_keyload_recipients(&link_to.msgid, psk_ids, ke_pks)?;
+            let key = NBytes::from(prng::random_key());
+            let last = parts.len() - 1;
+            // Parts follow each other in the sequence, but are all joined to `link_to`.
+            let mut prev = prev.msgid.clone();
+            let mut links = None;
+            for (i, (psk_ids, ke_pks)) in parts.iter().enumerate() {
+                let msg = self.user.share_keyload_part(&link_to.msgid, &prev, psk_ids, ke_pks, key, i == last)?;
+                let info = if i == last { MsgInfo::Keyload } else { MsgInfo::KeyloadPart };
+                let (msg_link, seq_link) = self.send_message_sequenced(msg, &prev, info)?;
+                prev = msg_link.msgid.clone();
+                links = Some((msg_link, seq_link));
+            }
+            // At least one part is always produced.
+            let links = links.unwrap();
+            self.user.track_keyload(links.0.msgid.clone(), &link_to.msgid);
+            let everyone = self.user.keyload_recipients_for_everyone();
+            self.user.rekey.keyload_sent(links.0.msgid.clone(), (psk_ids.clone(), ke_pks.to_vec()), &everyone, timestamp_now());
+            Ok(links)
+        })();
+        self.finish(saved, result)
     }
 
     /// Create and send keyload for all subscribed subscribers [Author].
@@ -226,8 +743,166 @@ impl<Trans: Transport> User<Trans>
     ///  * `link_to` - Address of the message the keyload will be attached to
     ///
     pub fn send_keyload_for_everyone(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.share_keyload_for_everyone(&link_to.msgid)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload)
+        let (psk_ids, ke_pks) = self.user.keyload_recipients_for_everyone();
+        self.send_keyload(link_to, &psk_ids, &ke_pks)
+    }
+
+    /// Create and send a private keyload starting a branch readable by the given participants
+    /// only [Author, Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `ke_pks`  - Vector of Public Keys of known participants to be included in message
+    ///
+    pub fn send_private_keyload(&mut self, link_to: &Address, ke_pks: &[PublicKey]) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.share_private_keyload(&link_to.msgid, ke_pks)?;
+            let links = self.send_message_sequenced(msg, link_to.rel(), MsgInfo::PrivateKeyload)?;
+            self.user.track_keyload(links.0.msgid.clone(), &link_to.msgid);
+            Ok(links)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send a direct message readable by Author only [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the direct message will be attached to
+    ///  * `masked_payload` - Payload encrypted for Author
+    ///
+    pub fn send_direct_message(&mut self, link_to: &Address, masked_payload: &Bytes) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.send_direct_message(&link_to.msgid, masked_payload)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::DirectMessage)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send a topic declaration, the message is the root of the topic branch [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the topic will be attached to
+    ///  * `name` - Topic name, unique within the channel
+    ///
+    pub fn send_topic(&mut self, link_to: &Address, name: &Bytes) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.share_topic(&link_to.msgid, name)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Topic)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send tree keyload with pending key tree updates [Author]. Updates exceeding
+    /// `uniform_payload_length` are split into several tree keyloads, links of the last one are returned.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the tree keyload will be attached to
+    ///
+    pub fn send_tree_keyload(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let parts = self.user.split_tree_keyload_members(&link_to.msgid)?;
+            // Parts follow each other in the sequence, but are all joined to `link_to`.
+            let mut prev = link_to.msgid.clone();
+            let mut links = None;
+            for members in parts.iter() {
+                let msg = self.user.share_tree_keyload(&link_to.msgid, &prev, members)?;
+                let (msg_link, seq_link) = self.send_message_sequenced(msg, &prev, MsgInfo::TreeKeyload)?;
+                prev = msg_link.msgid.clone();
+                links = Some((msg_link, seq_link));
+            }
+            // At least one part is always produced.
+            let links = links.unwrap();
+            self.user.track_keyload(links.0.msgid.clone(), &link_to.msgid);
+            self.user.commit_tree_keyload();
+            // Author holds the whole tree, so it's a recipient too.
+            let mut members = parts.concat();
+            members.push(self.user.sig_kp.public);
+            let everyone = self.user.keyload_recipients_for_everyone();
+            self.user.rekey.keyload_sent(links.0.msgid.clone(), (Vec::new(), members), &everyone, timestamp_now());
+            Ok(links)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send role assignment with all the assigned roles [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the role assignment will be attached to
+    ///
+    pub fn send_role_assignment(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.share_role_assignment(&link_to.msgid)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::RoleAssignment)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send the set of channel administrators [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the admin set will be attached to, the announcement
+    ///    in order to be readable by every subscriber
+    ///
+    pub fn send_admin_set(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.share_admin_set(&link_to.msgid)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::AdminSet)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send a certificate allowing a publisher to send signed packets, it replaces
+    /// any certificate previously issued for the publisher [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the certificate will be attached to
+    ///  * `subject` - ed25519 Public Key of the certified publisher
+    ///  * `branch` - Address of the message certified packets must be linked to, `None` for any message
+    ///  * `not_before` - Start of the validity window, milliseconds since Unix epoch
+    ///  * `not_after` - End of the validity window, milliseconds since Unix epoch, `0` for no expiry
+    ///
+    pub fn send_certificate(
+        &mut self,
+        link_to: &Address,
+        subject: &PublicKey,
+        branch: Option<&Address>,
+        not_before: u64,
+        not_after: u64,
+    ) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let cert = Certificate {
+                subject: *subject,
+                branch: branch.map(|addr| addr.msgid.clone()),
+                not_before,
+                not_after,
+            };
+            let msg = self.user.share_certificate(&link_to.msgid, cert)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Certificate)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and send a certificate revoking a publisher, none of its signed packets are
+    /// accepted until it's certified again [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the revocation will be attached to
+    ///  * `subject` - ed25519 Public Key of the revoked publisher
+    ///
+    pub fn revoke_publisher(&mut self, link_to: &Address, subject: &PublicKey) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.share_certificate(&link_to.msgid, Certificate::revocation(*subject))?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Certificate)
+        })();
+        self.finish(saved, result)
     }
 
     /// Create and Send a Subscribe message to a Channel app instance [Subscriber].
@@ -236,37 +911,95 @@ impl<Trans: Transport> User<Trans>
     /// * `link_to` - Address of the Channel Announcement message
     ///
     pub fn send_subscribe(&mut self, link_to: &Address) -> Result<Address> {
-        let msg = self.user.subscribe(&link_to.msgid)?;
-        self.send_message(msg, MsgInfo::Subscribe)
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.subscribe(&link_to.msgid)?;
+            self.send_message(msg, MsgInfo::Subscribe)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Create and Send a Subscribe message readable by a channel administrator only [Subscriber].
+    ///
+    /// # Arguments
+    /// * `link_to` - Address of the Channel Announcement message
+    /// * `admin_pk` - ed25519 Public Key of the administrator handling the subscription
+    ///
+    pub fn send_subscribe_to(&mut self, link_to: &Address, admin_pk: &PublicKey) -> Result<Address> {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.user.subscribe_to(&link_to.msgid, admin_pk)?;
+            self.send_message(msg, MsgInfo::Subscribe)
+        })();
+        self.finish(saved, result)
     }
 
     // Receive
 
-    /// Receive and process a sequence message [Author, Subscriber].
+    /// Receive the message at `link` and handle it with `handle`, unless it has been processed
+    /// before, then its result is returned again without changing the user state, see
+    /// `PROCESSED_CAPACITY`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///  * `handle` - Handler of the expected message type, returns the result and the processed message
+    ///  * `body` - Result of a processed message, `None` if it's of another type
+    ///
+    fn receive_processed<R, H, B>(&mut self, link: &Address, handle: H, body: B) -> Result<R>
+    where
+        H: FnOnce(&mut UserImp<LS>, Message) -> Result<(R, Processed)>,
+        B: FnOnce(Processed) -> Option<R>,
+    {
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.transport.recv_message(link)?;
+            let key = ProcessedKey::of(&msg);
+            if let Some(r) = self.processed.get(&key).and_then(body) {
+                return Ok(r);
+            }
+            let (r, processed) = handle(&mut self.user, msg)?;
+            self.processed.insert(key, processed);
+            Ok(r)
+        })();
+        self.finish(saved, result)
+    }
+
+    /// Receive and process a sequence message [Author, Subscriber]. Sequencing state is left as it
+    /// is if the sequence message has been processed before.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     ///
     pub fn receive_sequence(&mut self, link: &Address) -> Result<Address> {
-        let msg = self.transport.recv_message(link)?;
-        if let Some(_addr) = &self.user.appinst {
-            let seq_link = msg.binary.link.clone();
-            let seq_msg = self.user.handle_sequence(msg.binary, MsgInfo::Sequence)?.body;
-            let msg_id = self.user.link_gen.link_from(
-                &seq_msg.pk,
-                Cursor::new_at(&seq_msg.ref_link, 0, seq_msg.seq_num.0 as u32),
-            );
-
-            if self.is_multi_branching() {
-                self.store_state(seq_msg.pk, &seq_link)?
-            } else {
-                self.store_state_for_all(&seq_link, seq_msg.seq_num.0 as u32)?
+        let saved = self.begin();
+        let result = (|| {
+            let msg = self.transport.recv_message(link)?;
+            // Sequence handled with the message it refers to returns the link of that message
+            if let Some(processed) = self.processed.get(&ProcessedKey::of(&msg)) {
+                if !matches!(processed.message.body, MessageContent::Sequence) {
+                    return Ok(processed.message.link);
+                }
             }
+            if let Some(_addr) = &self.user.appinst {
+                let seq_link = msg.binary.link.clone();
+                let seq_msg = self.user.handle_sequence(msg.binary, MsgInfo::Sequence)?.body;
+                let msg_id = self.user.link_gen.link_from(
+                    &seq_msg.pk,
+                    Cursor::new_at(&seq_msg.ref_link, 0, seq_msg.seq_num.0 as u32),
+                );
+
+                if self.is_multi_branching() {
+                    self.user.store_sequence_state(seq_msg.pk, seq_link.msgid.clone(), seq_msg.seq_num.0)?
+                } else if !self.user.is_sequence_processed(&seq_msg.pk, seq_msg.seq_num.0) {
+                    self.store_state_for_all(&seq_link, seq_msg.seq_num.0 as u32)?
+                }
 
-            Ok(msg_id)
-        } else {
-            err!(UserNotRegistered)
-        }
+                Ok(msg_id)
+            } else {
+                err!(UserNotRegistered)
+            }
+        })();
+        self.finish(saved, result)
     }
 
     /// Receive and process a signed packet message [Author, Subscriber].
@@ -275,10 +1008,26 @@ impl<Trans: Transport> User<Trans>
     ///  * `link` - Address of the message to be processed
     ///
     pub fn receive_signed_packet(&mut self, link: &Address) -> Result<(PublicKey, Bytes, Bytes)> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_signed_packet(msg.binary, MsgInfo::SignedPacket, timestamp_now())?;
+                let body = m.body.clone();
+                let u = m.map(|(pk, public, masked)| MessageContent::new_signed_packet(pk, public, masked));
+                Ok((body, u.into()))
+            },
+            Processed::signed_packet,
+        )
+    }
+
+    /// Export a proof disclosing a received signed packet to an outside party [Author, Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the signed packet
+    ///
+    pub fn export_signed_packet_proof(&mut self, link: &Address) -> Result<Vec<u8>> {
         let msg = self.transport.recv_message(link)?;
-        // TODO: msg.timestamp is lost
-        let m = self.user.handle_signed_packet(msg.binary, MsgInfo::SignedPacket)?;
-        Ok(m.body)
+        self.user.export_signed_packet_proof(msg.binary)
     }
 
     /// Receive and process a tagged packet message [Author, Subscriber].
@@ -287,9 +1036,16 @@ impl<Trans: Transport> User<Trans>
     ///  * `link` - Address of the message to be processed
     ///
     pub fn receive_tagged_packet(&mut self, link: &Address) -> Result<(Bytes, Bytes)> {
-        let msg = self.transport.recv_message(link)?;
-        let m = self.user.handle_tagged_packet(msg.binary, MsgInfo::TaggedPacket)?;
-        Ok(m.body)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_tagged_packet(msg.binary, MsgInfo::TaggedPacket)?;
+                let body = m.body.clone();
+                let u = m.map(|(public, masked)| MessageContent::new_tagged_packet(public, masked));
+                Ok((body, u.into()))
+            },
+            Processed::tagged_packet,
+        )
     }
 
     /// Receive and process a subscribe message [Author].
@@ -298,9 +1054,17 @@ impl<Trans: Transport> User<Trans>
     ///  * `link` - Address of the message to be processed
     ///
     pub fn receive_subscribe(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link)?;
-        // TODO: Timestamp is lost.
-        self.user.handle_subscribe(msg.binary, MsgInfo::Subscribe)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                // TODO: Timestamp is lost.
+                let link = msg.binary.link.clone();
+                user.handle_subscribe(msg.binary, MsgInfo::Subscribe)?;
+                Ok(((), UnwrappedMessage::new(link, MessageContent::Subscribe).into()))
+            },
+            Processed::subscribe,
+        )?;
+        self.user.check_subscription_policy()
     }
 
     /// Receive and Process an announcement message [Subscriber].
@@ -309,19 +1073,143 @@ impl<Trans: Transport> User<Trans>
     /// * `link_to` - Address of the Channel Announcement message
     ///
     pub fn receive_announcement(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link)?;
-        self.user.handle_announcement(msg.binary, MsgInfo::Announce)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let link = msg.binary.link.clone();
+                user.handle_announcement(msg.binary, MsgInfo::Announce)?;
+                Ok(((), UnwrappedMessage::new(link, MessageContent::new_announce()).into()))
+            },
+            Processed::announcement,
+        )
     }
 
-    /// Receive and process a keyload message [Subscriber].
+    /// Receive and process a keyload message, a keyload part or a private keyload [Author, Subscriber].
+    /// Keyload parts must be processed before the final keyload.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     ///
     pub fn receive_keyload(&mut self, link: &Address) -> Result<bool> {
-        let msg = self.transport.recv_message(link)?;
-        let m = self.user.handle_keyload(msg.binary, MsgInfo::Keyload)?;
-        Ok(m.body)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let preparsed = msg.binary.parse_header()?;
+                let m = match preparsed.header.content_type {
+                    message::KEYLOAD_PART => user.handle_keyload_part(msg.binary, MsgInfo::KeyloadPart)?,
+                    message::PRIVATE_KEYLOAD => user.handle_private_keyload(msg.binary, MsgInfo::PrivateKeyload)?,
+                    _ => user.handle_keyload(msg.binary, MsgInfo::Keyload)?,
+                };
+                let allowed = m.body;
+                let u = m.map(|_| MessageContent::new_keyload());
+                Ok((allowed, Processed::with_detail(u, Detail::Keyload(allowed))))
+            },
+            Processed::keyload,
+        )
+    }
+
+    /// Receive and process a tree keyload message [Subscriber]. A member recovers the group key
+    /// from the tree keyloads split by `send_tree_keyload` once it has processed the one carrying its path.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_tree_keyload(&mut self, link: &Address) -> Result<bool> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_tree_keyload(msg.binary, MsgInfo::TreeKeyload)?;
+                let allowed = m.body;
+                let u = m.map(|_| MessageContent::new_keyload());
+                Ok((allowed, Processed::with_detail(u, Detail::Keyload(allowed))))
+            },
+            Processed::keyload,
+        )
+    }
+
+    /// Receive and process a role assignment message [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_role_assignment(&mut self, link: &Address) -> Result<()> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_role_assignment(msg.binary, MsgInfo::RoleAssignment)?;
+                Ok(((), m.map(|_| MessageContent::new_role_assignment()).into()))
+            },
+            Processed::role_assignment,
+        )
+    }
+
+    /// Receive and process a certificate message [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_certificate(&mut self, link: &Address) -> Result<Certificate> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_certificate(msg.binary, MsgInfo::Certificate)?;
+                let certificate = m.body.clone();
+                let u = m.map(|_| MessageContent::new_certificate());
+                Ok((certificate.clone(), Processed::with_detail(u, Detail::Certificate(Box::new(certificate)))))
+            },
+            Processed::certificate,
+        )
+    }
+
+    /// Receive and process an admin set message [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_admin_set(&mut self, link: &Address) -> Result<()> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_admin_set(msg.binary, MsgInfo::AdminSet)?;
+                Ok(((), m.map(|_| MessageContent::new_admin_set()).into()))
+            },
+            Processed::admin_set,
+        )
+    }
+
+    /// Receive and process a direct message, returns sender's public key and decrypted payload,
+    /// or `None` if the message is not addressed to this user [Author].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_direct_message(&mut self, link: &Address) -> Result<Option<(PublicKey, Bytes)>> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_direct_message(msg.binary, MsgInfo::DirectMessage)?;
+                let body = m.body.clone();
+                Ok((body, m.map(MessageContent::new_direct_message).into()))
+            },
+            Processed::direct_message,
+        )
+    }
+
+    /// Receive and process a topic declaration, returns the topic name [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub fn receive_topic(&mut self, link: &Address) -> Result<Bytes> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_topic(msg.binary, MsgInfo::Topic)?;
+                let name = m.body.clone();
+                Ok((name, m.map(MessageContent::new_topic).into()))
+            },
+            Processed::topic,
+        )
     }
 
     /// Receive and process a message of unknown type. Message will be handled appropriately and
@@ -338,57 +1226,110 @@ impl<Trans: Transport> User<Trans>
 
     /// Retrieves the next message for each user (if present in transport layer) and returns them [Author, Subscriber]
     pub fn fetch_next_msgs(&mut self) -> Vec<UnwrappedMessage> {
-        let ids = self.user.gen_next_msg_ids(self.user.is_multi_branching());
         let mut msgs = Vec::new();
+        // Publishers of skipped messages may have more messages to fetch
+        let mut skipped = true;
+
+        while skipped {
+            skipped = false;
+            let ids = self.user.gen_next_msg_ids(self.user.is_multi_branching());
+            for (
+                _pk,
+                Cursor {
+                    link,
+                    branch_no: _,
+                    seq_no,
+                },
+            ) in ids
+            {
+                let msg = self.transport.recv_message(&link);
 
-        for (
-            _pk,
-            Cursor {
-                link,
-                branch_no: _,
-                seq_no,
-            },
-        ) in ids
-        {
-            let msg = self.transport.recv_message(&link);
-
-            if msg.is_ok() {
-                let msg = self.handle_message(msg.unwrap());
                 if let Ok(msg) = msg {
-                    if !self.user.is_multi_branching() {
-                        let stored = self.user.store_state_for_all(link.msgid, seq_no);
-                        panic_if_not!(stored.is_ok())
+                    let saved = self.begin();
+                    let msg = self.process_message(msg);
+                    if let Ok(msg) = self.end(saved, msg) {
+                        if !self.user.is_multi_branching() {
+                            let stored = self.user.store_state_for_all(link.msgid, seq_no);
+                            panic_if_not!(stored.is_ok())
+                        }
+
+                        match msg.body {
+                            MessageContent::Sequence => skipped = true,
+                            _ => msgs.push(msg),
+                        }
                     }
-
-                    msgs.push(msg);
                 }
             }
         }
+        // Changes are kept until they are written, so a failed checkpoint is retried with the next one.
+        let _ = self.checkpoint();
         msgs
     }
 
     /// Handle message of unknown type. Ingests a message and unwraps it according to its determined
-    /// content type [Author, Subscriber].
+    /// content type [Author, Subscriber]. A message handled before, directly or as the message
+    /// referred to by a sequence, is recognized by its link and content and its result is returned
+    /// again without changing the user state, see `PROCESSED_CAPACITY`.
     ///
     /// # Arguments
     /// * `msg` - Binary message of unknown type
     /// * `pk` - Optional ed25519 Public Key of the sending participant. None if unknown
     ///
-    pub fn handle_message(&mut self, mut msg0: Message) -> Result<UnwrappedMessage> {
-        loop {
+    pub fn handle_message(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        let saved = self.begin();
+        let result = self.process_message(msg);
+        self.finish(saved, result)
+    }
+
+    /// Receive a message of unknown type and unwrap it like `receive_message`, but leave the
+    /// link store, sequencing states and link generator untouched [Author, Subscriber].
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be previewed
+    ///
+    pub fn peek_message(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        let msg = self.transport.recv_message(link)?;
+        self.preview_message(msg)
+    }
+
+    /// Unwrap a message of unknown type like `handle_message` without changing the user state,
+    /// eg. to decide whether to process it [Author, Subscriber].
+    ///
+    /// # Arguments
+    /// * `msg` - Binary message of unknown type
+    ///
+    pub fn preview_message(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        if let Some(u) = self.processed.get(&ProcessedKey::of(&msg)) {
+            return Ok(u.message);
+        }
+        let saved = self.begin_preview();
+        let result = self.process_message(msg);
+        self.end_preview(saved);
+        result
+    }
+
+    fn process_message(&mut self, mut msg0: Message) -> Result<UnwrappedMessage> {
+        // Keys of the message and of the messages it refers to, results are kept for all of them
+        let mut keys = Vec::new();
+        let u = loop {
+            let key = ProcessedKey::of(&msg0);
+            if let Some(u) = self.processed.get(&key) {
+                break u;
+            }
+            keys.push(key);
             // Forget TangleMessage and timestamp
             let msg = msg0.binary;
             let preparsed = msg.parse_header()?;
             match preparsed.header.content_type {
                 message::SIGNED_PACKET => {
-                    let m = self.user.handle_signed_packet(msg, MsgInfo::SignedPacket)?;
+                    let m = self.user.handle_signed_packet(msg, MsgInfo::SignedPacket, timestamp_now())?;
                     let u = m.map(|(pk, public, masked)| MessageContent::new_signed_packet(pk, public, masked));
-                    return Ok(u)
+                    break u.into()
                 },
                 message::TAGGED_PACKET => {
                     let m = self.user.handle_tagged_packet(msg, MsgInfo::TaggedPacket)?;
                     let u = m.map(|(public, masked)| MessageContent::new_tagged_packet(public, masked));
-                    return Ok(u)
+                    break u.into()
                 },
                 message::KEYLOAD => {
                     // So long as the unwrap has not failed, we will return a blank object to
@@ -396,8 +1337,53 @@ impl<Trans: Transport> User<Trans>
                     // the keyload itself. This is to prevent sequencing failures
                     let m = self.user.handle_keyload(msg, MsgInfo::Keyload)?;
                     // TODO: Verify content, whether user is allowed or not!
-                    let u = m.map(|_allowed| MessageContent::new_keyload());
-                    return Ok(u)
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::KEYLOAD_PART => {
+                    let m = self.user.handle_keyload_part(msg, MsgInfo::KeyloadPart)?;
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::PRIVATE_KEYLOAD => {
+                    let m = self.user.handle_private_keyload(msg, MsgInfo::PrivateKeyload)?;
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::TREE_KEYLOAD => {
+                    let m = self.user.handle_tree_keyload(msg, MsgInfo::TreeKeyload)?;
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::ROLE_ASSIGNMENT => {
+                    let m = self.user.handle_role_assignment(msg, MsgInfo::RoleAssignment)?;
+                    let u = m.map(|_| MessageContent::new_role_assignment());
+                    break u.into()
+                },
+                message::CERTIFICATE => {
+                    let m = self.user.handle_certificate(msg, MsgInfo::Certificate)?;
+                    let detail = Detail::Certificate(Box::new(m.body.clone()));
+                    let u = m.map(|_| MessageContent::new_certificate());
+                    break Processed::with_detail(u, detail)
+                },
+                message::ADMIN_SET => {
+                    let m = self.user.handle_admin_set(msg, MsgInfo::AdminSet)?;
+                    let u = m.map(|_| MessageContent::new_admin_set());
+                    break u.into()
+                },
+                message::DIRECT_MESSAGE => {
+                    let m = self.user.handle_direct_message(msg, MsgInfo::DirectMessage)?;
+                    let u = m.map(MessageContent::new_direct_message);
+                    break u.into()
+                },
+                message::TOPIC => {
+                    let m = self.user.handle_topic(msg, MsgInfo::Topic)?;
+                    let u = m.map(MessageContent::new_topic);
+                    break u.into()
                 },
                 message::SEQUENCE => {
                     let store_link = msg.link.rel().clone();
@@ -406,19 +1392,29 @@ impl<Trans: Transport> User<Trans>
                         &unwrapped.body.pk,
                         Cursor::new_at(&unwrapped.body.ref_link, 0, unwrapped.body.seq_num.0 as u32),
                     );
+                    self.user.track_topic(msg_link.msgid.clone(), &unwrapped.body.ref_link);
+                    if !self.user.is_topic_followed(&unwrapped.body.ref_link) {
+                        // Message of an unfollowed topic is not fetched, only the sequence is consumed
+                        self.user.store_sequence_state(unwrapped.body.pk, store_link, unwrapped.body.seq_num.0)?;
+                        break unwrapped.map(|_| MessageContent::Sequence).into();
+                    }
                     let msg = self.transport.recv_message(&msg_link)?;
-                    self.user.store_state(unwrapped.body.pk.clone(), store_link)?;
+                    self.user.store_sequence_state(unwrapped.body.pk, store_link, unwrapped.body.seq_num.0)?;
                     msg0 = msg;
                 },
                 unknown_content => return err!(UnknownMsgType(unknown_content)),
             }
+        };
+        for key in keys {
+            self.processed.insert(key, u.clone());
         }
+        Ok(u.message)
     }
 
 }
 
 #[cfg(feature = "async")]
-impl<Trans: Transport> User<Trans>
+impl<Trans: Transport, LS: TangleLinkStore> User<Trans, LS>
 {
     // Send
 
@@ -429,21 +1425,33 @@ impl<Trans: Transport> User<Trans>
     /// * `wrapped` - A wrapped sequence object containing the sequence message and state
     ///
     async fn send_sequence(&mut self, wrapped: WrappedSequence) -> Result<Option<Address>> {
-        if let Some(seq_msg) = wrapped.0 {
-            self.transport.send_message(&Message::new(seq_msg)).await?;
-        }
+        let link = match wrapped.1 {
+            Some(wrap_state) => self.user.commit_sequence(wrap_state, MsgInfo::Sequence)?,
+            None => None,
+        };
+        // The sequenced message is sent already, so its sequence message is sent again before
+        // the next message if it fails now.
+        self.unsent_sequence = wrapped.0.map(Message::new);
+        self.sent();
+        self.send_unsent_sequence().await?;
+        Ok(link)
+    }
 
-        if let Some(wrap_state) = wrapped.1 {
-            self.user.commit_sequence(wrap_state, MsgInfo::Sequence)
-        } else {
-            Ok(None)
+    /// Send the sequence message which failed to be sent after its message, if any.
+    async fn send_unsent_sequence(&mut self) -> Result<()> {
+        if let Some(ref seq_msg) = self.unsent_sequence {
+            self.transport.send_message(seq_msg).await?;
         }
+        self.unsent_sequence = None;
+        Ok(())
     }
 
     /// Send a message without using sequencing logic. Reserved for Announce and Subscribe messages
     async fn send_message(&mut self, msg: WrappedMessage, info: MsgInfo) -> Result<Address> {
         self.transport.send_message(&Message::new(msg.message)).await?;
-        self.user.commit_wrapped(msg.wrapped, info)
+        let link = self.user.commit_wrapped(msg.wrapped, info)?;
+        self.sent();
+        Ok(link)
     }
 
     /// Send a message using sequencing logic.
@@ -459,17 +1467,56 @@ impl<Trans: Transport> User<Trans>
         ref_link: &MsgId,
         info: MsgInfo,
     ) -> Result<(Address, Option<Address>)> {
+        self.send_unsent_sequence().await?;
         let seq = self.user.wrap_sequence(ref_link)?;
         self.transport.send_message(&Message::new(msg.message)).await?;
+        let msg_link = match info {
+            MsgInfo::SignedPacket | MsgInfo::TaggedPacket => self.user.commit_packet(msg.wrapped, ref_link, info)?,
+            _ => self.user.commit_wrapped(msg.wrapped, info)?,
+        };
+        self.user.track_topic(msg_link.msgid.clone(), ref_link);
         let seq_link = self.send_sequence(seq).await?;
-        let msg_link = self.user.commit_wrapped(msg.wrapped, info)?;
         Ok((msg_link, seq_link))
     }
 
     /// Send an announcement message, generating a channel [Author].
     pub async fn send_announce(&mut self) -> Result<Address> {
-        let msg = self.user.announce()?;
-        self.send_message(msg, MsgInfo::Announce).await
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.announce()?;
+            self.send_message(msg, MsgInfo::Announce).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Apply Author's rekey policy before a packet linked to `link_to` is sent, see `RekeyPolicy`.
+    /// Returns the address the packet should be linked to. A keyload for everyone is attached to
+    /// the announcement so that new subscribers can read it, and sequenced after `link_to`.
+    /// Packets of branches for some of the recipients are not rekeyed, `RekeyBranchNotEveryone`
+    /// is returned when rekey is due.
+    async fn rekey_if_due(&mut self, link_to: &Address) -> Result<Address> {
+        let everyone = self.user.keyload_recipients_for_everyone();
+        match self.user.rekey.check(&link_to.msgid, &everyone, timestamp_now()) {
+            Rekey::Keep => Ok(link_to.clone()),
+            Rekey::Follow(keyload) => Ok(Address {
+                appinst: link_to.appinst.clone(),
+                msgid: keyload,
+            }),
+            Rekey::After(prev) => {
+                let announcement = match &self.user.appinst {
+                    Some(appinst) => appinst.clone(),
+                    None => return err!(UserNotRegistered),
+                };
+                let prev = Address {
+                    appinst: link_to.appinst.clone(),
+                    msgid: prev,
+                };
+                let (keyload_link, _) = self.send_keyload_after(&announcement, &prev, &everyone.0, &everyone.1).await?;
+                Ok(keyload_link)
+            }
+            Rekey::Restricted => err!(RekeyBranchNotEveryone(link_to.msgid.to_string())),
+        }
     }
 
     /// Create and send a signed packet [Author, Subscriber].
@@ -485,8 +1532,16 @@ impl<Trans: Transport> User<Trans>
         public_payload: &Bytes,
         masked_payload: &Bytes,
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.sign_packet(&link_to.msgid, public_payload, masked_payload)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::SignedPacket).await
+        let saved = self.begin();
+        let result = async {
+            let link_to = &self.rekey_if_due(link_to).await?;
+            let msg = self.user.sign_packet(&link_to.msgid, public_payload, masked_payload, timestamp_now())?;
+            let links = self.send_message_sequenced(msg, link_to.rel(), MsgInfo::SignedPacket).await?;
+            self.user.rekey.packet_sent(&links.0.msgid);
+            Ok(links)
+        }
+        .await;
+        self.finish(saved, result)
     }
 
     /// Create and send a tagged packet [Author, Subscriber].
@@ -502,8 +1557,16 @@ impl<Trans: Transport> User<Trans>
         public_payload: &Bytes,
         masked_payload: &Bytes,
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.tag_packet(&link_to.msgid, public_payload, masked_payload)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::TaggedPacket).await
+        let saved = self.begin();
+        let result = async {
+            let link_to = &self.rekey_if_due(link_to).await?;
+            let msg = self.user.tag_packet(&link_to.msgid, public_payload, masked_payload)?;
+            let links = self.send_message_sequenced(msg, link_to.rel(), MsgInfo::TaggedPacket).await?;
+            self.user.rekey.packet_sent(&links.0.msgid);
+            Ok(links)
+        }
+        .await;
+        self.finish(saved, result)
     }
 
     /// Create and send a new keyload for a list of subscribers [Author].
@@ -517,10 +1580,43 @@ impl<Trans: Transport> User<Trans>
         &mut self,
         link_to: &Address,
         psk_ids: &PskIds,
-        ke_pks: &Vec<PublicKey>,
+        ke_pks: &[PublicKey],
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.share_keyload(&link_to.msgid, psk_ids, ke_pks)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload).await
+        self.send_keyload_after(link_to, link_to, psk_ids, ke_pks).await
+    }
+
+    /// Create and send a new keyload attached to `link_to`, but sequenced after `prev` [Author].
+    pub(crate) async fn send_keyload_after(
+        &mut self,
+        link_to: &Address,
+        prev: &Address,
+        psk_ids: &PskIds,
+        ke_pks: &[PublicKey],
+    ) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let parts = self.user.split_keyload_recipients(&link_to.msgid, psk_ids, ke_pks)?;
+            let key = NBytes::from(prng::random_key());
+            let last = parts.len() - 1;
+            // Parts follow each other in the sequence, but are all joined to `link_to`.
+            let mut prev = prev.msgid.clone();
+            let mut links = None;
+            for (i, (psk_ids, ke_pks)) in parts.iter().enumerate() {
+                let msg = self.user.share_keyload_part(&link_to.msgid, &prev, psk_ids, ke_pks, key, i == last)?;
+                let info = if i == last { MsgInfo::Keyload } else { MsgInfo::KeyloadPart };
+                let (msg_link, seq_link) = self.send_message_sequenced(msg, &prev, info).await?;
+                prev = msg_link.msgid.clone();
+                links = Some((msg_link, seq_link));
+            }
+            // At least one part is always produced.
+            let links = links.unwrap();
+            self.user.track_keyload(links.0.msgid.clone(), &link_to.msgid);
+            let everyone = self.user.keyload_recipients_for_everyone();
+            self.user.rekey.keyload_sent(links.0.msgid.clone(), (psk_ids.clone(), ke_pks.to_vec()), &everyone, timestamp_now());
+            Ok(links)
+        }
+        .await;
+        self.finish(saved, result)
     }
 
     /// Create and send keyload for all subscribed subscribers [Author].
@@ -529,8 +1625,174 @@ impl<Trans: Transport> User<Trans>
     ///  * `link_to` - Address of the message the keyload will be attached to
     ///
     pub async fn send_keyload_for_everyone(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.share_keyload_for_everyone(&link_to.msgid)?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload).await
+        let (psk_ids, ke_pks) = self.user.keyload_recipients_for_everyone();
+        self.send_keyload(link_to, &psk_ids, &ke_pks).await
+    }
+
+    /// Create and send a private keyload starting a branch readable by the given participants
+    /// only [Author, Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `ke_pks`  - Vector of Public Keys of known participants to be included in message
+    ///
+    pub async fn send_private_keyload(&mut self, link_to: &Address, ke_pks: &[PublicKey]) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.share_private_keyload(&link_to.msgid, ke_pks)?;
+            let links = self.send_message_sequenced(msg, link_to.rel(), MsgInfo::PrivateKeyload).await?;
+            self.user.track_keyload(links.0.msgid.clone(), &link_to.msgid);
+            Ok(links)
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send a direct message readable by Author only [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the direct message will be attached to
+    ///  * `masked_payload` - Payload encrypted for Author
+    ///
+    pub async fn send_direct_message(&mut self, link_to: &Address, masked_payload: &Bytes) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.send_direct_message(&link_to.msgid, masked_payload)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::DirectMessage).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send a topic declaration, the message is the root of the topic branch [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the topic will be attached to
+    ///  * `name` - Topic name, unique within the channel
+    ///
+    pub async fn send_topic(&mut self, link_to: &Address, name: &Bytes) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.share_topic(&link_to.msgid, name)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Topic).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send tree keyload with pending key tree updates [Author]. Updates exceeding
+    /// `uniform_payload_length` are split into several tree keyloads, links of the last one are returned.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the tree keyload will be attached to
+    ///
+    pub async fn send_tree_keyload(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let parts = self.user.split_tree_keyload_members(&link_to.msgid)?;
+            // Parts follow each other in the sequence, but are all joined to `link_to`.
+            let mut prev = link_to.msgid.clone();
+            let mut links = None;
+            for members in parts.iter() {
+                let msg = self.user.share_tree_keyload(&link_to.msgid, &prev, members)?;
+                let (msg_link, seq_link) = self.send_message_sequenced(msg, &prev, MsgInfo::TreeKeyload).await?;
+                prev = msg_link.msgid.clone();
+                links = Some((msg_link, seq_link));
+            }
+            // At least one part is always produced.
+            let links = links.unwrap();
+            self.user.track_keyload(links.0.msgid.clone(), &link_to.msgid);
+            self.user.commit_tree_keyload();
+            // Author holds the whole tree, so it's a recipient too.
+            let mut members = parts.concat();
+            members.push(self.user.sig_kp.public);
+            let everyone = self.user.keyload_recipients_for_everyone();
+            self.user.rekey.keyload_sent(links.0.msgid.clone(), (Vec::new(), members), &everyone, timestamp_now());
+            Ok(links)
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send role assignment with all the assigned roles [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the role assignment will be attached to
+    ///
+    pub async fn send_role_assignment(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.share_role_assignment(&link_to.msgid)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::RoleAssignment).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send the set of channel administrators [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the admin set will be attached to, the announcement
+    ///    in order to be readable by every subscriber
+    ///
+    pub async fn send_admin_set(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.share_admin_set(&link_to.msgid)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::AdminSet).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send a certificate allowing a publisher to send signed packets, it replaces
+    /// any certificate previously issued for the publisher [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the certificate will be attached to
+    ///  * `subject` - ed25519 Public Key of the certified publisher
+    ///  * `branch` - Address of the message certified packets must be linked to, `None` for any message
+    ///  * `not_before` - Start of the validity window, milliseconds since Unix epoch
+    ///  * `not_after` - End of the validity window, milliseconds since Unix epoch, `0` for no expiry
+    ///
+    pub async fn send_certificate(
+        &mut self,
+        link_to: &Address,
+        subject: &PublicKey,
+        branch: Option<&Address>,
+        not_before: u64,
+        not_after: u64,
+    ) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let cert = Certificate {
+                subject: *subject,
+                branch: branch.map(|addr| addr.msgid.clone()),
+                not_before,
+                not_after,
+            };
+            let msg = self.user.share_certificate(&link_to.msgid, cert)?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Certificate).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and send a certificate revoking a publisher, none of its signed packets are
+    /// accepted until it's certified again [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the revocation will be attached to
+    ///  * `subject` - ed25519 Public Key of the revoked publisher
+    ///
+    pub async fn revoke_publisher(&mut self, link_to: &Address, subject: &PublicKey) -> Result<(Address, Option<Address>)> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.share_certificate(&link_to.msgid, Certificate::revocation(*subject))?;
+            self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Certificate).await
+        }
+        .await;
+        self.finish(saved, result)
     }
 
     /// Create and Send a Subscribe message to a Channel app instance [Subscriber].
@@ -539,37 +1801,99 @@ impl<Trans: Transport> User<Trans>
     /// * `link_to` - Address of the Channel Announcement message
     ///
     pub async fn send_subscribe(&mut self, link_to: &Address) -> Result<Address> {
-        let msg = self.user.subscribe(&link_to.msgid)?;
-        self.send_message(msg, MsgInfo::Subscribe).await
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.subscribe(&link_to.msgid)?;
+            self.send_message(msg, MsgInfo::Subscribe).await
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Create and Send a Subscribe message readable by a channel administrator only [Subscriber].
+    ///
+    /// # Arguments
+    /// * `link_to` - Address of the Channel Announcement message
+    /// * `admin_pk` - ed25519 Public Key of the administrator handling the subscription
+    ///
+    pub async fn send_subscribe_to(&mut self, link_to: &Address, admin_pk: &PublicKey) -> Result<Address> {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.user.subscribe_to(&link_to.msgid, admin_pk)?;
+            self.send_message(msg, MsgInfo::Subscribe).await
+        }
+        .await;
+        self.finish(saved, result)
     }
 
     // Receive
 
-    /// Receive and process a sequence message [Author, Subscriber].
+    /// Receive the message at `link` and handle it with `handle`, unless it has been processed
+    /// before, then its result is returned again without changing the user state, see
+    /// `PROCESSED_CAPACITY`.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///  * `handle` - Handler of the expected message type, returns the result and the processed message
+    ///  * `body` - Result of a processed message, `None` if it's of another type
+    ///
+    async fn receive_processed<R, H, B>(&mut self, link: &Address, handle: H, body: B) -> Result<R>
+    where
+        H: FnOnce(&mut UserImp<LS>, Message) -> Result<(R, Processed)>,
+        B: FnOnce(Processed) -> Option<R>,
+    {
+        let saved = self.begin();
+        let result = async {
+            let msg = self.transport.recv_message(link).await?;
+            let key = ProcessedKey::of(&msg);
+            if let Some(r) = self.processed.get(&key).and_then(body) {
+                return Ok(r);
+            }
+            let (r, processed) = handle(&mut self.user, msg)?;
+            self.processed.insert(key, processed);
+            Ok(r)
+        }
+        .await;
+        self.finish(saved, result)
+    }
+
+    /// Receive and process a sequence message [Author, Subscriber]. Sequencing state is left as it
+    /// is if the sequence message has been processed before.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     ///
     pub async fn receive_sequence(&mut self, link: &Address) -> Result<Address> {
-        let msg = self.transport.recv_message(link).await?;
-        if let Some(_addr) = &self.user.appinst {
-            let seq_link = msg.binary.link.clone();
-            let seq_msg = self.user.handle_sequence(msg.binary, MsgInfo::Sequence)?.body;
-            let msg_id = self.user.link_gen.link_from(
-                &seq_msg.pk,
-                Cursor::new_at(&seq_msg.ref_link, 0, seq_msg.seq_num.0 as u32),
-            );
-
-            if self.is_multi_branching() {
-                self.store_state(seq_msg.pk, &seq_link)?
-            } else {
-                self.store_state_for_all(&seq_link, seq_msg.seq_num.0 as u32)?
+        let saved = self.begin();
+        let result = async {
+            let msg = self.transport.recv_message(link).await?;
+            // Sequence handled with the message it refers to returns the link of that message
+            if let Some(processed) = self.processed.get(&ProcessedKey::of(&msg)) {
+                if !matches!(processed.message.body, MessageContent::Sequence) {
+                    return Ok(processed.message.link);
+                }
             }
+            if let Some(_addr) = &self.user.appinst {
+                let seq_link = msg.binary.link.clone();
+                let seq_msg = self.user.handle_sequence(msg.binary, MsgInfo::Sequence)?.body;
+                let msg_id = self.user.link_gen.link_from(
+                    &seq_msg.pk,
+                    Cursor::new_at(&seq_msg.ref_link, 0, seq_msg.seq_num.0 as u32),
+                );
+
+                if self.is_multi_branching() {
+                    self.user.store_sequence_state(seq_msg.pk, seq_link.msgid.clone(), seq_msg.seq_num.0)?
+                } else if !self.user.is_sequence_processed(&seq_msg.pk, seq_msg.seq_num.0) {
+                    self.store_state_for_all(&seq_link, seq_msg.seq_num.0 as u32)?
+                }
 
-            Ok(msg_id)
-        } else {
-            err!(UserNotRegistered)
+                Ok(msg_id)
+            } else {
+                err!(UserNotRegistered)
+            }
         }
+        .await;
+        self.finish(saved, result)
     }
 
     /// Receive and process a signed packet message [Author, Subscriber].
@@ -578,10 +1902,27 @@ impl<Trans: Transport> User<Trans>
     ///  * `link` - Address of the message to be processed
     ///
     pub async fn receive_signed_packet(&mut self, link: &Address) -> Result<(PublicKey, Bytes, Bytes)> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_signed_packet(msg.binary, MsgInfo::SignedPacket, timestamp_now())?;
+                let body = m.body.clone();
+                let u = m.map(|(pk, public, masked)| MessageContent::new_signed_packet(pk, public, masked));
+                Ok((body, u.into()))
+            },
+            Processed::signed_packet,
+        )
+        .await
+    }
+
+    /// Export a proof disclosing a received signed packet to an outside party [Author, Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the signed packet
+    ///
+    pub async fn export_signed_packet_proof(&mut self, link: &Address) -> Result<Vec<u8>> {
         let msg = self.transport.recv_message(link).await?;
-        // TODO: msg.timestamp is lost
-        let m = self.user.handle_signed_packet(msg.binary, MsgInfo::SignedPacket)?;
-        Ok(m.body)
+        self.user.export_signed_packet_proof(msg.binary)
     }
 
     /// Receive and process a tagged packet message [Author, Subscriber].
@@ -590,9 +1931,17 @@ impl<Trans: Transport> User<Trans>
     ///  * `link` - Address of the message to be processed
     ///
     pub async fn receive_tagged_packet(&mut self, link: &Address) -> Result<(Bytes, Bytes)> {
-        let msg = self.transport.recv_message(link).await?;
-        let m = self.user.handle_tagged_packet(msg.binary, MsgInfo::TaggedPacket)?;
-        Ok(m.body)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_tagged_packet(msg.binary, MsgInfo::TaggedPacket)?;
+                let body = m.body.clone();
+                let u = m.map(|(public, masked)| MessageContent::new_tagged_packet(public, masked));
+                Ok((body, u.into()))
+            },
+            Processed::tagged_packet,
+        )
+        .await
     }
 
 
@@ -602,9 +1951,18 @@ impl<Trans: Transport> User<Trans>
     ///  * `link` - Address of the message to be processed
     ///
     pub async fn receive_subscribe(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link).await?;
-        // TODO: Timestamp is lost.
-        self.user.handle_subscribe(msg.binary, MsgInfo::Subscribe)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                // TODO: Timestamp is lost.
+                let link = msg.binary.link.clone();
+                user.handle_subscribe(msg.binary, MsgInfo::Subscribe)?;
+                Ok(((), UnwrappedMessage::new(link, MessageContent::Subscribe).into()))
+            },
+            Processed::subscribe,
+        )
+        .await?;
+        self.user.check_subscription_policy()
     }
 
     /// Receive and Process an announcement message [Subscriber].
@@ -613,19 +1971,151 @@ impl<Trans: Transport> User<Trans>
     /// * `link_to` - Address of the Channel Announcement message
     ///
     pub async fn receive_announcement(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link).await?;
-        self.user.handle_announcement(msg.binary, MsgInfo::Announce)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let link = msg.binary.link.clone();
+                user.handle_announcement(msg.binary, MsgInfo::Announce)?;
+                Ok(((), UnwrappedMessage::new(link, MessageContent::new_announce()).into()))
+            },
+            Processed::announcement,
+        )
+        .await
     }
 
-    /// Receive and process a keyload message [Subscriber].
+    /// Receive and process a keyload message, a keyload part or a private keyload [Author, Subscriber].
+    /// Keyload parts must be processed before the final keyload.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     ///
     pub async fn receive_keyload(&mut self, link: &Address) -> Result<bool> {
-        let msg = self.transport.recv_message(link).await?;
-        let m = self.user.handle_keyload(msg.binary, MsgInfo::Keyload)?;
-        Ok(m.body)
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let preparsed = msg.binary.parse_header()?;
+                let m = match preparsed.header.content_type {
+                    message::KEYLOAD_PART => user.handle_keyload_part(msg.binary, MsgInfo::KeyloadPart)?,
+                    message::PRIVATE_KEYLOAD => user.handle_private_keyload(msg.binary, MsgInfo::PrivateKeyload)?,
+                    _ => user.handle_keyload(msg.binary, MsgInfo::Keyload)?,
+                };
+                let allowed = m.body;
+                let u = m.map(|_| MessageContent::new_keyload());
+                Ok((allowed, Processed::with_detail(u, Detail::Keyload(allowed))))
+            },
+            Processed::keyload,
+        )
+        .await
+    }
+
+    /// Receive and process a tree keyload message [Subscriber]. A member recovers the group key
+    /// from the tree keyloads split by `send_tree_keyload` once it has processed the one carrying its path.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_tree_keyload(&mut self, link: &Address) -> Result<bool> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_tree_keyload(msg.binary, MsgInfo::TreeKeyload)?;
+                let allowed = m.body;
+                let u = m.map(|_| MessageContent::new_keyload());
+                Ok((allowed, Processed::with_detail(u, Detail::Keyload(allowed))))
+            },
+            Processed::keyload,
+        )
+        .await
+    }
+
+    /// Receive and process a role assignment message [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_role_assignment(&mut self, link: &Address) -> Result<()> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_role_assignment(msg.binary, MsgInfo::RoleAssignment)?;
+                Ok(((), m.map(|_| MessageContent::new_role_assignment()).into()))
+            },
+            Processed::role_assignment,
+        )
+        .await
+    }
+
+    /// Receive and process a certificate message [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_certificate(&mut self, link: &Address) -> Result<Certificate> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_certificate(msg.binary, MsgInfo::Certificate)?;
+                let certificate = m.body.clone();
+                let u = m.map(|_| MessageContent::new_certificate());
+                Ok((certificate.clone(), Processed::with_detail(u, Detail::Certificate(Box::new(certificate)))))
+            },
+            Processed::certificate,
+        )
+        .await
+    }
+
+    /// Receive and process an admin set message [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_admin_set(&mut self, link: &Address) -> Result<()> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_admin_set(msg.binary, MsgInfo::AdminSet)?;
+                Ok(((), m.map(|_| MessageContent::new_admin_set()).into()))
+            },
+            Processed::admin_set,
+        )
+        .await
+    }
+
+    /// Receive and process a direct message, returns sender's public key and decrypted payload,
+    /// or `None` if the message is not addressed to this user [Author].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_direct_message(&mut self, link: &Address) -> Result<Option<(PublicKey, Bytes)>> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_direct_message(msg.binary, MsgInfo::DirectMessage)?;
+                let body = m.body.clone();
+                Ok((body, m.map(MessageContent::new_direct_message).into()))
+            },
+            Processed::direct_message,
+        )
+        .await
+    }
+
+    /// Receive and process a topic declaration, returns the topic name [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    ///
+    pub async fn receive_topic(&mut self, link: &Address) -> Result<Bytes> {
+        self.receive_processed(
+            link,
+            |user, msg| {
+                let m = user.handle_topic(msg.binary, MsgInfo::Topic)?;
+                let name = m.body.clone();
+                Ok((name, m.map(MessageContent::new_topic).into()))
+            },
+            Processed::topic,
+        )
+        .await
     }
 
     /// Receive and process a message of unknown type. Message will be handled appropriately and
@@ -643,55 +2133,109 @@ impl<Trans: Transport> User<Trans>
 
     /// Retrieves the next message for each user (if present in transport layer) and returns them [Author, Subscriber]
     pub async fn fetch_next_msgs(&mut self) -> Vec<UnwrappedMessage> {
-        let ids = self.user.gen_next_msg_ids(self.user.is_multi_branching());
         let mut msgs = Vec::new();
+        // Publishers of skipped messages may have more messages to fetch
+        let mut skipped = true;
+
+        while skipped {
+            skipped = false;
+            let ids = self.user.gen_next_msg_ids(self.user.is_multi_branching());
+            for (
+                _pk,
+                Cursor {
+                    link,
+                    branch_no: _,
+                    seq_no,
+                },
+            ) in ids
+            {
+                let msg = self.transport.recv_message(&link).await;
 
-        for (
-            _pk,
-            Cursor {
-                link,
-                branch_no: _,
-                seq_no,
-            },
-        ) in ids
-        {
-            let msg = self.transport.recv_message(&link).await;
-
-            if let Ok(msg) = msg {
-                if let Ok(msg) = self.handle_message(msg).await {
-                    if !self.user.is_multi_branching() {
-                        let stored = self.user.store_state_for_all(link.msgid, seq_no);
-                        panic_if_not!(stored.is_ok())
+                if let Ok(msg) = msg {
+                    let saved = self.begin();
+                    let msg = self.process_message(msg).await;
+                    if let Ok(msg) = self.end(saved, msg) {
+                        if !self.user.is_multi_branching() {
+                            let stored = self.user.store_state_for_all(link.msgid, seq_no);
+                            panic_if_not!(stored.is_ok())
+                        }
+
+                        match msg.body {
+                            MessageContent::Sequence => skipped = true,
+                            _ => msgs.push(msg),
+                        }
                     }
-
-                    msgs.push(msg);
                 }
             }
         }
+        // Changes are kept until they are written, so a failed checkpoint is retried with the next one.
+        let _ = self.checkpoint();
         msgs
     }
 
     /// Handle message of unknown type. Ingests a message and unwraps it according to its determined
-    /// content type [Author, Subscriber].
+    /// content type [Author, Subscriber]. A message handled before, directly or as the message
+    /// referred to by a sequence, is recognized by its link and content and its result is returned
+    /// again without changing the user state, see `PROCESSED_CAPACITY`.
     ///
     /// # Arguments
     /// * `msg` - Binary message of unknown type
     ///
-    pub async fn handle_message(&mut self, mut msg0: Message) -> Result<UnwrappedMessage> {
-        loop {
+    pub async fn handle_message(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        let saved = self.begin();
+        let result = self.process_message(msg).await;
+        self.finish(saved, result)
+    }
+
+    /// Receive a message of unknown type and unwrap it like `receive_message`, but leave the
+    /// link store, sequencing states and link generator untouched [Author, Subscriber].
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be previewed
+    ///
+    pub async fn peek_message(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        let msg = self.transport.recv_message(link).await?;
+        self.preview_message(msg).await
+    }
+
+    /// Unwrap a message of unknown type like `handle_message` without changing the user state,
+    /// eg. to decide whether to process it [Author, Subscriber].
+    ///
+    /// # Arguments
+    /// * `msg` - Binary message of unknown type
+    ///
+    pub async fn preview_message(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        if let Some(u) = self.processed.get(&ProcessedKey::of(&msg)) {
+            return Ok(u.message);
+        }
+        let saved = self.begin_preview();
+        let result = self.process_message(msg).await;
+        self.end_preview(saved);
+        result
+    }
+
+    async fn process_message(&mut self, mut msg0: Message) -> Result<UnwrappedMessage> {
+        // Keys of the message and of the messages it refers to, results are kept for all of them
+        let mut keys = Vec::new();
+        let u = loop {
+            let key = ProcessedKey::of(&msg0);
+            if let Some(u) = self.processed.get(&key) {
+                break u;
+            }
+            keys.push(key);
             // Forget TangleMessage and timestamp
             let msg = msg0.binary;
             let preparsed = msg.parse_header()?;
             match preparsed.header.content_type {
                 message::SIGNED_PACKET => {
-                    let m = self.user.handle_signed_packet(msg, MsgInfo::SignedPacket)?;
+                    let m = self.user.handle_signed_packet(msg, MsgInfo::SignedPacket, timestamp_now())?;
                     let u = m.map(|(pk, public, masked)| MessageContent::new_signed_packet(pk, public, masked));
-                    return Ok(u)
+                    break u.into()
                 },
                 message::TAGGED_PACKET => {
                     let m = self.user.handle_tagged_packet(msg, MsgInfo::TaggedPacket)?;
                     let u = m.map(|(public, masked)| MessageContent::new_tagged_packet(public, masked));
-                    return Ok(u)
+                    break u.into()
                 },
                 message::KEYLOAD => {
                     // So long as the unwrap has not failed, we will return a blank object to
@@ -699,8 +2243,53 @@ impl<Trans: Transport> User<Trans>
                     // the keyload itself. This is to prevent sequencing failures
                     let m = self.user.handle_keyload(msg, MsgInfo::Keyload)?;
                     // TODO: Verify content, whether user is allowed or not!
-                    let u = m.map(|_allowed| MessageContent::new_keyload());
-                    return Ok(u)
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::KEYLOAD_PART => {
+                    let m = self.user.handle_keyload_part(msg, MsgInfo::KeyloadPart)?;
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::PRIVATE_KEYLOAD => {
+                    let m = self.user.handle_private_keyload(msg, MsgInfo::PrivateKeyload)?;
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::TREE_KEYLOAD => {
+                    let m = self.user.handle_tree_keyload(msg, MsgInfo::TreeKeyload)?;
+                    let detail = Detail::Keyload(m.body);
+                    let u = m.map(|_| MessageContent::new_keyload());
+                    break Processed::with_detail(u, detail)
+                },
+                message::ROLE_ASSIGNMENT => {
+                    let m = self.user.handle_role_assignment(msg, MsgInfo::RoleAssignment)?;
+                    let u = m.map(|_| MessageContent::new_role_assignment());
+                    break u.into()
+                },
+                message::CERTIFICATE => {
+                    let m = self.user.handle_certificate(msg, MsgInfo::Certificate)?;
+                    let detail = Detail::Certificate(Box::new(m.body.clone()));
+                    let u = m.map(|_| MessageContent::new_certificate());
+                    break Processed::with_detail(u, detail)
+                },
+                message::ADMIN_SET => {
+                    let m = self.user.handle_admin_set(msg, MsgInfo::AdminSet)?;
+                    let u = m.map(|_| MessageContent::new_admin_set());
+                    break u.into()
+                },
+                message::DIRECT_MESSAGE => {
+                    let m = self.user.handle_direct_message(msg, MsgInfo::DirectMessage)?;
+                    let u = m.map(MessageContent::new_direct_message);
+                    break u.into()
+                },
+                message::TOPIC => {
+                    let m = self.user.handle_topic(msg, MsgInfo::Topic)?;
+                    let u = m.map(MessageContent::new_topic);
+                    break u.into()
                 },
                 message::SEQUENCE => {
                     let store_link = msg.link.rel().clone();
@@ -709,13 +2298,23 @@ impl<Trans: Transport> User<Trans>
                         &unwrapped.body.pk,
                         Cursor::new_at(&unwrapped.body.ref_link, 0, unwrapped.body.seq_num.0 as u32),
                     );
+                    self.user.track_topic(msg_link.msgid.clone(), &unwrapped.body.ref_link);
+                    if !self.user.is_topic_followed(&unwrapped.body.ref_link) {
+                        // Message of an unfollowed topic is not fetched, only the sequence is consumed
+                        self.user.store_sequence_state(unwrapped.body.pk, store_link, unwrapped.body.seq_num.0)?;
+                        break unwrapped.map(|_| MessageContent::Sequence).into();
+                    }
                     let msg = self.transport.recv_message(&msg_link).await?;
-                    self.user.store_state(unwrapped.body.pk.clone(), store_link)?;
+                    self.user.store_sequence_state(unwrapped.body.pk, store_link, unwrapped.body.seq_num.0)?;
                     msg0 = msg;
                 },
                 unknown_content => return err!(UnknownMsgType(unknown_content)),
             }
+        };
+        for key in keys {
+            self.processed.insert(key, u.clone());
         }
+        Ok(u.message)
     }
 
 }
@@ -1,15 +1,20 @@
 use iota_streams_core::Result;
 use core::{
     cell::RefCell,
+    convert::TryFrom as _,
     fmt,
+    hash,
 };
 
 use iota_streams_core::{
     prelude::{
         vec,
         Vec,
+        HashMap,
+        HashSet,
         typenum::U32,
         string::ToString,
+        String,
     },
     prng,
     psk,
@@ -26,6 +31,11 @@ use iota_streams_core_edsig::{
 
 use iota_streams_app::message::{
     hdf::{
+        FLAG_ADMINS_MASK,
+        FLAG_ANONYMOUS_KEYLOAD_MASK,
+        FLAG_AUDITORS_MASK,
+        FLAG_CERTIFIED_PUBLISHERS_MASK,
+        FLAG_RATCHET_MASK,
         FLAG_BRANCHING_MASK,
         HDF,
     },
@@ -44,12 +54,31 @@ use iota_streams_ddml::{
 
 use crate::{
     api::{
+        certificate::Certificate,
+        kdf::{
+            KdfParams,
+            KDF_ARGON2ID,
+            KDF_EXTERNAL_KEY,
+            KDF_X25519,
+        },
+        key_tree::{
+            KeyTree,
+            NodeId,
+        },
         pk_store::*,
+        proof::SignedPacketProof,
         psk_store::*,
+        rekey::RekeyState,
+        role::Role,
+        subscription::SubscriptionPolicy,
     },
     message::*,
 };
 
+fn topic_name(name: &Bytes) -> String {
+    String::from_utf8_lossy(&name.0).into_owned()
+}
+
 const ANN_MESSAGE_NUM: u32 = 0;
 const SUB_MESSAGE_NUM: u32 = 0;
 const SEQ_MESSAGE_NUM: u32 = 1;
@@ -90,6 +119,12 @@ pub struct WrappedSequence<F, Link: HasLink>(
     pub(crate) Option<WrapStateSequence<F, Link>>,
 );
 
+impl<F, Link: HasLink> Default for WrappedSequence<F, Link> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<F, Link: HasLink> WrappedSequence<F, Link> {
     pub fn new() -> Self {
         Self(None, None)
@@ -103,7 +138,7 @@ impl<F, Link: HasLink> WrappedSequence<F, Link> {
     pub fn with_wrapped(mut self, m: WrappedMessage<F, Link>) -> Self {
         self.0 = Some(m.message);
         let wrapped = m.wrapped;
-        self.1.as_mut().map(|w| w.set_state(wrapped));
+        if let Some(w) = self.1.as_mut() { w.set_state(wrapped) }
         self
     }
 }
@@ -129,9 +164,54 @@ where
     /// Users' trusted public keys together with additional sequencing info: (msgid, seq_no).
     pub(crate) pk_store: PKS,
 
+    /// Key tree for group rekeying: whole tree for Author, own path keys for Subscriber.
+    pub(crate) key_tree: KeyTree,
+
+    /// Session key found in a keyload part, kept until the final keyload is handled.
+    pub(crate) keyload_part_key: Option<(<Link as HasLink>::Rel, NBytes<U32>)>,
+
+    /// Policy for approving subscriptions [Author].
+    pub(crate) subscription_policy: SubscriptionPolicy,
+
+    /// Callback policy was exported, it's restored as `Manual` until a policy is set again [Author].
+    pub(crate) subscription_callback_lost: bool,
+
+    /// Subscribers awaiting approval, they are not trusted until approved [Author].
+    pub(crate) pending_subscribers: Vec<ed25519::PublicKey>,
+
+    /// Roles assigned by Author. Permissions are not enforced until a role is assigned.
+    pub(crate) roles: HashMap<ed25519::PublicKeyWrap, Role>,
+
+    /// Latest certificates issued by Author, keyed by the certified publisher.
+    pub(crate) certificates: HashMap<ed25519::PublicKeyWrap, Certificate<<Link as HasLink>::Rel>>,
+
     /// Author's Ed25519 public key.
     pub(crate) author_sig_pk: Option<ed25519::PublicKey>,
 
+    /// Administrators besides Author, they can send keyloads and accept subscriptions.
+    pub(crate) admins: Vec<ed25519::PublicKey>,
+
+    /// Auditors announced by Author, every keyload is shared with them.
+    pub(crate) auditors: Vec<ed25519::PublicKey>,
+
+    /// Pre-shared keys announced by Author for auditing, they are included in every keyload.
+    pub(crate) auditor_psks: psk::PskIds,
+
+    /// Topics declared by Author: topic name and link to the topic root message.
+    pub(crate) topics: Vec<(Bytes, <Link as HasLink>::Rel)>,
+
+    /// Names of the topics followed when fetching messages, None follows all the topics.
+    pub(crate) followed_topics: Option<Vec<Bytes>>,
+
+    /// Topic root of the known messages attached to a topic branch.
+    pub(crate) topic_links: HashMap<<Link as HasLink>::Rel, <Link as HasLink>::Rel>,
+
+    /// Latest keyload attached to each branch root, it's kept when the link store is pruned.
+    pub(crate) latest_keyloads: HashMap<<Link as HasLink>::Rel, <Link as HasLink>::Rel>,
+
+    /// Rekey policy and keyloads and packets sent since the last keyload [Author].
+    pub(crate) rekey: RekeyState<<Link as HasLink>::Rel>,
+
     /// Link generator.
     pub(crate) link_gen: LG,
 
@@ -142,6 +222,9 @@ where
     /// None if channel is not created or user is not subscribed.
     pub(crate) appinst: Option<Link>,
 
+    /// Pre-shared key masking the announcement of a hidden channel.
+    pub(crate) announce_pskid: Option<psk::PskId>,
+
     /// Flags bit field
     pub flags: u8,
 
@@ -173,10 +256,26 @@ where
 
             psk_store: PSKS::default(),
             pk_store: PKS::default(),
+            key_tree: KeyTree::default(),
+            keyload_part_key: None,
+            subscription_policy: SubscriptionPolicy::default(),
+            subscription_callback_lost: false,
+            pending_subscribers: Vec::new(),
+            roles: HashMap::new(),
+            certificates: HashMap::new(),
             author_sig_pk: None,
+            admins: Vec::new(),
+            auditors: Vec::new(),
+            auditor_psks: Vec::new(),
+            topics: Vec::new(),
+            followed_topics: None,
+            topic_links: HashMap::new(),
+            latest_keyloads: HashMap::new(),
+            rekey: RekeyState::default(),
             link_gen: LG::default(),
             link_store: RefCell::new(LS::default()),
             appinst: None,
+            announce_pskid: None,
             flags: 0,
             message_encoding: Vec::new(),
             uniform_payload_length: 0,
@@ -189,7 +288,7 @@ where
     F: PRP,
     Link: HasLink + AbsorbExternalFallback<F>,
     <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
-    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
     LG: LinkGenerator<Link>,
     LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
     PKS: PublicKeyStore<Cursor<<Link as HasLink>::Rel>>,
@@ -226,10 +325,26 @@ where
 
             psk_store: PSKS::default(),
             pk_store: PKS::default(),
+            key_tree: KeyTree::default(),
+            keyload_part_key: None,
+            subscription_policy: SubscriptionPolicy::default(),
+            subscription_callback_lost: false,
+            pending_subscribers: Vec::new(),
+            roles: HashMap::new(),
+            certificates: HashMap::new(),
             author_sig_pk: None,
+            admins: Vec::new(),
+            auditors: Vec::new(),
+            auditor_psks: Vec::new(),
+            topics: Vec::new(),
+            followed_topics: None,
+            topic_links: HashMap::new(),
+            latest_keyloads: HashMap::new(),
+            rekey: RekeyState::default(),
             link_gen: LG::default(),
             link_store: RefCell::new(LS::default()),
             appinst: None,
+            announce_pskid: None,
             flags,
             message_encoding,
             uniform_payload_length,
@@ -238,13 +353,31 @@ where
 
     /// Create a new channel (without announcing it). User now becomes Author.
     pub fn create_channel(&mut self, channel_idx: u64) -> Result<()> {
-        if self.appinst.is_some() {
-            return err!(ChannelCreationFailure(self.appinst.as_ref().unwrap().base().to_string()));
+        if let Some(appinst) = &self.appinst {
+            return err!(ChannelCreationFailure(appinst.base().to_string()));
         }
         self.link_gen.gen(&self.sig_kp.public, channel_idx);
+        self.init_channel()
+    }
+
+    /// Create a new hidden channel (without announcing it), the channel address is derived from
+    /// the pre-shared key and the announcement is masked with it. User now becomes Author.
+    ///
+    /// Note, messages other than announcement still carry public keys of their publishers.
+    pub fn create_hidden_channel(&mut self, pskid: psk::PskId, psk: psk::Psk, channel_idx: u64) -> Result<()> {
+        if let Some(appinst) = &self.appinst {
+            return err!(ChannelCreationFailure(appinst.base().to_string()));
+        }
+        self.link_gen.gen_hidden(&psk, channel_idx);
+        self.psk_store.insert(pskid, psk);
+        self.announce_pskid = Some(pskid);
+        self.init_channel()
+    }
+
+    fn init_channel(&mut self) -> Result<()> {
         let appinst = self.link_gen.get();
         self.pk_store.insert(
-            self.sig_kp.public.clone(),
+            self.sig_kp.public,
             Cursor::new_at(appinst.rel().clone(), 0, 2_u32),
         )?;
         self.author_sig_pk = Some(self.sig_kp.public);
@@ -261,15 +394,37 @@ where
         wrapped.commit(self.link_store.borrow_mut(), info)
     }
 
+    /// Commit a wrapped signed or tagged packet joined to `link_to` and ratchet its branch,
+    /// see `ratchet`. Received packets are ratcheted the same way when they are handled.
+    pub fn commit_packet(
+        &mut self,
+        wrapped: WrapState<F, Link>,
+        link_to: &<Link as HasLink>::Rel,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<Link> {
+        let link = wrapped.commit(self.link_store.borrow_mut(), info)?;
+        self.ratchet(link_to, link.rel())?;
+        self.rekey.track(link.rel().clone(), link_to);
+        Ok(link)
+    }
+
     /// Prepare Announcement message.
-    pub fn prepare_announcement<'a>(&'a self) -> Result<PreparedMessage<'a, F, Link, LS, announce::ContentWrap<F>>> {
+    pub fn prepare_announcement<'a>(&'a self) -> Result<PreparedMessage<'a, F, Link, LS, announce::ContentWrap<'a, F>>> {
         // Create HDF for the first message in the channel.
         let msg_link = self.link_gen.get();
+        let content = announce::ContentWrap::new(&self.sig_kp, self.flags, &self.admins)
+            .with_auditors(&self.auditors, &self.auditor_psks);
+        let (content_type, content) = match &self.announce_pskid {
+            Some(pskid) => match self.psk_store.get(pskid) {
+                Some(psk) => (HIDDEN_ANNOUNCE, content.with_psk(psk)),
+                None => return err!(PskNotFound),
+            },
+            None => (ANNOUNCE, content),
+        };
         let header = HDF::new(msg_link)
-            .with_content_type(ANNOUNCE)?
+            .with_content_type(content_type)?
             .with_payload_length(1)?
             .with_seq_num(ANN_MESSAGE_NUM);
-        let content = announce::ContentWrap::new(&self.sig_kp, self.flags);
         Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
     }
 
@@ -289,6 +444,17 @@ where
             )?;
         }
 
+        if preparsed.content_type() == HIDDEN_ANNOUNCE {
+            // Pre-shared key is not published, try all the known keys
+            for (pskid, psk) in self.psk_store.iter() {
+                let content = announce::ContentUnwrap::<F>::default().with_psk(*pskid, *psk);
+                if let Ok(unwrapped) = preparsed.clone().unwrap(&*self.link_store.borrow(), content) {
+                    return Ok(unwrapped);
+                }
+            }
+            return err!(HiddenAnnouncementKeyNotFound);
+        }
+
         let content = announce::ContentUnwrap::<F>::default();
         let r = preparsed.unwrap(&*self.link_store.borrow(), content);
         r
@@ -296,14 +462,14 @@ where
 
     /// Bind Subscriber (or anonymously subscribe) to the channel announced
     /// in the message.
-    pub fn handle_announcement<'a>(
+    pub fn handle_announcement(
         &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
     ) -> Result<()> {
         let preparsed = msg.parse_header()?;
         try_or!(
-            preparsed.content_type() == ANNOUNCE,
+            preparsed.content_type() == ANNOUNCE || preparsed.content_type() == HIDDEN_ANNOUNCE,
             NotAnnouncement(preparsed.content_type())
         )?;
 
@@ -319,44 +485,224 @@ where
         // At the moment the Author is free to choose any address, not tied to PK.
 
         let cursor = Cursor::new_at(link.rel().clone(), 0, 2_u32);
-        self.pk_store.insert(content.sig_pk.clone(), cursor.clone())?;
-        self.pk_store.insert(self.sig_kp.public.clone(), cursor)?;
+        self.pk_store.insert(content.sig_pk, cursor.clone())?;
+        self.pk_store.insert(self.sig_kp.public, cursor)?;
         // Reset link_gen
         self.link_gen.reset(link.clone());
         self.appinst = Some(link);
         self.author_sig_pk = Some(content.sig_pk);
         self.flags = content.flags.0;
+        self.announce_pskid = content.psk.map(|(pskid, _)| pskid);
+        self.auditor_psks = content.auditor_psks;
+        self.set_auditors(content.auditors)?;
+        self.set_admins(content.admins)
+    }
+
+    /// Replace administrators, new ones are tracked as publishers.
+    fn set_admins(&mut self, admins: Vec<ed25519::PublicKey>) -> Result<()> {
+        if let Some(appinst) = &self.appinst {
+            for pk in admins.iter() {
+                if self.pk_store.get(pk).is_none() {
+                    // Store at state 2 since 0 and 1 are reserved states
+                    self.pk_store.insert(*pk, Cursor::new_at(appinst.rel().clone(), 0, 2))?;
+                }
+            }
+        }
+        self.admins = admins;
         Ok(())
     }
 
-    /// Prepare Subscribe message.
-    pub fn prepare_subscribe<'a>(
-        &'a mut self,
+    /// Replace auditors, they are tracked as publishers so that their exchange keys are at hand.
+    fn set_auditors(&mut self, auditors: Vec<ed25519::PublicKey>) -> Result<()> {
+        if let Some(appinst) = &self.appinst {
+            for pk in auditors.iter() {
+                if self.pk_store.get(pk).is_none() {
+                    self.pk_store.insert(*pk, Cursor::new_at(appinst.rel().clone(), 0, 2))?;
+                }
+            }
+        }
+        self.auditors = auditors;
+        Ok(())
+    }
+
+    /// Check whether the announcement of the channel has been created or received.
+    fn is_announced(&self) -> bool {
+        match &self.appinst {
+            Some(appinst) => self.link_store.borrow().lookup(appinst.rel()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Add an auditor, the session key of every keyload is shared with it [Author].
+    /// Auditors are recorded in the announcement, so they must be added before the channel is announced.
+    pub fn add_auditor(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        try_or!(
+            self.author_sig_pk == Some(self.sig_kp.public) && !self.is_announced(),
+            AuditorsAlreadyAnnounced
+        )?;
+        if !self.auditors.contains(pk) {
+            let mut auditors = self.auditors.clone();
+            auditors.push(*pk);
+            self.set_auditors(auditors)?;
+        }
+        self.flags |= FLAG_AUDITORS_MASK;
+        Ok(())
+    }
+
+    /// Add a stored pre-shared key to every keyload, it's recorded in the announcement [Author].
+    pub fn add_auditor_psk(&mut self, pskid: psk::PskId) -> Result<()> {
+        try_or!(
+            self.author_sig_pk == Some(self.sig_kp.public) && !self.is_announced(),
+            AuditorsAlreadyAnnounced
+        )?;
+        try_or!(self.psk_store.get(&pskid).is_some(), PskNotFound)?;
+        if !self.auditor_psks.contains(&pskid) {
+            self.auditor_psks.push(pskid);
+        }
+        self.flags |= FLAG_AUDITORS_MASK;
+        Ok(())
+    }
+
+    /// Auditors of the channel: public keys and pre-shared key identifiers included in every keyload.
+    pub fn auditors(&self) -> (&[ed25519::PublicKey], &[psk::PskId]) {
+        (&self.auditors, &self.auditor_psks)
+    }
+
+    /// Add an administrator, it's announced with the channel or with the next admin set [Author].
+    /// Administrators added before the channel is announced set `FLAG_ADMINS_MASK`, channels
+    /// announced without it can't have administrators.
+    pub fn add_admin(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), AdminSetIssuerNotAuthorized)?;
+        if !self.is_announced() {
+            self.flags |= FLAG_ADMINS_MASK;
+        }
+        try_or!(self.has_admins(), AdminsNotAnnounced)?;
+        if !self.admins.contains(pk) && self.author_sig_pk.as_ref() != Some(pk) {
+            let mut admins = self.admins.clone();
+            admins.push(*pk);
+            self.set_admins(admins)?;
+        }
+        Ok(())
+    }
+
+    /// Remove an administrator, its keyloads are rejected once the next admin set is received [Author].
+    pub fn remove_admin(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), AdminSetIssuerNotAuthorized)?;
+        match self.admins.iter().position(|p| p == pk) {
+            Some(pos) => {
+                self.admins.remove(pos);
+                Ok(())
+            }
+            None => err!(AdminNotFound),
+        }
+    }
+
+    pub fn admins(&self) -> &[ed25519::PublicKey] {
+        &self.admins
+    }
+
+    /// Author and administrators can send keyloads.
+    pub fn is_admin(&self, pk: &ed25519::PublicKey) -> bool {
+        self.author_sig_pk.as_ref() == Some(pk) || self.admins.contains(pk)
+    }
+
+    /// Prepare AdminSet message carrying all the administrators.
+    pub fn prepare_admin_set<'a>(
+        &'a self,
         link_to: &'a <Link as HasLink>::Rel,
-    ) -> Result<PreparedMessage<'a, F, Link, LS, subscribe::ContentWrap<'a, F, Link>>> {
-        if let Some(author_sig_pk) = &self.author_sig_pk {
-            if let Some(author_ke_pk) = self.pk_store.get_ke_pk(author_sig_pk) {
+    ) -> Result<PreparedMessage<'a, F, Link, LS, admin_set::ContentWrap<'a, F, Link>>> {
+        match self.get_seq_no() {
+            Some(seq_no) => {
                 let msg_link = self
                     .link_gen
-                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, SUB_MESSAGE_NUM));
+                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
                 let header = HDF::new(msg_link)
-                    .with_content_type(SUBSCRIBE)?
+                    .with_content_type(ADMIN_SET)?
                     .with_payload_length(1)?
-                    .with_seq_num(SUB_MESSAGE_NUM);
-                let unsubscribe_key = NBytes::from(prng::random_key());
-                let content = subscribe::ContentWrap {
+                    .with_seq_num(seq_no);
+                let content = admin_set::ContentWrap {
                     link: link_to,
-                    unsubscribe_key,
-                    subscriber_sig_kp: &self.sig_kp,
-                    author_ke_pk: author_ke_pk,
+                    admins: &self.admins,
+                    sig_kp: &self.sig_kp,
                     _phantom: core::marker::PhantomData,
                 };
                 Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
-            } else {
-                err!(AuthorExchangeKeyNotFound)
-            }
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create AdminSet message.
+    pub fn share_admin_set(&self, link_to: &<Link as HasLink>::Rel) -> Result<WrappedMessage<F, Link>> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), AdminSetIssuerNotAuthorized)?;
+        try_or!(self.has_admins(), AdminsNotAnnounced)?;
+        self.prepare_admin_set(link_to)?.wrap()
+    }
+
+    pub fn unwrap_admin_set<'a>(
+        &'a self,
+        preparsed: PreparsedMessage<'a, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, admin_set::ContentUnwrap<F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let content = admin_set::ContentUnwrap::default();
+        preparsed.unwrap(&*self.link_store.borrow(), content)
+    }
+
+    /// Verify Author's signature and replace the known administrators.
+    pub fn handle_admin_set(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, ()>> {
+        try_or!(self.has_admins(), AdminsNotAnnounced)?;
+        let preparsed = msg.parse_header()?;
+        let unwrapped = self.unwrap_admin_set(preparsed)?;
+        try_or!(
+            self.author_sig_pk.as_ref() == Some(&unwrapped.pcf.content.sig_pk),
+            AdminSetIssuerNotAuthorized
+        )?;
+        let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+        self.set_admins(content.admins)?;
+        Ok(GenericMessage::new(msg.link, ()))
+    }
+
+    /// Prepare Subscribe message.
+    pub fn prepare_subscribe<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, subscribe::ContentWrap<'a, F, Link>>> {
+        match self.author_sig_pk {
+            Some(author_sig_pk) => self.prepare_subscribe_to(link_to, &author_sig_pk),
+            None => err!(AuthorSigKeyNotFound),
+        }
+    }
+
+    /// Prepare Subscribe message readable by the given administrator only.
+    pub fn prepare_subscribe_to<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        admin_sig_pk: &ed25519::PublicKey,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, subscribe::ContentWrap<'a, F, Link>>> {
+        try_or!(self.is_admin(admin_sig_pk), AdminNotFound)?;
+        if let Some(admin_ke_pk) = self.pk_store.get_ke_pk(admin_sig_pk) {
+            let msg_link = self
+                .link_gen
+                .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, SUB_MESSAGE_NUM));
+            let header = HDF::new(msg_link)
+                .with_content_type(SUBSCRIBE)?
+                .with_payload_length(1)?
+                .with_seq_num(SUB_MESSAGE_NUM);
+            let unsubscribe_key = NBytes::from(prng::random_key());
+            let content = subscribe::ContentWrap {
+                link: link_to,
+                unsubscribe_key,
+                subscriber_sig_kp: &self.sig_kp,
+                author_ke_pk: admin_ke_pk,
+                _phantom: core::marker::PhantomData,
+            };
+            Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
         } else {
-            err!(AuthorSigKeyNotFound)
+            err!(AuthorExchangeKeyNotFound)
         }
     }
 
@@ -365,17 +711,26 @@ where
         self.prepare_subscribe(link_to)?.wrap()
     }
 
+    /// Subscribe to the channel via an administrator, the subscription is not seen by Author.
+    pub fn subscribe_to(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        admin_sig_pk: &ed25519::PublicKey,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_subscribe_to(link_to, admin_sig_pk)?.wrap()
+    }
+
     pub fn unwrap_subscribe<'a>(
         &self,
         preparsed: PreparsedMessage<'a, F, Link>,
-    ) -> Result<UnwrappedMessage<F, Link, subscribe::ContentUnwrap<F, Link>>> {
+    ) -> Result<UnwrappedMessage<F, Link, subscribe::ContentUnwrap<'_, F, Link>>> {
         self.ensure_appinst(&preparsed)?;
         let content = subscribe::ContentUnwrap::new(&self.ke_kp.0)?;
         preparsed.unwrap(&*self.link_store.borrow(), content)
     }
 
     /// Get public payload, decrypt masked payload and verify MAC.
-    pub fn handle_subscribe<'a>(
+    pub fn handle_subscribe(
         &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
@@ -386,13 +741,87 @@ where
         let content = self
             .unwrap_subscribe(preparsed)?
             .commit(self.link_store.borrow_mut(), info)?;
-        // TODO: trust content.subscriber_sig_pk
         let subscriber_sig_pk = content.subscriber_sig_pk;
+        // Unwrapped unsubscribe_key is not used explicitly.
+        if self.pk_store.get(&subscriber_sig_pk).is_some() || self.pending_subscribers.contains(&subscriber_sig_pk) {
+            return Ok(());
+        }
+        if self.subscription_policy.approves(&subscriber_sig_pk, self.subscribers_count()) {
+            self.trust_subscriber(subscriber_sig_pk)
+        } else {
+            self.pending_subscribers.push(subscriber_sig_pk);
+            Ok(())
+        }
+    }
+
+    fn trust_subscriber(&mut self, pk: ed25519::PublicKey) -> Result<()> {
         let ref_link = self.appinst.as_ref().unwrap().rel().clone();
+        self.pk_store.insert(pk, Cursor::new_at(ref_link, 0, SEQ_MESSAGE_NUM))
+    }
+
+    /// Number of approved subscribers, not counting the user itself.
+    fn subscribers_count(&self) -> usize {
         self.pk_store
-            .insert(subscriber_sig_pk, Cursor::new_at(ref_link, 0, SEQ_MESSAGE_NUM))?;
-        // Unwrapped unsubscribe_key is not used explicitly.
-        Ok(())
+            .keys()
+            .into_iter()
+            .filter(|(pk, _)| **pk != self.sig_kp.public)
+            .count()
+    }
+
+    pub fn set_subscription_policy(&mut self, policy: SubscriptionPolicy) {
+        self.subscription_policy = policy;
+        self.subscription_callback_lost = false;
+    }
+
+    /// Fail if subscriptions are kept pending because a callback policy couldn't be restored
+    /// with imported state.
+    pub fn check_subscription_policy(&self) -> Result<()> {
+        try_or!(!self.subscription_callback_lost, SubscriptionCallbackNotRestored)
+    }
+
+    /// Subscribers awaiting approval.
+    pub fn pending_subscribers(&self) -> &[ed25519::PublicKey] {
+        &self.pending_subscribers
+    }
+
+    /// Approve a pending subscriber: it's included in keyloads and accepted as publisher.
+    pub fn approve_subscriber(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        match self.pending_subscribers.iter().position(|p| p == pk) {
+            Some(pos) => {
+                let pk = self.pending_subscribers.remove(pos);
+                self.trust_subscriber(pk)
+            }
+            None => err!(SubscriptionNotPending),
+        }
+    }
+
+    /// Drop a pending subscriber.
+    pub fn reject_subscriber(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        match self.pending_subscribers.iter().position(|p| p == pk) {
+            Some(pos) => {
+                self.pending_subscribers.remove(pos);
+                Ok(())
+            }
+            None => err!(SubscriptionNotPending),
+        }
+    }
+
+    /// Append the auditors missing from keyload recipients. Auditor pre-shared keys unknown to the user are skipped.
+    fn add_auditor_recipients<'a>(
+        &'a self,
+        psks: &mut Vec<psk::IPsk<'a>>,
+        ke_pks: &mut Vec<(ed25519::IPk<'a>, x25519::IPk<'a>)>,
+    ) {
+        for (pskid, psk) in self.psk_store.filter(&self.auditor_psks) {
+            if !psks.iter().any(|(id, _)| *id == pskid) {
+                psks.push((pskid, psk));
+            }
+        }
+        for (pk, ke_pk) in self.pk_store.filter(&self.auditors) {
+            if !ke_pks.iter().any(|(p, _)| *p == pk) {
+                ke_pks.push((pk, ke_pk));
+            }
+        }
     }
 
     fn do_prepare_keyload<'a, Psks, KePks>(
@@ -401,20 +830,22 @@ where
         link_to: &'a <Link as HasLink>::Rel,
         psks: Psks,
         ke_pks: KePks,
+        key: NBytes<U32>,
     ) -> Result<PreparedMessage<'a, F, Link, LS, keyload::ContentWrap<'a, F, Link, Psks, KePks>>>
     where
         Psks: Clone + ExactSizeIterator<Item = psk::IPsk<'a>>,
         KePks: Clone + ExactSizeIterator<Item = (ed25519::IPk<'a>, x25519::IPk<'a>)>,
     {
         let nonce = NBytes::from(prng::random_nonce());
-        let key = NBytes::from(prng::random_key());
         let content = keyload::ContentWrap {
             link: link_to,
-            nonce: nonce,
-            key: key,
-            psks: psks,
-            ke_pks: ke_pks,
+            nonce,
+            key,
+            psks,
+            ke_pks,
             sig_kp: &self.sig_kp,
+            anonymous: self.is_anonymous_keyload(),
+            with_sig_pk: self.has_admins() || header.content_type == PRIVATE_KEYLOAD,
             _phantom: core::marker::PhantomData,
         };
         Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
@@ -424,7 +855,7 @@ where
         &'a mut self,
         link_to: &'a <Link as HasLink>::Rel,
         psk_ids: &psk::PskIds,
-        pks: &'a Vec<ed25519::PublicKey>,
+        pks: &'a [ed25519::PublicKey],
     ) -> Result<
         PreparedMessage<
             'a,
@@ -449,9 +880,11 @@ where
                     .with_content_type(KEYLOAD)?
                     .with_payload_length(1)?
                     .with_seq_num(seq_no);
-                let psks = self.psk_store.filter(psk_ids);
-                let ke_pks = self.pk_store.filter(pks);
-                self.do_prepare_keyload(header, link_to, psks.into_iter(), ke_pks.into_iter())
+                let mut psks = self.psk_store.filter(psk_ids);
+                let mut ke_pks = self.pk_store.filter(pks);
+                self.add_auditor_recipients(&mut psks, &mut ke_pks);
+                let key = NBytes::from(prng::random_key());
+                self.do_prepare_keyload(header, link_to, psks.into_iter(), ke_pks.into_iter(), key)
             },
             None => err!(SeqNumRetrievalFailure)
         }
@@ -484,31 +917,217 @@ where
                     .with_content_type(KEYLOAD)?
                     .with_payload_length(1)?
                     .with_seq_num(seq_no);
-                let ipsks = self.psk_store.iter();
-                let ike_pks = self.pk_store.keys();
-                self.do_prepare_keyload(header, link_to, ipsks.into_iter(), ike_pks.into_iter())
+                let mut ipsks = self.psk_store.iter();
+                let mut ike_pks = self.pk_store.keys();
+                self.add_auditor_recipients(&mut ipsks, &mut ike_pks);
+                let key = NBytes::from(prng::random_key());
+                self.do_prepare_keyload(header, link_to, ipsks.into_iter(), ike_pks.into_iter(), key)
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Prepare one part of a keyload split into several messages. All parts are joined to `link_to`
+    /// and share the session `key`, the `last` part is a `Keyload` and the preceding ones are `KeyloadPart`s.
+    /// Part's address is derived from `prev`, the preceding part or `link_to` for the first one.
+    pub fn prepare_keyload_part<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        prev: &<Link as HasLink>::Rel,
+        psk_ids: &psk::PskIds,
+        pks: &'a [ed25519::PublicKey],
+        key: NBytes<U32>,
+        last: bool,
+    ) -> Result<
+        PreparedMessage<
+            'a,
+            F,
+            Link,
+            LS,
+            keyload::ContentWrap<
+                'a,
+                F,
+                Link,
+                vec::IntoIter<psk::IPsk<'a>>,
+                vec::IntoIter<(ed25519::IPk<'a>, x25519::IPk<'a>)>,
+            >,
+        >,
+    > {
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(prev, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(if last { KEYLOAD } else { KEYLOAD_PART })?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let mut psks = self.psk_store.filter(psk_ids);
+                let mut ke_pks = self.pk_store.filter(pks);
+                self.add_auditor_recipients(&mut psks, &mut ke_pks);
+                self.do_prepare_keyload(header, link_to, psks.into_iter(), ke_pks.into_iter(), key)
             },
             None => err!(SeqNumRetrievalFailure)
         }
     }
 
+    /// Create one part of a keyload split into several messages.
+    pub fn share_keyload_part(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        prev: &<Link as HasLink>::Rel,
+        psk_ids: &psk::PskIds,
+        ke_pks: &[ed25519::PublicKey],
+        key: NBytes<U32>,
+        last: bool,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_keyload_part(link_to, prev, psk_ids, ke_pks, key, last)?.wrap()
+    }
+
+    /// Identifiers of all pre-shared keys and Subscribers known to Author.
+    pub fn keyload_recipients_for_everyone(&self) -> (psk::PskIds, Vec<ed25519::PublicKey>) {
+        let psk_ids = self.psk_store.iter().into_iter().map(|(pskid, _)| *pskid).collect();
+        let pks = self.pk_store.keys().into_iter().map(|(pk, _)| *pk).collect();
+        (psk_ids, pks)
+    }
+
+    /// Split known keyload recipients into parts such that each keyload message fits
+    /// into `uniform_payload_length` bytes. Zero `uniform_payload_length` means no limit.
+    /// Auditors are left out of the parts since they're added to each of them.
+    pub fn split_keyload_recipients(
+        &self,
+        link_to: &<Link as HasLink>::Rel,
+        psk_ids: &psk::PskIds,
+        pks: &[ed25519::PublicKey],
+    ) -> Result<Vec<(psk::PskIds, Vec<ed25519::PublicKey>)>> {
+        let psk_ids: psk::PskIds = psk_ids
+            .iter()
+            .filter(|id| self.psk_store.get(id).is_some() && !self.auditor_psks.contains(id))
+            .cloned()
+            .collect();
+        let pks: Vec<ed25519::PublicKey> = pks
+            .iter()
+            .filter(|pk| self.pk_store.get_ke_pk(pk).is_some() && !self.auditors.contains(pk))
+            .cloned()
+            .collect();
+        let limit = self.uniform_payload_length;
+        if limit == 0 {
+            return Ok(vec![(psk_ids, pks)]);
+        }
+
+        // Size of keyload without recipients, both repeated counts are encoded with 1 byte.
+        let base = {
+            let header = HDF::new(self.link_gen.get())
+                .with_content_type(KEYLOAD)?
+                .with_payload_length(1)?
+                .with_seq_num(0);
+            let no_psks: Vec<psk::IPsk> = Vec::new();
+            let no_ke_pks: Vec<(ed25519::IPk, x25519::IPk)> = Vec::new();
+            self.do_prepare_keyload(header, link_to, no_psks.into_iter(), no_ke_pks.into_iter(), NBytes::default())?
+                .size()? - 2
+        };
+        let psk_fork = keyload::psk_fork_size::<F>(self.is_anonymous_keyload());
+        let ke_pk_fork = keyload::ke_pk_fork_size::<F>(self.is_anonymous_keyload());
+        let n_auditor_psks = self.psk_store.filter(&self.auditor_psks).len();
+        let n_auditors = self.pk_store.filter(&self.auditors).len();
+        let size = |n_psks: usize, n_pks: usize| {
+            let (n_psks, n_pks) = (n_psks + n_auditor_psks, n_pks + n_auditors);
+            base + sizeof_sizet(n_psks) + n_psks * psk_fork + sizeof_sizet(n_pks) + n_pks * ke_pk_fork
+        };
+        try_or!(size(0, 0) <= limit, MaxSizeExceeded(limit, size(0, 0)))?;
+
+        let mut parts = Vec::new();
+        let mut part: (psk::PskIds, Vec<ed25519::PublicKey>) = (Vec::new(), Vec::new());
+        for pskid in psk_ids {
+            if size(part.0.len() + 1, 0) > limit {
+                try_or!(!part.0.is_empty(), MaxSizeExceeded(limit, size(1, 0)))?;
+                parts.push(core::mem::take(&mut part));
+            }
+            part.0.push(pskid);
+        }
+        for pk in pks {
+            if size(part.0.len(), part.1.len() + 1) > limit {
+                try_or!(!part.0.is_empty() || !part.1.is_empty(), MaxSizeExceeded(limit, size(0, 1)))?;
+                parts.push(core::mem::take(&mut part));
+            }
+            part.1.push(pk);
+        }
+        parts.push(part);
+        Ok(parts)
+    }
+
     /// Create keyload message with a new session key shared with recipients
     /// identified by pre-shared key IDs and by NTRU public key IDs.
     pub fn share_keyload(
         &mut self,
         link_to: &<Link as HasLink>::Rel,
         psk_ids: &psk::PskIds,
-        ke_pks: &Vec<ed25519::PublicKey>,
+        ke_pks: &[ed25519::PublicKey],
     ) -> Result<WrappedMessage<F, Link>> {
         self.prepare_keyload(link_to, psk_ids, ke_pks)?.wrap()
     }
 
+    /// Prepare PrivateKeyload message starting a private branch with participants known to the user.
+    pub fn prepare_private_keyload<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        pks: &'a [ed25519::PublicKey],
+    ) -> Result<
+        PreparedMessage<
+            'a,
+            F,
+            Link,
+            LS,
+            keyload::ContentWrap<
+                'a,
+                F,
+                Link,
+                vec::IntoIter<psk::IPsk<'a>>,
+                vec::IntoIter<(ed25519::IPk<'a>, x25519::IPk<'a>)>,
+            >,
+        >,
+    > {
+        try_or!(pks.iter().all(|pk| self.pk_store.get_ke_pk(pk).is_some()), SubscriberNotFound)?;
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(PRIVATE_KEYLOAD)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let mut psks = Vec::new();
+                let mut ke_pks = self.pk_store.filter(pks);
+                self.add_auditor_recipients(&mut psks, &mut ke_pks);
+                let key = NBytes::from(prng::random_key());
+                self.do_prepare_keyload(header, link_to, psks.into_iter(), ke_pks.into_iter(), key)
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create private keyload message with a new session key shared with the given participants,
+    /// messages linked to it form a private branch of the channel.
+    pub fn share_private_keyload(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        pks: &[ed25519::PublicKey],
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_private_keyload(link_to, pks)?.wrap()
+    }
+
     /// Create keyload message with a new session key shared with all Subscribers
     /// known to Author.
     pub fn share_keyload_for_everyone(&mut self, link_to: &<Link as HasLink>::Rel) -> Result<WrappedMessage<F, Link>> {
         self.prepare_keyload_for_everyone(link_to)?.wrap()
     }
 
+    /// Store a pre-shared key, it's used in keyloads and to unmask hidden announcements.
+    pub fn store_psk(&mut self, pskid: psk::PskId, psk: psk::Psk) {
+        self.psk_store.insert(pskid, psk);
+    }
+
     fn lookup_psk<'b>(&'b self, pskid: &psk::PskId) -> Option<&'b psk::Psk> {
         self.psk_store.get(pskid)
     }
@@ -539,7 +1158,7 @@ where
         >,
     > {
         self.ensure_appinst(&preparsed)?;
-        if let Some(ref author_sig_pk) = self.author_sig_pk {
+        if self.author_sig_pk.is_some() {
             let content = keyload::ContentUnwrap::<
                 'b,
                 F,
@@ -547,7 +1166,22 @@ where
                 Self,
                 for<'c> fn(&'c Self, &psk::PskId) -> Option<&'c psk::Psk>,
                 for<'c> fn(&'c Self, &ed25519::PublicKey) -> Option<&'c x25519::StaticSecret>,
-            >::new(self, Self::lookup_psk, Self::lookup_ke_sk, author_sig_pk);
+            >::new(self, Self::lookup_psk, Self::lookup_ke_sk);
+            let content = if self.is_anonymous_keyload() {
+                content.with_anonymous(self.psk_store.iter(), Some(&self.ke_kp.0))
+            } else {
+                content
+            };
+            let content = match self.author_sig_pk {
+                Some(author_sig_pk) if !self.has_admins() && preparsed.content_type() != PRIVATE_KEYLOAD => {
+                    content.with_signer(author_sig_pk)
+                }
+                _ => content,
+            };
+            let content = match &self.keyload_part_key {
+                Some((link, key)) => content.with_carried_key(link.clone(), *key),
+                None => content,
+            };
             let unwrapped = preparsed.unwrap(&*self.link_store.borrow(), content)?;
             Ok(unwrapped)
         } else {
@@ -555,59 +1189,717 @@ where
         }
     }
 
-    /// Try unwrapping session key from keyload using Subscriber's pre-shared key or NTRU private key (if any).
-    pub fn handle_keyload<'a>(
+    fn do_handle_keyload(
         &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
-    ) -> Result<GenericMessage<Link, bool>> {
+        private: bool,
+    ) -> Result<Option<(<Link as HasLink>::Rel, NBytes<U32>)>> {
         let preparsed = msg.parse_header()?;
 
         let unwrapped = self
             .unwrap_keyload(preparsed)?;
 
         if unwrapped.pcf.content.key.is_some() {
+            if private {
+                try_or!(self.pk_store.get(&unwrapped.pcf.content.sig_pk).is_some(), KeyloadSignerUnknown)?;
+            } else {
+                try_or!(self.is_admin(&unwrapped.pcf.content.sig_pk), KeyloadSignerNotAdmin)?;
+            }
             // Do not commit if key not found hence spongos state is invalid
-            let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+            let (link, key, ke_pks) = {
+                let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+                (content.link, content.key, content.ke_pks)
+            };
 
             // Presence of the key indicates the user is allowed
-            // Unwrapped nonce and key in content are not used explicitly.
+            // Unwrapped nonce in content is not used explicitly.
             // The resulting spongos state is joined into a protected message state.
-            // Store any unknown publishers
-            if let Some(appinst) = &self.appinst {
-                for ke_pk in content.ke_pks {
+            // Store any unknown publishers, private keyloads can't introduce new participants
+            if let (Some(appinst), false) = (&self.appinst, private) {
+                for ke_pk in ke_pks {
                     if self.pk_store.get(&ke_pk).is_none() {
                         // Store at state 2 since 0 and 1 are reserved states
                         self.pk_store.insert(ke_pk, Cursor::new_at(appinst.rel().clone(), 0, 2))?;
                     }
                 }
             }
+            Ok(key.map(|key| (link, key)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Try unwrapping session key from keyload using Subscriber's pre-shared key or NTRU private key (if any).
+    pub fn handle_keyload(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, bool>> {
+        let link = msg.link.clone();
+        let found = self.do_handle_keyload(msg, info, false)?;
+        if let Some((ref link_to, _)) = found {
+            self.track_keyload(link.rel().clone(), link_to);
+        }
+        // Session key of the preceding parts is not needed anymore.
+        self.keyload_part_key = None;
+        Ok(GenericMessage::new(link, found.is_some()))
+    }
+
+    /// Try unwrapping session key from keyload part, the key is kept to unwrap the final keyload.
+    pub fn handle_keyload_part(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, bool>> {
+        let link = msg.link.clone();
+        let found = self.do_handle_keyload(msg, info, false)?;
+        let allowed = found.is_some();
+        if allowed {
+            self.keyload_part_key = found;
+        }
+        Ok(GenericMessage::new(link, allowed))
+    }
+
+    /// Try unwrapping session key from private keyload sent by a known participant.
+    pub fn handle_private_keyload(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, bool>> {
+        let link = msg.link.clone();
+        let found = self.do_handle_keyload(msg, info, true)?;
+        if let Some((ref link_to, _)) = found {
+            self.track_keyload(link.rel().clone(), link_to);
+        }
+        Ok(GenericMessage::new(link, found.is_some()))
+    }
+
+    /// Add a known subscriber to the key tree, the leaf key is sent with the next tree keyload.
+    pub fn add_tree_member(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        try_or!(self.pk_store.get_ke_pk(pk).is_some(), SubscriberNotFound)?;
+        self.key_tree.add_member(*pk)?;
+        Ok(())
+    }
+
+    /// Remove subscriber from the key tree, it won't be able to recover the group key
+    /// distributed with the next tree keyload.
+    pub fn remove_tree_member(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.key_tree.remove_member(pk)
+    }
+
+    /// Refresh the group key without changing members.
+    pub fn rekey_tree(&mut self) {
+        self.key_tree.rekey()
+    }
+
+    /// Prepare TreeKeyload message distributing pending key tree updates to the given members.
+    /// Updates may be split into several tree keyloads joined to `link_to`, message's address is derived from `prev`, the preceding part or `link_to` for the first one.
+    pub fn prepare_tree_keyload<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        prev: &<Link as HasLink>::Rel,
+        members: &[ed25519::PublicKey],
+    ) -> Result<PreparedMessage<'a, F, Link, LS, tree_keyload::ContentWrap<'a, F, Link>>> {
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(prev, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(TREE_KEYLOAD)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let key_tree = &self.key_tree;
+                let pk_store = &self.pk_store;
+                match key_tree.root_key() {
+                    Some(key) => {
+                        let joins = key_tree
+                            .joins
+                            .iter()
+                            .filter(|pk| members.contains(pk))
+                            .filter_map(|pk| {
+                                let leaf = key_tree.leaf(pk)?;
+                                Some((pk, pk_store.get_ke_pk(pk)?, leaf, key_tree.get(leaf)?))
+                            })
+                            .collect();
+                        let leaves: Vec<NodeId> = members.iter().filter_map(|pk| key_tree.leaf(pk)).collect();
+                        let content = tree_keyload::ContentWrap {
+                            link: link_to,
+                            nonce: NBytes::from(prng::random_nonce()),
+                            joins,
+                            updates: key_tree.path_updates(&leaves),
+                            key,
+                            sig_kp: &self.sig_kp,
+                            _phantom: core::marker::PhantomData,
+                        };
+                        Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
+                    },
+                    None => err!(KeyTreeEmpty),
+                }
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create TreeKeyload message for the given members, pending key tree updates are kept
+    /// until `commit_tree_keyload`.
+    pub fn share_tree_keyload(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        prev: &<Link as HasLink>::Rel,
+        members: &[ed25519::PublicKey],
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_tree_keyload(link_to, prev, members)?.wrap()
+    }
+
+    /// Consider pending key tree updates distributed, to be called once all the tree keyloads are sent.
+    pub fn commit_tree_keyload(&mut self) {
+        self.key_tree.clear_pending();
+    }
+
+    /// Split key tree members into groups such that each tree keyload fits into
+    /// `uniform_payload_length` bytes. Zero `uniform_payload_length` means no limit.
+    /// A tree keyload carries whole paths of its members, so that they recover their path keys from it.
+    pub fn split_tree_keyload_members(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+    ) -> Result<Vec<Vec<ed25519::PublicKey>>> {
+        // Neighbouring leaves share most of their paths.
+        let mut members: Vec<(NodeId, ed25519::PublicKey)> =
+            self.key_tree.leaves.iter().map(|(pk, leaf)| (*leaf, pk.0)).collect();
+        members.sort_unstable_by_key(|(leaf, _)| *leaf);
+        let limit = self.uniform_payload_length;
+        if limit == 0 {
+            return Ok(vec![members.into_iter().map(|(_, pk)| pk).collect()]);
+        }
+
+        // Size of tree keyload without joins and updates, both repeated counts are encoded with 1 byte.
+        // Updates of the root children are included in every tree keyload.
+        let root_children = self.key_tree.root_children();
+        let join_size = tree_keyload::join_size::<F>();
+        let update_size = tree_keyload::update_size::<F>();
+        let base = self.prepare_tree_keyload(link_to, link_to, &[])?.size()? - 2 - root_children.len() * update_size;
+        let size = |n_joins: usize, n_updates: usize| {
+            base + sizeof_sizet(n_joins) + n_joins * join_size + sizeof_sizet(n_updates) + n_updates * update_size
+        };
+
+        let mut parts = Vec::new();
+        let mut part = Vec::new();
+        let mut n_joins = 0;
+        let mut children: HashSet<NodeId> = root_children.iter().copied().collect();
+        for (leaf, pk) in members {
+            let join = self.key_tree.joins.contains(&pk) as usize;
+            let path = self.key_tree.path_children(leaf);
+            let n_new = path.iter().filter(|child| !children.contains(*child)).count();
+            if size(n_joins + join, children.len() + n_new) > limit && !part.is_empty() {
+                parts.push(core::mem::take(&mut part));
+                n_joins = 0;
+                children = root_children.iter().copied().collect();
+            }
+            children.extend(path);
+            n_joins += join;
+            try_or!(size(n_joins, children.len()) <= limit, MaxSizeExceeded(limit, size(n_joins, children.len())))?;
+            part.push(pk);
+        }
+        parts.push(part);
+        Ok(parts)
+    }
+
+    pub fn unwrap_tree_keyload<'a, 'b>(
+        &'b self,
+        preparsed: PreparsedMessage<'a, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, tree_keyload::ContentUnwrap<'b, F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        if let Some(ref author_sig_pk) = self.author_sig_pk {
+            let content = tree_keyload::ContentUnwrap::new(
+                author_sig_pk,
+                &self.sig_kp.public,
+                &self.ke_kp.0,
+                self.key_tree.keys.clone(),
+            );
+            preparsed.unwrap(&*self.link_store.borrow(), content)
+        } else {
+            err!(AuthorSigKeyNotFound)
+        }
+    }
+
+    /// Try recovering group key from tree keyload using Subscriber's path keys.
+    pub fn handle_tree_keyload(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, bool>> {
+        let preparsed = msg.parse_header()?;
+
+        let unwrapped = self.unwrap_tree_keyload(preparsed)?;
+
+        if unwrapped.pcf.content.key.is_some() {
+            // Do not commit if key not found hence spongos state is invalid
+            let (link_to, keys, joins) = {
+                let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+                (content.link, content.keys, content.joins)
+            };
+            self.key_tree.keys = keys;
+            self.track_keyload(msg.link.rel().clone(), &link_to);
+
+            // Store any unknown publishers
+            if let Some(appinst) = &self.appinst {
+                for pk in joins {
+                    if self.pk_store.get(&pk).is_none() {
+                        // Store at state 2 since 0 and 1 are reserved states
+                        self.pk_store.insert(pk, Cursor::new_at(appinst.rel().clone(), 0, 2))?;
+                    }
+                }
+            }
             Ok(GenericMessage::new(msg.link, true))
         } else {
             Ok(GenericMessage::new(msg.link, false))
         }
     }
 
-    /// Prepare SignedPacket message.
-    pub fn prepare_signed_packet<'a>(
-        &'a mut self,
+    /// Prepare SignedPacket message.
+    ///
+    ///  # Arguments
+    ///  * `timestamp` - Publication time, signed in channels with certified publishers
+    pub fn prepare_signed_packet<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        public_payload: &'a Bytes,
+        masked_payload: &'a Bytes,
+        timestamp: u64,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, signed_packet::ContentWrap<'a, F, Link>>> {
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(SIGNED_PACKET)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let timestamp = if self.is_certified_publishers() { Some(timestamp) } else { None };
+                let content = signed_packet::ContentWrap {
+                    link: link_to,
+                    public_payload,
+                    masked_payload,
+                    timestamp,
+                    sig_kp: &self.sig_kp,
+                    _phantom: core::marker::PhantomData,
+                };
+                Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create a signed message with public and masked payload.
+    pub fn sign_packet(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        public_payload: &Bytes,
+        masked_payload: &Bytes,
+        timestamp: u64,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_signed_packet(link_to, public_payload, masked_payload, timestamp)?
+            .wrap()
+    }
+
+    pub fn unwrap_signed_packet<'a>(
+        &'a self,
+        preparsed: PreparsedMessage<'a, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, signed_packet::ContentUnwrap<F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let mut content = signed_packet::ContentUnwrap::default();
+        if self.is_certified_publishers() {
+            content = content.with_timestamp();
+        }
+        preparsed.unwrap(&*self.link_store.borrow(), content)
+    }
+
+    /// Verify new Author's MSS public key and update Author's MSS public key.
+    ///
+    ///  # Arguments
+    ///  * `msg` - Signed packet
+    ///  * `info` - Link store info of the packet
+    ///  * `now` - Current time, milliseconds since Unix epoch, publisher certificates are checked against it
+    pub fn handle_signed_packet(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+        now: u64,
+    ) -> Result<GenericMessage<Link, (ed25519::PublicKey, Bytes, Bytes)>> {
+        // TODO: pass author_pk to unwrap
+        let preparsed = msg.parse_header()?;
+
+        let unwrapped = self.unwrap_signed_packet(preparsed)?;
+        // Author accepts packets from approved subscribers only.
+        if self.author_sig_pk == Some(self.sig_kp.public) {
+            try_or!(self.pk_store.get(&unwrapped.pcf.content.sig_pk).is_some(), PublisherNotApproved)?;
+        }
+        try_or!(self.can_write(&unwrapped.pcf.content.sig_pk), WritePermissionDenied)?;
+        try_or!(
+            self.is_certified(&unwrapped.pcf.content.sig_pk, &unwrapped.pcf.content.link, now),
+            PublisherNotCertified
+        )?;
+        let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+        self.ratchet(&content.link, msg.link.rel())?;
+        self.rekey.track(msg.link.rel().clone(), &content.link);
+        let body = (content.sig_pk, content.public_payload, content.masked_payload);
+        Ok(GenericMessage::new(msg.link, body))
+    }
+
+    /// Prepare TaggedPacket message.
+    pub fn prepare_tagged_packet<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        public_payload: &'a Bytes,
+        masked_payload: &'a Bytes,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, tagged_packet::ContentWrap<'a, F, Link>>> {
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(TAGGED_PACKET)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let content = tagged_packet::ContentWrap {
+                    link: link_to,
+                    public_payload,
+                    masked_payload,
+                    _phantom: core::marker::PhantomData,
+                };
+                Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create a tagged (ie. MACed) message with public and masked payload.
+    /// Tagged messages must be linked to a secret spongos state, ie. keyload or a message linked to keyload.
+    pub fn tag_packet(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        public_payload: &Bytes,
+        masked_payload: &Bytes,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_tagged_packet(link_to, public_payload, masked_payload)?
+            .wrap()
+    }
+
+    pub fn unwrap_tagged_packet<'a>(
+        &self,
+        preparsed: PreparsedMessage<'a, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, tagged_packet::ContentUnwrap<F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let content = tagged_packet::ContentUnwrap::new();
+        preparsed.unwrap(&*self.link_store.borrow(), content)
+    }
+
+    /// Get public payload, decrypt masked payload and verify MAC.
+    pub fn handle_tagged_packet(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, (Bytes, Bytes)>> {
+        let preparsed = msg.parse_header()?;
+        let seq_num = preparsed.header.get_seq_num() as u32;
+
+        let unwrapped = self.unwrap_tagged_packet(preparsed)?;
+        // Tagged packet carries no publisher key, the publisher is recovered from the message address.
+        if !self.roles.is_empty() {
+            let link_to = &unwrapped.pcf.content.link;
+            let is_writer = |pk: &ed25519::PublicKey| {
+                self.link_gen.link_from(pk, Cursor::new_at(link_to, 0, seq_num)).rel() == msg.link.rel()
+            };
+            let by_writer = self.author_sig_pk.as_ref().is_some_and(&is_writer)
+                || self.roles.iter().any(|(pk, role)| role.can_write() && is_writer(&pk.0));
+            try_or!(by_writer, WritePermissionDenied)?;
+        }
+        let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+        self.ratchet(&content.link, msg.link.rel())?;
+        self.rekey.track(msg.link.rel().clone(), &content.link);
+        let body = (content.public_payload, content.masked_payload);
+        Ok(GenericMessage::new(msg.link, body))
+    }
+
+    /// Prepare DirectMessage message readable by Author only.
+    pub fn prepare_direct_message<'a>(
+        &'a mut self,
+        link_to: &'a <Link as HasLink>::Rel,
+        masked_payload: &'a Bytes,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, direct_message::ContentWrap<'a, F, Link>>> {
+        let author_ke_pk = match &self.author_sig_pk {
+            Some(author_sig_pk) => match self.pk_store.get_ke_pk(author_sig_pk) {
+                Some(ke_pk) => ke_pk,
+                None => return err!(AuthorExchangeKeyNotFound),
+            },
+            None => return err!(AuthorSigKeyNotFound),
+        };
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(DIRECT_MESSAGE)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let content = direct_message::ContentWrap {
+                    link: link_to,
+                    key: NBytes::from(prng::random_key()),
+                    masked_payload,
+                    sig_kp: &self.sig_kp,
+                    author_ke_pk,
+                    _phantom: core::marker::PhantomData,
+                };
+                Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create a signed message with payload encrypted for Author.
+    pub fn send_direct_message(
+        &mut self,
+        link_to: &<Link as HasLink>::Rel,
+        masked_payload: &Bytes,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_direct_message(link_to, masked_payload)?.wrap()
+    }
+
+    pub fn unwrap_direct_message<'a>(
+        &'a self,
+        preparsed: PreparsedMessage<'a, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, direct_message::ContentUnwrap<'a, F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let content = direct_message::ContentUnwrap::new(&self.ke_kp.0)?;
+        preparsed.unwrap(&*self.link_store.borrow(), content)
+    }
+
+    /// Decrypt direct message and verify sender's signature. Only Author can read direct messages,
+    /// other users get `None` and skip the message.
+    pub fn handle_direct_message(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, Option<(ed25519::PublicKey, Bytes)>>> {
+        if self.author_sig_pk != Some(self.sig_kp.public) {
+            return Ok(GenericMessage::new(msg.link, None));
+        }
+        let preparsed = msg.parse_header()?;
+        let content = self
+            .unwrap_direct_message(preparsed)?
+            .commit(self.link_store.borrow_mut(), info)?;
+        let body = (content.sig_pk, content.masked_payload);
+        Ok(GenericMessage::new(msg.link, Some(body)))
+    }
+
+    /// Prepare Topic message declaring a new topic branch.
+    pub fn prepare_topic<'a>(
+        &'a self,
+        link_to: &'a <Link as HasLink>::Rel,
+        name: &'a Bytes,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, topic::ContentWrap<'a, F, Link>>> {
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_content_type(TOPIC)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no);
+                let content = topic::ContentWrap {
+                    link: link_to,
+                    name,
+                    sig_kp: &self.sig_kp,
+                    _phantom: core::marker::PhantomData,
+                };
+                Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
+            },
+            None => err!(SeqNumRetrievalFailure)
+        }
+    }
+
+    /// Create Topic message, the message is the root of the topic branch [Author]. Topics
+    /// require a multi-branching channel, single-branch messages are fetched without their
+    /// sequence messages and can't be skipped by topic.
+    pub fn share_topic(&mut self, link_to: &<Link as HasLink>::Rel, name: &Bytes) -> Result<WrappedMessage<F, Link>> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), TopicCreatorNotAuthorized)?;
+        try_or!(self.is_multi_branching(), TopicsNotMultiBranching)?;
+        try_or!(self.topic(name).is_none(), TopicAlreadyExists(topic_name(name)))?;
+        let wrapped = self.prepare_topic(link_to, name)?.wrap()?;
+        self.add_topic(name.clone(), wrapped.message.link.rel().clone());
+        Ok(wrapped)
+    }
+
+    pub fn unwrap_topic<'a>(
+        &'a self,
+        preparsed: PreparsedMessage<'a, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, topic::ContentUnwrap<F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let content = topic::ContentUnwrap::default();
+        preparsed.unwrap(&*self.link_store.borrow(), content)
+    }
+
+    /// Verify Author's signature and remember the topic.
+    pub fn handle_topic(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+    ) -> Result<GenericMessage<Link, Bytes>> {
+        try_or!(self.is_multi_branching(), TopicsNotMultiBranching)?;
+        let preparsed = msg.parse_header()?;
+        let unwrapped = self.unwrap_topic(preparsed)?;
+        try_or!(
+            self.author_sig_pk.as_ref() == Some(&unwrapped.pcf.content.sig_pk),
+            TopicCreatorNotAuthorized
+        )?;
+        let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+        if self.topic(&content.name).is_none() {
+            self.add_topic(content.name.clone(), msg.link.rel().clone());
+        }
+        Ok(GenericMessage::new(msg.link, content.name))
+    }
+
+    fn add_topic(&mut self, name: Bytes, root: <Link as HasLink>::Rel) {
+        self.topic_links.insert(root.clone(), root.clone());
+        self.topics.push((name, root));
+    }
+
+    /// Link to the root message of the topic.
+    pub fn topic(&self, name: &Bytes) -> Option<&<Link as HasLink>::Rel> {
+        self.topics.iter().find(|(n, _)| n == name).map(|(_, root)| root)
+    }
+
+    pub fn topics(&self) -> &[(Bytes, <Link as HasLink>::Rel)] {
+        &self.topics
+    }
+
+    /// Follow only the given topics, messages of other topics are skipped. Messages
+    /// outside of topic branches are always followed. Fails in single-branch channels.
+    pub fn follow_topics(&mut self, names: Vec<Bytes>) -> Result<()> {
+        try_or!(self.appinst.is_none() || self.is_multi_branching(), TopicsNotMultiBranching)?;
+        self.followed_topics = Some(names);
+        Ok(())
+    }
+
+    pub fn follow_all_topics(&mut self) {
+        self.followed_topics = None;
+    }
+
+    /// Message attached to `link_to` belongs to the same topic as `link_to`.
+    pub fn track_topic(&mut self, link: <Link as HasLink>::Rel, link_to: &<Link as HasLink>::Rel) {
+        if let Some(root) = self.topic_links.get(link_to).cloned() {
+            self.topic_links.insert(link, root);
+        }
+    }
+
+    /// Keyload `link` attached to `link_to` is the latest one of its branch.
+    pub fn track_keyload(&mut self, link: <Link as HasLink>::Rel, link_to: &<Link as HasLink>::Rel) {
+        self.latest_keyloads.insert(link_to.clone(), link);
+    }
+
+    /// Links needed to continue in the channel: the announcement, topic roots, branch roots
+    /// with the latest keyload of each branch (the latest packet in ratchet channels) and
+    /// the messages referenced by sequencing states.
+    fn essential_links(&self) -> Vec<<Link as HasLink>::Rel> {
+        let mut kept = Vec::new();
+        if let Some(ref appinst) = self.appinst {
+            kept.push(appinst.rel().clone());
+        }
+        kept.extend(self.topics.iter().map(|(_, root)| root.clone()));
+        for (root, keyload) in self.latest_keyloads.iter() {
+            kept.push(root.clone());
+            kept.push(keyload.clone());
+        }
+        kept.extend(self.pk_store.iter().into_iter().map(|(_, cursor)| cursor.link.clone()));
+        kept
+    }
+
+    /// Pin the links needed to continue in the channel, so that link stores with limits
+    /// don't evict them. Pinned links are replaced with the current ones on each call.
+    pub fn pin_essential_links(&mut self) {
+        let links = self.essential_links();
+        self.link_store.borrow_mut().pin(links);
+    }
+
+    /// Erase link store entries which are not needed to continue in the channel, see
+    /// `essential_links`. Messages joined to erased entries can't be unwrapped anymore.
+    /// Return the number of erased entries.
+    pub fn prune_link_store(&mut self) -> Result<usize> {
+        let kept = self.essential_links();
+
+        let erased: Vec<<Link as HasLink>::Rel> = self
+            .link_store
+            .borrow()
+            .iter()
+            .into_iter()
+            .filter(|(link, _)| !kept.contains(link))
+            .map(|(link, _)| link.clone())
+            .collect();
+
+        let mut link_store = self.link_store.borrow_mut();
+        let result = erased.iter().try_for_each(|link| link_store.try_erase(link));
+        drop(link_store);
+        // Forget topics of the erased entries even if erasing the rest failed.
+        let link_store = self.link_store.borrow();
+        self.topic_links.retain(|link, _| link_store.lookup(link).is_ok());
+        self.rekey.retain(|link| link_store.lookup(link).is_ok());
+        result?;
+        Ok(erased.len())
+    }
+
+    /// Whether messages attached to `link_to` belong to a followed topic or to no topic at all.
+    pub fn is_topic_followed(&self, link_to: &<Link as HasLink>::Rel) -> bool {
+        match (&self.followed_topics, self.topic_links.get(link_to)) {
+            (Some(names), Some(root)) => self
+                .topics
+                .iter()
+                .any(|(name, r)| r == root && names.contains(name)),
+            _ => true,
+        }
+    }
+
+    /// Assign role to a subscriber, the roles are distributed with the next role assignment [Author].
+    pub fn set_role(&mut self, pk: &ed25519::PublicKey, role: Role) -> Result<()> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), RoleAssignerNotAuthorized)?;
+        try_or!(self.pk_store.get(pk).is_some(), SubscriberNotFound)?;
+        self.roles.insert((*pk).into(), role);
+        Ok(())
+    }
+
+    pub fn role(&self, pk: &ed25519::PublicKey) -> Option<Role> {
+        self.roles.get(pk.into()).copied()
+    }
+
+    /// Author can always write, other keys need a writing role once roles are assigned.
+    pub fn can_write(&self, pk: &ed25519::PublicKey) -> bool {
+        self.roles.is_empty()
+            || self.author_sig_pk.as_ref() == Some(pk)
+            || self.role(pk).is_some_and(|role| role.can_write())
+    }
+
+    /// Prepare RoleAssignment message carrying all the assigned roles.
+    pub fn prepare_role_assignment<'a>(
+        &'a self,
         link_to: &'a <Link as HasLink>::Rel,
-        public_payload: &'a Bytes,
-        masked_payload: &'a Bytes,
-    ) -> Result<PreparedMessage<'a, F, Link, LS, signed_packet::ContentWrap<'a, F, Link>>> {
+    ) -> Result<PreparedMessage<'a, F, Link, LS, role_assignment::ContentWrap<'a, F, Link>>> {
         match self.get_seq_no() {
             Some(seq_no) => {
                 let msg_link = self
                     .link_gen
                     .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
                 let header = HDF::new(msg_link)
-                    .with_content_type(SIGNED_PACKET)?
+                    .with_content_type(ROLE_ASSIGNMENT)?
                     .with_payload_length(1)?
                     .with_seq_num(seq_no);
-                let content = signed_packet::ContentWrap {
+                let content = role_assignment::ContentWrap {
                     link: link_to,
-                    public_payload: public_payload,
-                    masked_payload: masked_payload,
+                    roles: self.roles.iter().map(|(pk, role)| (&pk.0, *role)).collect(),
                     sig_kp: &self.sig_kp,
                     _phantom: core::marker::PhantomData,
                 };
@@ -617,62 +1909,61 @@ where
         }
     }
 
-    /// Create a signed message with public and masked payload.
-    pub fn sign_packet(
-        &mut self,
-        link_to: &<Link as HasLink>::Rel,
-        public_payload: &Bytes,
-        masked_payload: &Bytes,
-    ) -> Result<WrappedMessage<F, Link>> {
-        self.prepare_signed_packet(link_to, public_payload, masked_payload)?
-            .wrap()
+    /// Create RoleAssignment message.
+    pub fn share_role_assignment(&self, link_to: &<Link as HasLink>::Rel) -> Result<WrappedMessage<F, Link>> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), RoleAssignerNotAuthorized)?;
+        self.prepare_role_assignment(link_to)?.wrap()
     }
 
-    pub fn unwrap_signed_packet<'a>(
+    pub fn unwrap_role_assignment<'a>(
         &'a self,
         preparsed: PreparsedMessage<'a, F, Link>,
-    ) -> Result<UnwrappedMessage<F, Link, signed_packet::ContentUnwrap<F, Link>>> {
+    ) -> Result<UnwrappedMessage<F, Link, role_assignment::ContentUnwrap<F, Link>>> {
         self.ensure_appinst(&preparsed)?;
-        let content = signed_packet::ContentUnwrap::default();
+        let content = role_assignment::ContentUnwrap::default();
         preparsed.unwrap(&*self.link_store.borrow(), content)
     }
 
-    /// Verify new Author's MSS public key and update Author's MSS public key.
-    pub fn handle_signed_packet<'a>(
-        &'a mut self,
+    /// Verify Author's signature and replace the known roles.
+    pub fn handle_role_assignment(
+        &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
-    ) -> Result<GenericMessage<Link, (ed25519::PublicKey, Bytes, Bytes)>> {
-        // TODO: pass author_pk to unwrap
+    ) -> Result<GenericMessage<Link, ()>> {
         let preparsed = msg.parse_header()?;
-
-        let content = self
-            .unwrap_signed_packet(preparsed)?
-            .commit(self.link_store.borrow_mut(), info)?;
-        let body = (content.sig_pk, content.public_payload, content.masked_payload);
-        Ok(GenericMessage::new(msg.link, body))
+        let unwrapped = self.unwrap_role_assignment(preparsed)?;
+        try_or!(
+            self.author_sig_pk.as_ref() == Some(&unwrapped.pcf.content.sig_pk),
+            RoleAssignerNotAuthorized
+        )?;
+        let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+        self.roles = content.roles.into_iter().map(|(pk, role)| (pk.into(), role)).collect();
+        Ok(GenericMessage::new(msg.link, ()))
     }
 
-    /// Prepare TaggedPacket message.
-    pub fn prepare_tagged_packet<'a>(
-        &'a mut self,
+    /// Certify a publisher, replacing any certificate previously issued for the same key [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the certificate will be linked to
+    ///  * `cert` - Certificate to be issued
+    pub fn prepare_certificate<'a>(
+        &'a self,
         link_to: &'a <Link as HasLink>::Rel,
-        public_payload: &'a Bytes,
-        masked_payload: &'a Bytes,
-    ) -> Result<PreparedMessage<'a, F, Link, LS, tagged_packet::ContentWrap<'a, F, Link>>> {
+        cert: &'a Certificate<<Link as HasLink>::Rel>,
+    ) -> Result<PreparedMessage<'a, F, Link, LS, certificate::ContentWrap<'a, F, Link>>> {
         match self.get_seq_no() {
             Some(seq_no) => {
                 let msg_link = self
                     .link_gen
                     .link_from(&self.sig_kp.public, Cursor::new_at(link_to, 0, seq_no));
                 let header = HDF::new(msg_link)
-                    .with_content_type(TAGGED_PACKET)?
+                    .with_content_type(CERTIFICATE)?
                     .with_payload_length(1)?
                     .with_seq_num(seq_no);
-                let content = tagged_packet::ContentWrap {
+                let content = certificate::ContentWrap {
                     link: link_to,
-                    public_payload: public_payload,
-                    masked_payload: masked_payload,
+                    cert,
+                    sig_kp: &self.sig_kp,
                     _phantom: core::marker::PhantomData,
                 };
                 Ok(PreparedMessage::new(self.link_store.borrow(), header, content))
@@ -681,40 +1972,76 @@ where
         }
     }
 
-    /// Create a tagged (ie. MACed) message with public and masked payload.
-    /// Tagged messages must be linked to a secret spongos state, ie. keyload or a message linked to keyload.
-    pub fn tag_packet(
+    /// Create Certificate message and remember the certificate.
+    pub fn share_certificate(
         &mut self,
         link_to: &<Link as HasLink>::Rel,
-        public_payload: &Bytes,
-        masked_payload: &Bytes,
+        cert: Certificate<<Link as HasLink>::Rel>,
     ) -> Result<WrappedMessage<F, Link>> {
-        self.prepare_tagged_packet(link_to, public_payload, masked_payload)?
-            .wrap()
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), CertificateIssuerNotAuthorized)?;
+        let wrapped = self.prepare_certificate(link_to, &cert)?.wrap()?;
+        self.certificates.insert(cert.subject.into(), cert);
+        Ok(wrapped)
     }
 
-    pub fn unwrap_tagged_packet<'a>(
-        &self,
+    pub fn unwrap_certificate<'a>(
+        &'a self,
         preparsed: PreparsedMessage<'a, F, Link>,
-    ) -> Result<UnwrappedMessage<F, Link, tagged_packet::ContentUnwrap<F, Link>>> {
+    ) -> Result<UnwrappedMessage<F, Link, certificate::ContentUnwrap<F, Link>>> {
         self.ensure_appinst(&preparsed)?;
-        let content = tagged_packet::ContentUnwrap::new();
+        let content = certificate::ContentUnwrap::default();
         preparsed.unwrap(&*self.link_store.borrow(), content)
     }
 
-    /// Get public payload, decrypt masked payload and verify MAC.
-    pub fn handle_tagged_packet<'a>(
+    /// Verify Author's signature and remember the certificate.
+    pub fn handle_certificate(
         &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
-    ) -> Result<GenericMessage<Link, (Bytes, Bytes)>> {
+    ) -> Result<GenericMessage<Link, Certificate<<Link as HasLink>::Rel>>> {
         let preparsed = msg.parse_header()?;
+        let unwrapped = self.unwrap_certificate(preparsed)?;
+        try_or!(
+            self.author_sig_pk.as_ref() == Some(&unwrapped.pcf.content.sig_pk),
+            CertificateIssuerNotAuthorized
+        )?;
+        let content = unwrapped.commit(self.link_store.borrow_mut(), info)?;
+        self.certificates.insert(content.cert.subject.into(), content.cert.clone());
+        Ok(GenericMessage::new(msg.link, content.cert))
+    }
 
-        let content = self
-            .unwrap_tagged_packet(preparsed)?
-            .commit(self.link_store.borrow_mut(), info)?;
-        let body = (content.public_payload, content.masked_payload);
-        Ok(GenericMessage::new(msg.link, body))
+    pub fn certificate(&self, pk: &ed25519::PublicKey) -> Option<&Certificate<<Link as HasLink>::Rel>> {
+        self.certificates.get(pk.into())
+    }
+
+    /// Author can always publish, other keys need a certificate covering the packet if the channel
+    /// is announced with `FLAG_CERTIFIED_PUBLISHERS_MASK`. Validity is checked at `now`, the time
+    /// the packet is received, not at the publication time signed in the packet: that one is
+    /// asserted by the publisher. Packets of an expired or revoked publisher are rejected even
+    /// if they were published while the certificate was valid.
+    pub fn is_certified(&self, pk: &ed25519::PublicKey, link_to: &<Link as HasLink>::Rel, now: u64) -> bool {
+        !self.is_certified_publishers()
+            || self.author_sig_pk.as_ref() == Some(pk)
+            || self.certificate(pk).is_some_and(|cert| cert.allows(link_to, now))
+    }
+
+    /// Erase spongos state of the message a packet has been linked to, if the channel is announced
+    /// with `FLAG_RATCHET_MASK`. State of the packet is derived from the erased one, so
+    /// messages preceding it can't be unwrapped again, and the next packet must be linked to it.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Link of the message the packet has been linked to
+    ///  * `link` - Link of the packet, it replaces `link_to` as the latest state of the branch
+    fn ratchet(&mut self, link_to: &<Link as HasLink>::Rel, link: &<Link as HasLink>::Rel) -> Result<()> {
+        if self.is_ratchet() {
+            self.link_store.borrow_mut().try_erase(link_to)?;
+            for latest in self.latest_keyloads.values_mut() {
+                if latest == link_to {
+                    *latest = link.clone();
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn prepare_sequence<'a>(
@@ -788,7 +2115,7 @@ where
                 cursor.link = wrapped.link.rel().clone();
                 cursor.next_seq();
                 wrapped.commit(self.link_store.borrow_mut(), info)?;
-                self.pk_store.insert(self.sig_kp.public.clone(), cursor)?;
+                self.pk_store.insert(self.sig_kp.public, cursor)?;
                 Ok(Some(link))
             }
             None => {
@@ -848,7 +2175,7 @@ where
     }
 
     // Fetch unwrapped sequence message to fetch referenced message
-    pub fn handle_sequence<'a>(
+    pub fn handle_sequence(
         &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
@@ -864,6 +2191,31 @@ where
         (self.flags & FLAG_BRANCHING_MASK) != 0
     }
 
+    /// Keyload forks carry no recipient identifiers and are found by trial decryption.
+    pub fn is_anonymous_keyload(&self) -> bool {
+        (self.flags & FLAG_ANONYMOUS_KEYLOAD_MASK) != 0
+    }
+
+    /// Spongos state of a message is erased once a packet has been linked to it.
+    pub fn is_ratchet(&self) -> bool {
+        (self.flags & FLAG_RATCHET_MASK) != 0
+    }
+
+    /// Keyloads are sent by Author and administrators announced with the channel or in admin sets.
+    pub fn has_admins(&self) -> bool {
+        (self.flags & FLAG_ADMINS_MASK) != 0
+    }
+
+    /// Signed packets are only accepted from Author and publishers certified by Author.
+    pub fn is_certified_publishers(&self) -> bool {
+        (self.flags & FLAG_CERTIFIED_PUBLISHERS_MASK) != 0
+    }
+
+    /// Channel address and announcement are hidden with a pre-shared key.
+    pub fn is_hidden(&self) -> bool {
+        self.announce_pskid.is_some()
+    }
+
     // TODO: own seq_no should be stored outside of pk_store to avoid lookup and Option
     pub fn get_seq_no(&self) -> Option<u32> {
         self.pk_store.get(&self.sig_kp.public).map(|cursor| cursor.seq_no)
@@ -896,13 +2248,13 @@ where
             },
         ) = pk_info;
         if branching {
-            let msg_id = link_gen.link_from(pk, Cursor::new_at(&*seq_link, 0, 1));
-            ids.push((pk.clone(), Cursor::new_at(msg_id, 0, 1)));
+            let msg_id = link_gen.link_from(pk, Cursor::new_at(seq_link, 0, 1));
+            ids.push((*pk, Cursor::new_at(msg_id, 0, 1)));
         } else {
-            let msg_id = link_gen.link_from(pk, Cursor::new_at(&*seq_link, 0, *seq_no));
-            let msg_id1 = link_gen.link_from(pk, Cursor::new_at(&*seq_link, 0, *seq_no - 1));
-            ids.push((pk.clone(), Cursor::new_at(msg_id, 0, *seq_no)));
-            ids.push((pk.clone(), Cursor::new_at(msg_id1, 0, *seq_no - 1)));
+            let msg_id = link_gen.link_from(pk, Cursor::new_at(seq_link, 0, *seq_no));
+            let msg_id1 = link_gen.link_from(pk, Cursor::new_at(seq_link, 0, *seq_no - 1));
+            ids.push((*pk, Cursor::new_at(msg_id, 0, *seq_no)));
+            ids.push((*pk, Cursor::new_at(msg_id1, 0, *seq_no - 1)));
         }
     }
 
@@ -927,9 +2279,36 @@ where
         Ok(())
     }
 
+    /// Whether sequence message numbered `seq_num` of publisher `pk` precedes its stored state.
+    pub fn is_sequence_processed(&self, pk: &ed25519::PublicKey, seq_num: u64) -> bool {
+        self.pk_store.get(pk).is_some_and(|cursor| seq_num < cursor.get_seq_num())
+    }
+
+    /// Store sequence message `link` numbered `seq_num` as the state of publisher `pk`. The state is
+    /// left as it is if the message precedes it, so processing a sequence message again doesn't move
+    /// the state back.
+    pub fn store_sequence_state(
+        &mut self,
+        pk: ed25519::PublicKey,
+        link: <Link as HasLink>::Rel,
+        seq_num: u64,
+    ) -> Result<()> {
+        if self.is_sequence_processed(&pk, seq_num) {
+            return Ok(());
+        }
+        if let Some(cursor) = self.pk_store.get(&pk) {
+            let mut cursor = cursor.clone();
+            cursor.link = link;
+            cursor.seq_no = seq_num as u32;
+            cursor.next_seq();
+            self.pk_store.insert(pk, cursor)?;
+        }
+        Ok(())
+    }
+
     pub fn store_state_for_all(&mut self, link: <Link as HasLink>::Rel, seq_no: u32) -> Result<()>{
         self.pk_store
-            .insert(self.sig_kp.public.clone(), Cursor::new_at(link.clone(), 0, seq_no + 1))?;
+            .insert(self.sig_kp.public, Cursor::new_at(link.clone(), 0, seq_no + 1))?;
         for (_pk, cursor) in self.pk_store.iter_mut() {
             cursor.link = link.clone();
             cursor.seq_no = seq_no + 1;
@@ -943,7 +2322,7 @@ where
     F: PRP,
     Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
     <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
-    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
     LG: LinkGenerator<Link>,
     LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
     <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: AbsorbFallback<F>,
@@ -1004,9 +2383,64 @@ where
                     .absorb(Uint32(cursor.seq_no))?
                 ;
                 Ok(ctx)
+            })?;
+        self.key_tree.sizeof(ctx)?
+            .absorb(Size(self.pending_subscribers.len()))?
+            .repeated(self.pending_subscribers.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Size(self.roles.len()))?
+            .repeated(self.roles.iter(), |ctx, (pk, role)| ctx.absorb(&pk.0)?.absorb(Uint8((*role).into())))?
+            .absorb(Size(self.certificates.len()))?
+            .repeated(self.certificates.values(), |ctx, cert| {
+                ctx.absorb(&cert.subject)?.absorb(Uint8(cert.branch.is_some() as u8))?;
+                if let Some(ref branch) = cert.branch {
+                    ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(branch))?;
+                }
+                ctx.absorb(Uint64(cert.not_before))?.absorb(Uint64(cert.not_after))
             })?
-
-            .commit()?
+            .absorb(Size(self.admins.len()))?
+            .repeated(self.admins.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Uint8(self.announce_pskid.is_some() as u8))?;
+        if let Some(ref pskid) = self.announce_pskid {
+            ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?;
+        }
+        ctx.absorb(Size(self.topics.len()))?
+            .repeated(self.topics.iter(), |ctx, (name, root)| {
+                ctx.absorb(name)?.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(root))
+            })?
+            .absorb(Uint8(self.followed_topics.is_some() as u8))?;
+        if let Some(ref names) = self.followed_topics {
+            ctx.absorb(Size(names.len()))?
+                .repeated(names.iter(), |ctx, name| ctx.absorb(name))?;
+        }
+        ctx.absorb(Size(self.topic_links.len()))?
+            .repeated(self.topic_links.iter(), |ctx, (link, root)| {
+                ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(link))?
+                    .absorb(<&Fallback<<Link as HasLink>::Rel>>::from(root))
+            })?
+            .absorb(Size(self.auditors.len()))?
+            .repeated(self.auditors.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Size(self.auditor_psks.len()))?
+            .repeated(self.auditor_psks.iter(), |ctx, pskid| {
+                ctx.absorb(<&NBytes<psk::PskIdSize>>::from(pskid))
+            })?
+            .absorb(Size(self.latest_keyloads.len()))?
+            .repeated(self.latest_keyloads.iter(), |ctx, (root, keyload)| {
+                ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(root))?
+                    .absorb(<&Fallback<<Link as HasLink>::Rel>>::from(keyload))
+            })?
+            .absorb(Uint8(self.subscription_policy.tag()))?;
+        match self.subscription_policy {
+            SubscriptionPolicy::Allowlist(ref pks) => {
+                ctx.absorb(Size(pks.len()))?
+                    .repeated(pks.iter(), |ctx, pk| ctx.absorb(pk))?;
+            }
+            SubscriptionPolicy::MaxSubscribers(n) => {
+                ctx.absorb(Uint64(n as u64))?;
+            }
+            _ => {}
+        }
+        self.rekey.sizeof(ctx)?;
+        ctx.commit()?
             .squeeze(Mac(32))?
             ;
         Ok(ctx)
@@ -1018,7 +2452,7 @@ where
     F: PRP,
     Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
     <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
-    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
     Store: LinkStore<F, <Link as HasLink>::Rel>,
     LG: LinkGenerator<Link>,
     LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
@@ -1084,9 +2518,65 @@ where
                     .absorb(Uint32(cursor.seq_no))?
                 ;
                 Ok(ctx)
+            })?;
+        // Fields below are not present in version 0 state.
+        self.key_tree.wrap(_store, ctx)?
+            .absorb(Size(self.pending_subscribers.len()))?
+            .repeated(self.pending_subscribers.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Size(self.roles.len()))?
+            .repeated(self.roles.iter(), |ctx, (pk, role)| ctx.absorb(&pk.0)?.absorb(Uint8((*role).into())))?
+            .absorb(Size(self.certificates.len()))?
+            .repeated(self.certificates.values(), |ctx, cert| {
+                ctx.absorb(&cert.subject)?.absorb(Uint8(cert.branch.is_some() as u8))?;
+                if let Some(ref branch) = cert.branch {
+                    ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(branch))?;
+                }
+                ctx.absorb(Uint64(cert.not_before))?.absorb(Uint64(cert.not_after))
             })?
-
-            .commit()?
+            .absorb(Size(self.admins.len()))?
+            .repeated(self.admins.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Uint8(self.announce_pskid.is_some() as u8))?;
+        if let Some(ref pskid) = self.announce_pskid {
+            ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?;
+        }
+        ctx.absorb(Size(self.topics.len()))?
+            .repeated(self.topics.iter(), |ctx, (name, root)| {
+                ctx.absorb(name)?.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(root))
+            })?
+            .absorb(Uint8(self.followed_topics.is_some() as u8))?;
+        if let Some(ref names) = self.followed_topics {
+            ctx.absorb(Size(names.len()))?
+                .repeated(names.iter(), |ctx, name| ctx.absorb(name))?;
+        }
+        ctx.absorb(Size(self.topic_links.len()))?
+            .repeated(self.topic_links.iter(), |ctx, (link, root)| {
+                ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(link))?
+                    .absorb(<&Fallback<<Link as HasLink>::Rel>>::from(root))
+            })?
+            .absorb(Size(self.auditors.len()))?
+            .repeated(self.auditors.iter(), |ctx, pk| ctx.absorb(pk))?
+            .absorb(Size(self.auditor_psks.len()))?
+            .repeated(self.auditor_psks.iter(), |ctx, pskid| {
+                ctx.absorb(<&NBytes<psk::PskIdSize>>::from(pskid))
+            })?
+            .absorb(Size(self.latest_keyloads.len()))?
+            .repeated(self.latest_keyloads.iter(), |ctx, (root, keyload)| {
+                ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(root))?
+                    .absorb(<&Fallback<<Link as HasLink>::Rel>>::from(keyload))
+            })?
+            .absorb(Uint8(self.subscription_policy.tag()))?;
+        match self.subscription_policy {
+            SubscriptionPolicy::Allowlist(ref pks) => {
+                ctx.absorb(Size(pks.len()))?
+                    .repeated(pks.iter(), |ctx, pk| ctx.absorb(pk))?;
+            }
+            SubscriptionPolicy::MaxSubscribers(n) => {
+                ctx.absorb(Uint64(n as u64))?;
+            }
+            _ => {}
+        }
+        self.rekey.wrap(_store, ctx)?;
+        ctx.commit()?
             .squeeze(Mac(32))?
             ;
         Ok(ctx)
@@ -1098,7 +2588,7 @@ where
     F: PRP,
     Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
     <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
-    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
     Store: LinkStore<F, <Link as HasLink>::Rel>,
     LG: LinkGenerator<Link>,
     LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
@@ -1108,6 +2598,29 @@ where
 {
     fn unwrap<'c, IS: io::IStream>(
         &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        self.unwrap_state(1, store, ctx)
+    }
+}
+
+impl<F, Link, LG, LS, PKS, PSKS> User<F, Link, LG, LS, PKS, PSKS>
+where
+    F: PRP,
+    Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    LG: LinkGenerator<Link>,
+    LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
+    <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: Default + AbsorbFallback<F>,
+    PKS: PublicKeyStore<Cursor<<Link as HasLink>::Rel>> + Default,
+    PSKS: PresharedKeyStore + Default,
+{
+    /// Unwrap user state exported in the given format version.
+    fn unwrap_state<'c, Store, IS: io::IStream>(
+        &mut self,
+        version: u8,
         _store: &Store,
         ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
@@ -1154,7 +2667,8 @@ where
         };
 
         let mut repeated_links = Size(0);
-        let mut link_store = LS::default();
+        // Links are inserted once the links to pin are known, so that a store with limits keeps them.
+        let mut links = Vec::new();
         ctx
             .absorb(&mut repeated_links)?
             .repeated(repeated_links, |ctx| {
@@ -1167,7 +2681,7 @@ where
                     .absorb(&mut info)?
                 ;
                 let a: GenericArray::<u8, F::CapacitySize> = s.into();
-                link_store.insert(&link.0, Inner::<F>::from(a), info.0)?;
+                links.push((link.0, Inner::<F>::from(a), info.0));
                 Ok(ctx)
             })?
         ;
@@ -1205,7 +2719,168 @@ where
                 ;
                 pk_store.insert(pk, Cursor::new_at(link.0, branch_no.0, seq_no.0))?;
                 Ok(ctx)
-            })?
+            })?;
+        let mut key_tree = KeyTree::default();
+        let mut pending_subscribers = Vec::new();
+        let mut roles = HashMap::new();
+        let mut certificates = HashMap::new();
+        let mut admins = Vec::new();
+        let mut announce_pskid = None;
+        let mut topics = Vec::new();
+        let mut followed_topics = None;
+        let mut topic_links = HashMap::new();
+        let mut auditors = Vec::new();
+        let mut auditor_psks = Vec::new();
+        let mut latest_keyloads = HashMap::new();
+        let mut subscription_policy = SubscriptionPolicy::default();
+        let mut subscription_callback_lost = false;
+        let mut rekey = RekeyState::default();
+        // Version 0 state ends with public key store, fields added since are left default.
+        if version != 0 {
+            let mut repeated_pending = Size(0);
+            let mut repeated_roles = Size(0);
+            let mut repeated_certificates = Size(0);
+            let mut repeated_admins = Size(0);
+            let mut has_announce_pskid = Uint8(0);
+            key_tree.unwrap(_store, ctx)?
+                .absorb(&mut repeated_pending)?
+                .repeated(repeated_pending, |ctx| {
+                    let mut pk = ed25519::PublicKey::default();
+                    ctx.absorb(&mut pk)?;
+                    pending_subscribers.push(pk);
+                    Ok(ctx)
+                })?
+                .absorb(&mut repeated_roles)?
+                .repeated(repeated_roles, |ctx| {
+                    let mut pk = ed25519::PublicKey::default();
+                    let mut role = Uint8(0);
+                    ctx.absorb(&mut pk)?.absorb(&mut role)?;
+                    match Role::try_from(role.0) {
+                        Ok(role) => roles.insert(pk.into(), role),
+                        Err(()) => return err!(BadRole(role.0)),
+                    };
+                    Ok(ctx)
+                })?
+                .absorb(&mut repeated_certificates)?
+                .repeated(repeated_certificates, |ctx| {
+                    let mut cert = Certificate::default();
+                    let mut has_branch = Uint8(0);
+                    let mut not_before = Uint64(0);
+                    let mut not_after = Uint64(0);
+                    ctx.absorb(&mut cert.subject)?.absorb(&mut has_branch)?;
+                    if has_branch.0 != 0 {
+                        let mut branch = Fallback(<Link as HasLink>::Rel::default());
+                        ctx.absorb(&mut branch)?;
+                        cert.branch = Some(branch.0);
+                    }
+                    ctx.absorb(&mut not_before)?.absorb(&mut not_after)?;
+                    cert.not_before = not_before.0;
+                    cert.not_after = not_after.0;
+                    certificates.insert(cert.subject.into(), cert);
+                    Ok(ctx)
+                })?
+                .absorb(&mut repeated_admins)?
+                .repeated(repeated_admins, |ctx| {
+                    let mut pk = ed25519::PublicKey::default();
+                    ctx.absorb(&mut pk)?;
+                    admins.push(pk);
+                    Ok(ctx)
+                })?
+                .absorb(&mut has_announce_pskid)?;
+            if has_announce_pskid.0 != 0 {
+                let mut pskid = NBytes::<psk::PskIdSize>::default();
+                ctx.mask(&mut pskid)?;
+                announce_pskid = Some(pskid.0);
+            }
+            let mut repeated_topics = Size(0);
+            let mut has_followed_topics = Uint8(0);
+            ctx.absorb(&mut repeated_topics)?
+                .repeated(repeated_topics, |ctx| {
+                    let mut name = Bytes::default();
+                    let mut root = Fallback(<Link as HasLink>::Rel::default());
+                    ctx.absorb(&mut name)?.absorb(&mut root)?;
+                    topics.push((name, root.0));
+                    Ok(ctx)
+                })?
+                .absorb(&mut has_followed_topics)?;
+            if has_followed_topics.0 != 0 {
+                let mut repeated_names = Size(0);
+                let mut names = Vec::new();
+                ctx.absorb(&mut repeated_names)?
+                    .repeated(repeated_names, |ctx| {
+                        let mut name = Bytes::default();
+                        ctx.absorb(&mut name)?;
+                        names.push(name);
+                        Ok(ctx)
+                    })?;
+                followed_topics = Some(names);
+            }
+            let mut repeated_topic_links = Size(0);
+            let mut repeated_auditors = Size(0);
+            let mut repeated_auditor_psks = Size(0);
+            let mut repeated_latest_keyloads = Size(0);
+            let mut policy_tag = Uint8(0);
+            ctx.absorb(&mut repeated_topic_links)?
+                .repeated(repeated_topic_links, |ctx| {
+                    let mut link = Fallback(<Link as HasLink>::Rel::default());
+                    let mut root = Fallback(<Link as HasLink>::Rel::default());
+                    ctx.absorb(&mut link)?.absorb(&mut root)?;
+                    topic_links.insert(link.0, root.0);
+                    Ok(ctx)
+                })?
+                .absorb(&mut repeated_auditors)?
+                .repeated(repeated_auditors, |ctx| {
+                    let mut pk = ed25519::PublicKey::default();
+                    ctx.absorb(&mut pk)?;
+                    auditors.push(pk);
+                    Ok(ctx)
+                })?
+                .absorb(&mut repeated_auditor_psks)?
+                .repeated(repeated_auditor_psks, |ctx| {
+                    let mut pskid = psk::PskId::default();
+                    ctx.absorb(<&mut NBytes<psk::PskIdSize>>::from(&mut pskid))?;
+                    auditor_psks.push(pskid);
+                    Ok(ctx)
+                })?
+                .absorb(&mut repeated_latest_keyloads)?
+                .repeated(repeated_latest_keyloads, |ctx| {
+                    let mut root = Fallback(<Link as HasLink>::Rel::default());
+                    let mut keyload = Fallback(<Link as HasLink>::Rel::default());
+                    ctx.absorb(&mut root)?.absorb(&mut keyload)?;
+                    latest_keyloads.insert(root.0, keyload.0);
+                    Ok(ctx)
+                })?
+                .absorb(&mut policy_tag)?;
+            subscription_policy = match policy_tag.0 {
+                0 => SubscriptionPolicy::AcceptAll,
+                1 => SubscriptionPolicy::Manual,
+                2 => {
+                    let mut repeated_allowed = Size(0);
+                    let mut pks = Vec::new();
+                    ctx.absorb(&mut repeated_allowed)?
+                        .repeated(repeated_allowed, |ctx| {
+                            let mut pk = ed25519::PublicKey::default();
+                            ctx.absorb(&mut pk)?;
+                            pks.push(pk);
+                            Ok(ctx)
+                        })?;
+                    SubscriptionPolicy::Allowlist(pks)
+                }
+                // Callback can't be exported, subscribers are kept pending until the policy is set again.
+                3 => {
+                    subscription_callback_lost = true;
+                    SubscriptionPolicy::Manual
+                }
+                4 => {
+                    let mut n = Uint64(0);
+                    ctx.absorb(&mut n)?;
+                    SubscriptionPolicy::MaxSubscribers(n.0 as usize)
+                }
+                tag => return err!(BadSubscriptionPolicy(tag)),
+            };
+            rekey.unwrap(_store, ctx)?;
+        }
+        ctx
             .commit()?
             .squeeze(Mac(32))?
         ;
@@ -1217,9 +2892,23 @@ where
             public: sig_pk,
         };
         self.ke_kp = x25519::keypair_from_ed25519(&self.sig_kp);
-        self.link_store = RefCell::new(link_store);
         self.psk_store = psk_store;
         self.pk_store = pk_store;
+        self.key_tree = key_tree;
+        self.pending_subscribers = pending_subscribers;
+        self.roles = roles;
+        self.certificates = certificates;
+        self.admins = admins;
+        self.announce_pskid = announce_pskid;
+        self.topics = topics;
+        self.followed_topics = followed_topics;
+        self.topic_links = topic_links;
+        self.auditors = auditors;
+        self.auditor_psks = auditor_psks;
+        self.latest_keyloads = latest_keyloads;
+        self.subscription_policy = subscription_policy;
+        self.subscription_callback_lost = subscription_callback_lost;
+        self.rekey = rekey;
         self.author_sig_pk = author_sig_pk;
         if let Some(ref seed) = appinst {
             self.link_gen.reset(seed.clone());
@@ -1228,6 +2917,13 @@ where
         self.flags = flags.0;
         self.message_encoding = message_encoding.0;
         self.uniform_payload_length = uniform_payload_length.0 as usize;
+
+        let mut link_store = LS::default();
+        link_store.pin(self.essential_links());
+        for (link, inner, info) in links {
+            link_store.insert(&link, inner, info)?;
+        }
+        self.link_store = RefCell::new(link_store);
         Ok(ctx)
     }
 }
@@ -1237,20 +2933,130 @@ where
     F: PRP,
     Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
     <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
-    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
     LG: LinkGenerator<Link>,
     LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
     <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: AbsorbFallback<F>,
     PKS: PublicKeyStore<Cursor<<Link as HasLink>::Rel>>,
     PSKS: PresharedKeyStore,
 {
+    /// Export a proof disclosing a received signed packet to an outside party, the proof
+    /// can be verified without user state with `proof::verify_signed_packet_proof`.
+    ///
+    ///  # Arguments
+    ///  * `msg` - Signed packet, its base message must be known to the user
+    pub fn export_signed_packet_proof(&self, msg: BinaryMessage<F, Link>) -> Result<Vec<u8>> {
+        let content = {
+            let preparsed = msg.parse_header()?;
+            try_or!(
+                preparsed.content_type() == SIGNED_PACKET,
+                NotSignedPacket(preparsed.content_type())
+            )?;
+            self.unwrap_signed_packet(preparsed)?.pcf.content
+        };
+        let (spongos, _) = self.link_store.borrow().lookup(&content.link)?;
+        let proof = SignedPacketProof {
+            msg,
+            link_to: content.link,
+            spongos: spongos.to_inner()?,
+            sig_pk: content.sig_pk,
+            certified: content.timestamp.is_some(),
+        };
+        proof.to_bytes()
+    }
+
+    /// Export user state encrypted with a key derived from `pwd` with default Argon2id parameters.
     pub fn export(&self, flag: u8, pwd: &str) -> Result<Vec<u8>> {
-        const VERSION: u8 = 0;
+        self.export_with_kdf(flag, pwd, &KdfParams::default())
+    }
+
+    /// Prune the link store and export user state encrypted with a key derived from `pwd`,
+    /// the size of the export doesn't grow with channel history. See `prune_link_store`
+    /// for the kept link store entries.
+    ///
+    ///  # Arguments
+    ///  * `flag` - User type flag checked on import
+    ///  * `pwd` - Password
+    ///
+    /// Return the exported state and the number of erased link store entries.
+    pub fn export_compact(&mut self, flag: u8, pwd: &str) -> Result<(Vec<u8>, usize)> {
+        let erased = self.prune_link_store()?;
+        Ok((self.export(flag, pwd)?, erased))
+    }
+
+    /// Export user state encrypted with a key derived from `pwd` and a random salt with Argon2id.
+    /// KDF parameters, salt and a random nonce are stored in the header, so exports under
+    /// the same password never share a keystream.
+    ///
+    ///  # Arguments
+    ///  * `flag` - User type flag checked on import
+    ///  * `pwd` - Password
+    ///  * `kdf` - Argon2id parameters
+    pub fn export_with_kdf(&self, flag: u8, pwd: &str, kdf: &KdfParams) -> Result<Vec<u8>> {
+        const VERSION: u8 = 1;
+        let salt = NBytes::<U16>::from(prng::random_nonce());
+        let nonce = NBytes::<U16>::from(prng::random_nonce());
         let buf_size = {
             let mut ctx = sizeof::Context::<F>::new();
             ctx
                 .absorb(Uint8(VERSION))?
                 .absorb(Uint8(flag))?
+                .absorb(Uint8(KDF_ARGON2ID))?
+                .absorb(Uint32(kdf.m_cost))?
+                .absorb(Uint32(kdf.t_cost))?
+                .absorb(Uint32(kdf.p_cost))?
+                .absorb(&salt)?
+                .absorb(&nonce)?
+            ;
+            self.sizeof(&mut ctx)?;
+            ctx.get_size()
+        };
+
+        let mut buf = vec![0; buf_size];
+
+        {
+            let mut ctx = wrap::Context::new(&mut buf[..]);
+            let key = kdf.derive_key(pwd, salt.as_slice())?;
+            ctx
+                .absorb(Uint8(VERSION))?
+                .absorb(Uint8(flag))?
+                .absorb(Uint8(KDF_ARGON2ID))?
+                .absorb(Uint32(kdf.m_cost))?
+                .absorb(Uint32(kdf.t_cost))?
+                .absorb(Uint32(kdf.p_cost))?
+                .absorb(&salt)?
+                .absorb(&nonce)?
+                .absorb(External(&key))?
+            ;
+            let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+            self.wrap(&store, &mut ctx)?;
+            try_or!(
+                ctx.stream.is_empty(),
+                OutputStreamNotFullyConsumed(ctx.stream.len())
+            )?;
+        }
+
+        Ok(buf)
+    }
+
+    /// Export user state encrypted with a random key encapsulated to the recipient's X25519 public key,
+    /// it can be imported with the corresponding secret key only.
+    ///
+    ///  # Arguments
+    ///  * `flag` - User type flag checked on import
+    ///  * `ke_pk` - Recipient X25519 public key, eg. an organizational recovery key
+    pub fn export_to(&self, flag: u8, ke_pk: &x25519::PublicKey) -> Result<Vec<u8>> {
+        const VERSION: u8 = 1;
+        let nonce = NBytes::<U16>::from(prng::random_nonce());
+        let key = NBytes::<U32>::from(prng::random_key());
+        let buf_size = {
+            let mut ctx = sizeof::Context::<F>::new();
+            ctx
+                .absorb(Uint8(VERSION))?
+                .absorb(Uint8(flag))?
+                .absorb(Uint8(KDF_X25519))?
+                .absorb(&nonce)?
+                .x25519(ke_pk, &key)?
             ;
             self.sizeof(&mut ctx)?;
             ctx.get_size()
@@ -1260,11 +3066,12 @@ where
 
         {
             let mut ctx = wrap::Context::new(&mut buf[..]);
-            let prng = prng::from_seed::<F>("IOTA Streams Channels app", pwd);
-            let key = NBytes::<U32>(prng.gen_arr("user export key"));
             ctx
                 .absorb(Uint8(VERSION))?
                 .absorb(Uint8(flag))?
+                .absorb(Uint8(KDF_X25519))?
+                .absorb(&nonce)?
+                .x25519(ke_pk, &key)?
                 .absorb(External(&key))?
             ;
             let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
@@ -1277,6 +3084,49 @@ where
 
         Ok(buf)
     }
+
+    /// Export user state encrypted with the given key, eg. a key of checkpoint storage.
+    /// A random nonce is stored in the header, so the key can be reused for many exports.
+    ///
+    ///  # Arguments
+    ///  * `flag` - User type flag checked on import
+    ///  * `key` - Encryption key
+    pub fn export_with_key(&self, flag: u8, key: &NBytes<U32>) -> Result<Vec<u8>> {
+        const VERSION: u8 = 1;
+        let nonce = NBytes::<U16>::from(prng::random_nonce());
+        let buf_size = {
+            let mut ctx = sizeof::Context::<F>::new();
+            ctx
+                .absorb(Uint8(VERSION))?
+                .absorb(Uint8(flag))?
+                .absorb(Uint8(KDF_EXTERNAL_KEY))?
+                .absorb(&nonce)?
+            ;
+            self.sizeof(&mut ctx)?;
+            ctx.get_size()
+        };
+
+        let mut buf = vec![0; buf_size];
+
+        {
+            let mut ctx = wrap::Context::new(&mut buf[..]);
+            ctx
+                .absorb(Uint8(VERSION))?
+                .absorb(Uint8(flag))?
+                .absorb(Uint8(KDF_EXTERNAL_KEY))?
+                .absorb(&nonce)?
+                .absorb(External(key))?
+            ;
+            let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+            self.wrap(&store, &mut ctx)?;
+            try_or!(
+                ctx.stream.is_empty(),
+                OutputStreamNotFullyConsumed(ctx.stream.len())
+            )?;
+        }
+
+        Ok(buf)
+    }
 }
 
 impl<F, Link, LG, LS, PKS, PSKS> User<F, Link, LG, LS, PKS, PSKS>
@@ -1284,36 +3134,116 @@ where
     F: PRP,
     Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
     <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
-    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Rel: Eq + hash::Hash + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
     LG: LinkGenerator<Link>,
     LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
     <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: Default + AbsorbFallback<F>,
     PKS: PublicKeyStore<Cursor<<Link as HasLink>::Rel>> + Default,
     PSKS: PresharedKeyStore + Default,
 {
+    /// Import user state exported with password, either in version 0 or version 1 format.
     pub fn import(bytes: &[u8], flag: u8, pwd: &str) -> Result<Self> {
-        const VERSION: u8 = 0;
+        let mut ctx = unwrap::Context::new(bytes);
+        let version = Self::import_header(&mut ctx, flag)?;
+        let key = if version == 0 {
+            // Version 0 key is derived from the password only.
+            let prng = prng::from_seed::<F>("IOTA Streams Channels app", pwd);
+            NBytes::<U32>(prng.gen_arr("user export key"))
+        } else {
+            let mut kdf_id = Uint8(0);
+            let mut m_cost = Uint32(0);
+            let mut t_cost = Uint32(0);
+            let mut p_cost = Uint32(0);
+            let mut salt = NBytes::<U16>::default();
+            let mut nonce = NBytes::<U16>::default();
+            ctx
+                .absorb(&mut kdf_id)?
+                .guard(kdf_id.0 == KDF_ARGON2ID, ExportKdfNotSupported(kdf_id.0))?
+                .absorb(&mut m_cost)?
+                .absorb(&mut t_cost)?
+                .absorb(&mut p_cost)?
+                .absorb(&mut salt)?
+                .absorb(&mut nonce)?
+            ;
+            let kdf = KdfParams {
+                m_cost: m_cost.0,
+                t_cost: t_cost.0,
+                p_cost: p_cost.0,
+            };
+            kdf.check_limits()?;
+            kdf.derive_key(pwd, salt.as_slice())?
+        };
+        ctx.absorb(External(&key))?;
+        Self::import_state(version, ctx)
+    }
+
+    /// Import user state exported with `export_to`.
+    ///
+    ///  # Arguments
+    ///  * `bytes` - Exported state
+    ///  * `flag` - User type flag
+    ///  * `ke_sk` - X25519 secret key of the export recipient
+    pub fn import_with(bytes: &[u8], flag: u8, ke_sk: &x25519::StaticSecret) -> Result<Self> {
+        let mut ctx = unwrap::Context::new(bytes);
+        let version = Self::import_header(&mut ctx, flag)?;
+        let mut kdf_id = Uint8(0);
+        let mut nonce = NBytes::<U16>::default();
+        let mut key = NBytes::<U32>::default();
+        ctx
+            .guard(version == 1, UserVersionRecoveryFailure(1, version))?
+            .absorb(&mut kdf_id)?
+            .guard(kdf_id.0 == KDF_X25519, ExportKdfNotSupported(kdf_id.0))?
+            .absorb(&mut nonce)?
+            .x25519(ke_sk, &mut key)?
+            .absorb(External(&key))?
+        ;
+        Self::import_state(version, ctx)
+    }
 
+    /// Import user state exported with `export_with_key`.
+    ///
+    ///  # Arguments
+    ///  * `bytes` - Exported state
+    ///  * `flag` - User type flag
+    ///  * `key` - Encryption key
+    pub fn import_with_key(bytes: &[u8], flag: u8, key: &NBytes<U32>) -> Result<Self> {
         let mut ctx = unwrap::Context::new(bytes);
-        let prng = prng::from_seed::<F>("IOTA Streams Channels app", pwd);
-        let key = NBytes::<U32>(prng.gen_arr("user export key"));
+        let version = Self::import_header(&mut ctx, flag)?;
+        let mut kdf_id = Uint8(0);
+        let mut nonce = NBytes::<U16>::default();
+        ctx
+            .guard(version == 1, UserVersionRecoveryFailure(1, version))?
+            .absorb(&mut kdf_id)?
+            .guard(kdf_id.0 == KDF_EXTERNAL_KEY, ExportKdfNotSupported(kdf_id.0))?
+            .absorb(&mut nonce)?
+            .absorb(External(key))?
+        ;
+        Self::import_state(version, ctx)
+    }
+
+    /// Check version and user type flag of exported state, return the version.
+    fn import_header(ctx: &mut unwrap::Context<F, &[u8]>, flag: u8) -> Result<u8> {
+        const VERSION: u8 = 1;
+
         let mut version = Uint8(0);
         let mut flag2 = Uint8(0);
         ctx
             .absorb(&mut version)?
-            .guard(version.0 == VERSION,
+            .guard(version.0 <= VERSION,
                    UserVersionRecoveryFailure(VERSION, version.0)
             )?
             .absorb(&mut flag2)?
             .guard(flag2.0 == flag,
                    UserFlagRecoveryFailure(flag, flag2.0)
             )?
-            .absorb(External(&key))?
         ;
+        Ok(version.0)
+    }
 
+    fn import_state(version: u8, mut ctx: unwrap::Context<F, &[u8]>) -> Result<Self> {
         let mut user = User::default();
         let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
-        user.unwrap(&store, &mut ctx)?;
+        user.unwrap_state(version, &store, &mut ctx)?;
         try_or!(
             ctx.stream.is_empty(),
             InputStreamNotFullyConsumed(ctx.stream.len())
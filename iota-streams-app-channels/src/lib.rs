@@ -1,6 +1,8 @@
 //! Implementation of Streams Channel Application.
 
 #![no_std]
+// Messages are prepared and unwrapped with their generic content types spelled out.
+#![allow(clippy::type_complexity)]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -0,0 +1,149 @@
+//! `AdminSet` message content. Author publishes the whole set of channel administrators,
+//! it replaces the set announced in `Announce` or in the previous `AdminSet`.
+//!
+//! ```ddml
+//! message AdminSet {
+//!     join link msgid;
+//!     absorb u8 sig_pk[32];
+//!     absorb repeated {
+//!         absorb u8 admin_pk[32];
+//!     }
+//!     commit;
+//!     squeeze external u8 hash[64];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `sig_pk` -- public key of the sender, must be Author's.
+//!
+//! * `admin_pk` -- public key of an administrator.
+//!
+//! * `sig` -- Author's signature.
+//!
+//! Notes:
+//! 1) Administrators are public, so the message should be linked to the announcement
+//!     in order to be readable by every subscriber.
+
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::{
+    prelude::Vec,
+    sponge::prp::PRP,
+    Result,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+pub struct ContentWrap<'a, F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a,
+{
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) admins: &'a [ed25519::PublicKey],
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        let repeated_admins = Size(self.admins.len());
+        ctx.join(&store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .absorb(repeated_admins)?
+            .repeated(self.admins.iter(), |ctx, pk| ctx.absorb(pk))?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        let repeated_admins = Size(self.admins.len());
+        ctx.join(store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .absorb(repeated_admins)?
+            .repeated(self.admins.iter(), |ctx, pk| ctx.absorb(pk))?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) admins: Vec<ed25519::PublicKey>,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<F, Link> Default for ContentUnwrap<F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+{
+    fn default() -> Self {
+        Self {
+            link: <<Link as HasLink>::Rel as Default>::default(),
+            sig_pk: ed25519::PublicKey::default(),
+            admins: Vec::new(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut repeated_admins = Size(0);
+        ctx.join(store, &mut self.link)?
+            .absorb(&mut self.sig_pk)?
+            .absorb(&mut repeated_admins)?
+            .repeated(repeated_admins, |ctx| {
+                let mut pk = ed25519::PublicKey::default();
+                ctx.absorb(&mut pk)?;
+                self.admins.push(pk);
+                Ok(ctx)
+            })?
+            .ed25519(&self.sig_pk, HashSig)?;
+        Ok(ctx)
+    }
+}
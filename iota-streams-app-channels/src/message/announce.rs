@@ -6,6 +6,20 @@
 //! ```ddml
 //! message Announce {
 //!     absorb u8 ed25519pk[32];
+//!     absorb u8 flags;
+//!     if flags & FLAG_ADMINS_MASK {
+//!         absorb repeated {
+//!             absorb u8 admin_pk[32];
+//!         }
+//!     }
+//!     if flags & FLAG_AUDITORS_MASK {
+//!         absorb repeated {
+//!             absorb u8 auditor_pk[32];
+//!         }
+//!         absorb repeated {
+//!             absorb u8 auditor_pskid[16];
+//!         }
+//!     }
 //!     commit;
 //!     squeeze external u8 tag[32];
 //!     ed25519(tag) sig;
@@ -16,14 +30,64 @@
 //!
 //! * `ed25519pk` -- channel owner's Ed25519 public key.
 //!
+//! * `flags` -- channel flags, see `FLAG_BRANCHING_MASK` and others.
+//!
+//! * `admin_pk` -- Ed25519 public key of a channel administrator allowed to send keyloads
+//! besides the channel owner, present in channels with administrators only.
+//!
+//! * `auditor_pk` -- Ed25519 public key of an auditor, the session key of every keyload is shared with it.
+//!
+//! * `auditor_pskid` -- identifier of a pre-shared key included in every keyload for auditing.
+//!
+//! Channels without administrators and auditors are announced in the original layout.
+//!
 //! * `tag` -- hash-value to be signed.
 //!
 //! * `sig` -- signature of `tag` field produced with the Ed25519 private key corresponding to ed25519pk`.
 //!
+//! Hidden channels are announced with `HiddenAnnounce` message, its content is masked with
+//! a pre-shared key so that only the key holders learn channel owner's public key. Pre-shared
+//! key identifier is not published, recipients try the keys they know.
+//!
+//! ```ddml
+//! message HiddenAnnounce {
+//!     absorb external u8 psk[32];
+//!     commit;
+//!     mask u8 ed25519pk[32];
+//!     mask u8 flags;
+//!     if flags & FLAG_ADMINS_MASK {
+//!         mask repeated {
+//!             mask u8 admin_pk[32];
+//!         }
+//!     }
+//!     if flags & FLAG_AUDITORS_MASK {
+//!         mask repeated {
+//!             mask u8 auditor_pk[32];
+//!         }
+//!         mask repeated {
+//!             mask u8 auditor_pskid[16];
+//!         }
+//!     }
+//!     commit;
+//!     squeeze external u8 tag[32];
+//!     ed25519(tag) sig;
+//! }
+//! ```
+//!
 
-use iota_streams_core::Result;
+use iota_streams_core::{
+    prelude::Vec,
+    psk,
+    Result,
+};
 
-use iota_streams_app::message;
+use iota_streams_app::message::{
+    self,
+    hdf::{
+        FLAG_ADMINS_MASK,
+        FLAG_AUDITORS_MASK,
+    },
+};
 use iota_streams_core::sponge::prp::PRP;
 use iota_streams_core_edsig::{
     key_exchange::x25519,
@@ -38,24 +102,80 @@ use iota_streams_ddml::{
 pub struct ContentWrap<'a, F> {
     sig_kp: &'a ed25519::Keypair,
     flags: Uint8,
+    admins: &'a [ed25519::PublicKey],
+    auditors: &'a [ed25519::PublicKey],
+    auditor_psks: &'a [psk::PskId],
+    psk: Option<&'a psk::Psk>,
     _phantom: core::marker::PhantomData<F>,
 }
 
 impl<'a, F> ContentWrap<'a, F> {
-    pub fn new(sig_kp: &'a ed25519::Keypair, flags: u8) -> Self {
+    pub fn new(sig_kp: &'a ed25519::Keypair, flags: u8, admins: &'a [ed25519::PublicKey]) -> Self {
         Self {
             sig_kp,
             flags: Uint8(flags),
+            admins,
+            auditors: &[],
+            auditor_psks: &[],
+            psk: None,
             _phantom: core::marker::PhantomData,
         }
     }
+
+    /// Mask announcement content with pre-shared key.
+    pub fn with_psk(mut self, psk: &'a psk::Psk) -> Self {
+        self.psk = Some(psk);
+        self
+    }
+
+    /// Announce auditors included in every keyload.
+    pub fn with_auditors(mut self, auditors: &'a [ed25519::PublicKey], auditor_psks: &'a [psk::PskId]) -> Self {
+        self.auditors = auditors;
+        self.auditor_psks = auditor_psks;
+        self
+    }
+
+    fn has(&self, flag: u8) -> bool {
+        (self.flags.0 & flag) != 0
+    }
 }
 
 impl<'a, F: PRP> message::ContentSizeof<F> for ContentWrap<'a, F>
 {
     fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
-        ctx.absorb(&self.sig_kp.public)?;
-        ctx.absorb(&self.flags)?;
+        if let Some(psk) = self.psk {
+            ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                .commit()?
+                .mask(&self.sig_kp.public)?
+                .mask(&self.flags)?;
+            if self.has(FLAG_ADMINS_MASK) {
+                ctx.mask(&Size(self.admins.len()))?
+                    .repeated(self.admins.iter(), |ctx, pk| ctx.mask(pk))?;
+            }
+            if self.has(FLAG_AUDITORS_MASK) {
+                ctx.mask(&Size(self.auditors.len()))?
+                    .repeated(self.auditors.iter(), |ctx, pk| ctx.mask(pk))?
+                    .mask(&Size(self.auditor_psks.len()))?
+                    .repeated(self.auditor_psks.iter(), |ctx, pskid| {
+                        ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))
+                    })?;
+            }
+        } else {
+            ctx.absorb(&self.sig_kp.public)?;
+            ctx.absorb(&self.flags)?;
+            if self.has(FLAG_ADMINS_MASK) {
+                ctx.absorb(Size(self.admins.len()))?
+                    .repeated(self.admins.iter(), |ctx, pk| ctx.absorb(pk))?;
+            }
+            if self.has(FLAG_AUDITORS_MASK) {
+                ctx.absorb(Size(self.auditors.len()))?
+                    .repeated(self.auditors.iter(), |ctx, pk| ctx.absorb(pk))?
+                    .absorb(Size(self.auditor_psks.len()))?
+                    .repeated(self.auditor_psks.iter(), |ctx, pskid| {
+                        ctx.absorb(<&NBytes<psk::PskIdSize>>::from(pskid))
+                    })?;
+            }
+        }
         ctx.ed25519(self.sig_kp, HashSig)?;
         Ok(ctx)
     }
@@ -68,8 +188,39 @@ impl<'a, F: PRP, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F>
         _store: &Store,
         ctx: &'c mut wrap::Context<F, OS>,
     ) -> Result<&'c mut wrap::Context<F, OS>> {
-        ctx.absorb(&self.sig_kp.public)?;
-        ctx.absorb(&self.flags)?;
+        if let Some(psk) = self.psk {
+            ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                .commit()?
+                .mask(&self.sig_kp.public)?
+                .mask(&self.flags)?;
+            if self.has(FLAG_ADMINS_MASK) {
+                ctx.mask(&Size(self.admins.len()))?
+                    .repeated(self.admins.iter(), |ctx, pk| ctx.mask(pk))?;
+            }
+            if self.has(FLAG_AUDITORS_MASK) {
+                ctx.mask(&Size(self.auditors.len()))?
+                    .repeated(self.auditors.iter(), |ctx, pk| ctx.mask(pk))?
+                    .mask(&Size(self.auditor_psks.len()))?
+                    .repeated(self.auditor_psks.iter(), |ctx, pskid| {
+                        ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))
+                    })?;
+            }
+        } else {
+            ctx.absorb(&self.sig_kp.public)?;
+            ctx.absorb(&self.flags)?;
+            if self.has(FLAG_ADMINS_MASK) {
+                ctx.absorb(Size(self.admins.len()))?
+                    .repeated(self.admins.iter(), |ctx, pk| ctx.absorb(pk))?;
+            }
+            if self.has(FLAG_AUDITORS_MASK) {
+                ctx.absorb(Size(self.auditors.len()))?
+                    .repeated(self.auditors.iter(), |ctx, pk| ctx.absorb(pk))?
+                    .absorb(Size(self.auditor_psks.len()))?
+                    .repeated(self.auditor_psks.iter(), |ctx, pskid| {
+                        ctx.absorb(<&NBytes<psk::PskIdSize>>::from(pskid))
+                    })?;
+            }
+        }
         ctx.ed25519(self.sig_kp, HashSig)?;
         Ok(ctx)
     }
@@ -79,9 +230,21 @@ pub struct ContentUnwrap<F> {
     pub(crate) sig_pk: ed25519::PublicKey,
     pub(crate) ke_pk: x25519::PublicKey,
     pub(crate) flags: Uint8,
+    pub(crate) admins: Vec<ed25519::PublicKey>,
+    pub(crate) auditors: Vec<ed25519::PublicKey>,
+    pub(crate) auditor_psks: psk::PskIds,
+    pub(crate) psk: Option<(psk::PskId, psk::Psk)>,
     _phantom: core::marker::PhantomData<F>,
 }
 
+impl<F> ContentUnwrap<F> {
+    /// Unmask announcement content with pre-shared key.
+    pub fn with_psk(mut self, pskid: psk::PskId, psk: psk::Psk) -> Self {
+        self.psk = Some((pskid, psk));
+        self
+    }
+}
+
 impl<F> Default for ContentUnwrap<F> {
     fn default() -> Self {
         let sig_pk = ed25519::PublicKey::default();
@@ -92,6 +255,10 @@ impl<F> Default for ContentUnwrap<F> {
             sig_pk,
             ke_pk,
             flags,
+            admins: Vec::new(),
+            auditors: Vec::new(),
+            auditor_psks: Vec::new(),
+            psk: None,
             _phantom: core::marker::PhantomData,
         }
     }
@@ -106,9 +273,72 @@ where
         _store: &Store,
         ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
-        ctx.absorb(&mut self.sig_pk)?;
+        let mut repeated_admins = Size(0);
+        let admins = &mut self.admins;
+        let mut repeated_auditors = Size(0);
+        let auditors = &mut self.auditors;
+        let mut repeated_auditor_psks = Size(0);
+        let auditor_psks = &mut self.auditor_psks;
+        if let Some((_, psk)) = &self.psk {
+            ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                .commit()?
+                .mask(&mut self.sig_pk)?
+                .mask(&mut self.flags)?;
+            if (self.flags.0 & FLAG_ADMINS_MASK) != 0 {
+                ctx.mask(&mut repeated_admins)?
+                    .repeated(repeated_admins, |ctx| {
+                        let mut pk = ed25519::PublicKey::default();
+                        ctx.mask(&mut pk)?;
+                        admins.push(pk);
+                        Ok(ctx)
+                    })?;
+            }
+            if (self.flags.0 & FLAG_AUDITORS_MASK) != 0 {
+                ctx.mask(&mut repeated_auditors)?
+                    .repeated(repeated_auditors, |ctx| {
+                        let mut pk = ed25519::PublicKey::default();
+                        ctx.mask(&mut pk)?;
+                        auditors.push(pk);
+                        Ok(ctx)
+                    })?
+                    .mask(&mut repeated_auditor_psks)?
+                    .repeated(repeated_auditor_psks, |ctx| {
+                        let mut pskid = psk::PskId::default();
+                        ctx.mask(<&mut NBytes<psk::PskIdSize>>::from(&mut pskid))?;
+                        auditor_psks.push(pskid);
+                        Ok(ctx)
+                    })?;
+            }
+        } else {
+            ctx.absorb(&mut self.sig_pk)?
+                .absorb(&mut self.flags)?;
+            if (self.flags.0 & FLAG_ADMINS_MASK) != 0 {
+                ctx.absorb(&mut repeated_admins)?
+                    .repeated(repeated_admins, |ctx| {
+                        let mut pk = ed25519::PublicKey::default();
+                        ctx.absorb(&mut pk)?;
+                        admins.push(pk);
+                        Ok(ctx)
+                    })?;
+            }
+            if (self.flags.0 & FLAG_AUDITORS_MASK) != 0 {
+                ctx.absorb(&mut repeated_auditors)?
+                    .repeated(repeated_auditors, |ctx| {
+                        let mut pk = ed25519::PublicKey::default();
+                        ctx.absorb(&mut pk)?;
+                        auditors.push(pk);
+                        Ok(ctx)
+                    })?
+                    .absorb(&mut repeated_auditor_psks)?
+                    .repeated(repeated_auditor_psks, |ctx| {
+                        let mut pskid = psk::PskId::default();
+                        ctx.absorb(<&mut NBytes<psk::PskIdSize>>::from(&mut pskid))?;
+                        auditor_psks.push(pskid);
+                        Ok(ctx)
+                    })?;
+            }
+        }
         self.ke_pk = x25519::public_from_ed25519(&self.sig_pk)?;
-        ctx.absorb(&mut self.flags)?;
         ctx.ed25519(&self.sig_pk, HashSig)?;
         Ok(ctx)
     }
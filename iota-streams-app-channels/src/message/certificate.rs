@@ -0,0 +1,174 @@
+//! `Certificate` message content. Author certifies a publisher key so that readers accept
+//! signed packets from it in channels announced with `FLAG_CERTIFIED_PUBLISHERS_MASK`.
+//!
+//! ```ddml
+//! message Certificate {
+//!     join link msgid;
+//!     absorb u8 sig_pk[32];
+//!     absorb u8 subject[32];
+//!     absorb u8 has_branch;
+//!     if has_branch {
+//!         absorb link branch;
+//!     }
+//!     absorb u64 not_before;
+//!     absorb u64 not_after;
+//!     commit;
+//!     squeeze external u8 hash[64];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `sig_pk` -- public key of the issuer, must be Author's.
+//!
+//! * `subject` -- public key of the certified publisher.
+//!
+//! * `branch` -- message the certified publisher's packets must be linked to.
+//!
+//! * `not_before`, `not_after` -- validity window of the certificate, `not_after` is `0`
+//!     for certificates that don't expire. An empty window, `not_after < not_before`, revokes
+//!     the publisher.
+//!
+//! * `sig` -- issuer's signature.
+//!
+//! Notes:
+//! 1) A later certificate for the same subject replaces the earlier one, a publisher is retired
+//!     with a certificate that has already expired.
+
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::{
+    sponge::prp::PRP,
+    Result,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+use crate::api::certificate::Certificate;
+
+pub struct ContentWrap<'a, F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a,
+{
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) cert: &'a Certificate<<Link as HasLink>::Rel>,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F> + AbsorbFallback<F>,
+{
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        ctx.join(&store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .absorb(&self.cert.subject)?
+            .absorb(Uint8(self.cert.branch.is_some() as u8))?;
+        if let Some(ref branch) = self.cert.branch {
+            ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(branch))?;
+        }
+        ctx.absorb(Uint64(self.cert.not_before))?
+            .absorb(Uint64(self.cert.not_after))?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F> + AbsorbFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.join(store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .absorb(&self.cert.subject)?
+            .absorb(Uint8(self.cert.branch.is_some() as u8))?;
+        if let Some(ref branch) = self.cert.branch {
+            ctx.absorb(<&Fallback<<Link as HasLink>::Rel>>::from(branch))?;
+        }
+        ctx.absorb(Uint64(self.cert.not_before))?
+            .absorb(Uint64(self.cert.not_after))?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) cert: Certificate<<Link as HasLink>::Rel>,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<F, Link> Default for ContentUnwrap<F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+{
+    fn default() -> Self {
+        Self {
+            link: <<Link as HasLink>::Rel as Default>::default(),
+            sig_pk: ed25519::PublicKey::default(),
+            cert: Certificate::default(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F> + AbsorbFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut has_branch = Uint8(0);
+        let mut not_before = Uint64(0);
+        let mut not_after = Uint64(0);
+        ctx.join(store, &mut self.link)?
+            .absorb(&mut self.sig_pk)?
+            .absorb(&mut self.cert.subject)?
+            .absorb(&mut has_branch)?;
+        if has_branch.0 != 0 {
+            let mut branch = <Link as HasLink>::Rel::default();
+            ctx.absorb(<&mut Fallback<<Link as HasLink>::Rel>>::from(&mut branch))?;
+            self.cert.branch = Some(branch);
+        }
+        ctx.absorb(&mut not_before)?
+            .absorb(&mut not_after)?
+            .ed25519(&self.sig_pk, HashSig)?;
+        self.cert.not_before = not_before.0;
+        self.cert.not_after = not_after.0;
+        Ok(ctx)
+    }
+}
@@ -0,0 +1,156 @@
+//! `DirectMessage` message content. The message is published by a subscriber and is
+//! readable by the channel owner only. A random `key` is encapsulated for channel
+//! owner with owner's X25519 public key, the key is absorbed into spongos and the
+//! resulting state is used to mask subscriber's public key and the payload.
+//! The message is signed with subscriber's private key so that channel owner
+//! can authenticate the sender. No keyload is involved.
+//!
+//! ```pb3
+//! message DirectMessage {
+//!     join link msgid;
+//!     x25519(key) u8 xkey[32];
+//!     mask u8 sig_pk[32];
+//!     mask bytes masked_payload;
+//!     commit;
+//!     squeeze external byte hash[64];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `key` -- random key encapsulated for channel owner.
+//!
+//! * `sig_pk` -- subscriber's Ed25519 public key.
+//!
+//! * `masked_payload` -- masked payload.
+//!
+//! * `hash` -- hash value to be signed.
+//!
+//! * `sig` -- message signature generated with subscriber's private key.
+//!
+//! Note, only the content is hidden: the message is published at an address derived from
+//! subscriber's public key and sequence number, and in multi-branch channels the following
+//! sequence message carries subscriber's public key in the clear. Direct messages of the
+//! same subscriber can thus be linked together and to the subscriber by any reader.
+
+use iota_streams_core::{Result, WrappedError, wrapped_err, LOCATION_LOG};
+use iota_streams_core::Errors::MessageCreationFailure;
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::sponge::prp::PRP;
+use iota_streams_core_edsig::{
+    key_exchange::x25519,
+    signature::ed25519,
+};
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+pub struct ContentWrap<'a, F, Link: HasLink> {
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) key: NBytes<U32>,
+    pub(crate) masked_payload: &'a Bytes,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) author_ke_pk: &'a x25519::PublicKey,
+    pub(crate) _phantom: core::marker::PhantomData<(Link, F)>,
+}
+
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        ctx.join(&store, self.link)?
+            .x25519(self.author_ke_pk, &self.key)?
+            .mask(&self.sig_kp.public)?
+            .mask(self.masked_payload)?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.join(store, self.link)?
+            .x25519(self.author_ke_pk, &self.key)?
+            .mask(&self.sig_kp.public)?
+            .mask(self.masked_payload)?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<'a, F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) key: NBytes<U32>,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) masked_payload: Bytes,
+    author_ke_sk: &'a x25519::StaticSecret,
+    _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> ContentUnwrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+{
+    pub fn new(author_ke_sk: &'a x25519::StaticSecret) -> Result<Self> {
+        match ed25519::PublicKey::from_bytes(&[0_u8; ed25519::PUBLIC_KEY_LENGTH]) {
+            Ok(pk) => Ok(Self {
+                link: <<Link as HasLink>::Rel as Default>::default(),
+                key: NBytes::<U32>::default(),
+                sig_pk: pk,
+                masked_payload: Bytes::default(),
+                author_ke_sk,
+                _phantom: core::marker::PhantomData,
+            }),
+            Err(e) => Err(wrapped_err!(MessageCreationFailure, WrappedError(e)))
+        }
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        ctx.join(store, &mut self.link)?
+            .x25519(self.author_ke_sk, &mut self.key)?
+            .mask(&mut self.sig_pk)?
+            .mask(&mut self.masked_payload)?
+            .ed25519(&self.sig_pk, HashSig)?;
+        Ok(ctx)
+    }
+}
@@ -5,6 +5,9 @@
 //! message Keyload {
 //!     join link msgid;
 //!     absorb u8 nonce[16];
+//!     if flags & FLAG_ADMINS_MASK {
+//!         absorb u8 sig_pk[32];
+//!     }
 //!     skip repeated {
 //!         fork;
 //!         mask u8 id[16];
@@ -25,11 +28,48 @@
 //! }
 //! ```
 //!
+//! When the channel is announced with `FLAG_ANONYMOUS_KEYLOAD_MASK` set, forks carry no
+//! recipient identifiers and recipients find their fork by trial decryption:
+//!
+//! ```ddml
+//! message Keyload {
+//!     join link msgid;
+//!     absorb u8 nonce[16];
+//!     if flags & FLAG_ADMINS_MASK {
+//!         absorb u8 sig_pk[32];
+//!     }
+//!     skip repeated {
+//!         fork;
+//!         absorb external u8 psk[32];
+//!         commit;
+//!         squeeze u8 hint[16];
+//!         mask u8 key[32];
+//!     }
+//!     skip repeated {
+//!         fork;
+//!         absorb u8 eph_key[32];
+//!         x25519(eph_key) u8 xkey[32];
+//!         commit;
+//!         mask u8 key[32];
+//!         commit;
+//!         squeeze u8 hint[16];
+//!     }
+//!     absorb external u8 key[32];
+//!     repeated {
+//!         mask u8 pk[32];
+//!     }
+//!     commit;
+//! }
+//! ```
+//!
 //! # Fields:
 //!
 //! * `nonce` -- A nonce to be used with the key encapsulated in the keyload.
 //! A unique nonce allows for session keys to be reused.
 //!
+//! * `sig_pk` -- Public key of the sender, Author or one of channel administrators. It's present
+//! in channels announced with `FLAG_ADMINS_MASK` only, keyloads are signed by Author otherwise.
+//!
 //! * `id` -- Key (PSK or X25519 public key) identifier.
 //!
 //! * `psk` -- Pre-shared key known to the author and to a legit recipient.
@@ -42,12 +82,24 @@
 //!
 //! * `key` -- Session key; a legit recipient gets it from corresponding fork.
 //!
+//! * `hint` -- Tag allowing a recipient to recognize its fork in anonymous mode.
+//!
+//! * `pk` -- Ed25519 public keys of the X25519 recipients in anonymous mode,
+//! masked with the session key so that only recipients learn which publishers to fetch.
+//!
 //! * `sig` -- Optional signature; allows to authenticate keyload.
 //!
 //! Notes:
-//! 1) Keys identities are not encrypted and may be linked to recipients identities.
+//! 1) Keys identities are not encrypted and may be linked to recipients identities
+//!     unless anonymous mode is used. The number of recipients is still revealed.
+//!     In anonymous mode public keys are masked, they are disclosed to recipients only.
 //! 2) Keyload is not authenticated (signed). It can later be implicitly authenticated
 //!     via `SignedPacket`.
+//! 3) Recipients that don't fit into one message are put into preceding `KeyloadPart` messages
+//!     with the same content and session key. Recipients found in a part use the session key to
+//!     unwrap the final `Keyload`, so packets are attached to the final `Keyload` only.
+//! 4) `PrivateKeyload` messages have the same content, they are sent by any participant to
+//!     start a private branch with other participants it knows. They always carry `sig_pk`.
 
 use iota_streams_core::Result;
 use iota_streams_app::message::{
@@ -79,6 +131,22 @@ use iota_streams_ddml::{
     types::*,
 };
 
+/// Size of the fork hint used in anonymous keyloads.
+pub const HINT_SIZE: usize = 16;
+type HintSize = U16;
+
+/// Size of a pre-shared key fork.
+pub fn psk_fork_size<F: PRP>(anonymous: bool) -> usize {
+    let id_size = if anonymous { HINT_SIZE } else { psk::PSKID_SIZE };
+    id_size + spongos::KeySize::<F>::USIZE
+}
+
+/// Size of a public key fork, including the masked public key in anonymous mode.
+pub fn ke_pk_fork_size<F: PRP>(anonymous: bool) -> usize {
+    let id_size = if anonymous { HINT_SIZE + ed25519::PUBLIC_KEY_LENGTH } else { ed25519::PUBLIC_KEY_LENGTH };
+    id_size + x25519::PUBLIC_KEY_LENGTH + spongos::KeySize::<F>::USIZE
+}
+
 pub struct ContentWrap<'a, F, Link: HasLink, Psks, KePks> {
     pub(crate) link: &'a <Link as HasLink>::Rel,
     pub nonce: NBytes<U16>,
@@ -86,6 +154,8 @@ pub struct ContentWrap<'a, F, Link: HasLink, Psks, KePks> {
     pub(crate) psks: Psks,
     pub(crate) ke_pks: KePks,
     pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) anonymous: bool,
+    pub(crate) with_sig_pk: bool,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
@@ -102,22 +172,41 @@ where
         let repeated_psks = Size(self.psks.len());
         let repeated_ke_pks = Size(self.ke_pks.len());
         ctx.join(&store, self.link)?
-            .absorb(&self.nonce)?
-            .skip(repeated_psks)?
+            .absorb(&self.nonce)?;
+        if self.with_sig_pk {
+            ctx.absorb(&self.sig_kp.public)?;
+        }
+        ctx.skip(repeated_psks)?
             .repeated(self.psks.clone(), |ctx, (pskid, psk)| {
                 ctx.fork(|ctx| {
-                    ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?
-                        .absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
-                        .commit()?
-                        .mask(&self.key)
+                    if self.anonymous {
+                        ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                            .commit()?
+                            .squeeze(Mac(HINT_SIZE))?
+                            .mask(&self.key)
+                    } else {
+                        ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?
+                            .absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                            .commit()?
+                            .mask(&self.key)
+                    }
                 })
             })?
             .skip(repeated_ke_pks)?
             .repeated(self.ke_pks.clone(), |ctx, (sig_pk, ke_pk)| {
-                ctx.fork(|ctx| ctx.absorb(sig_pk)?.x25519(ke_pk, &self.key))
+                ctx.fork(|ctx| {
+                    if self.anonymous {
+                        ctx.x25519(ke_pk, &self.key)?.commit()?.squeeze(Mac(HINT_SIZE))
+                    } else {
+                        ctx.absorb(sig_pk)?.x25519(ke_pk, &self.key)
+                    }
+                })
             })?
-            .absorb(External(&self.key))?
-            .ed25519(self.sig_kp, HashSig)?
+            .absorb(External(&self.key))?;
+        if self.anonymous {
+            ctx.repeated(self.ke_pks.clone(), |ctx, (sig_pk, _)| ctx.mask(sig_pk))?;
+        }
+        ctx.ed25519(self.sig_kp, HashSig)?
             .commit()?;
         Ok(ctx)
     }
@@ -141,26 +230,46 @@ where
         let repeated_ke_pks = Size(self.ke_pks.len());
         ctx
             .join(store, self.link)?
-            .absorb(&self.nonce)?
-            .skip(repeated_psks)?
-            .repeated(self.psks.clone().into_iter(), |ctx, (pskid, psk)| {
+            .absorb(&self.nonce)?;
+        if self.with_sig_pk {
+            ctx.absorb(&self.sig_kp.public)?;
+        }
+        ctx.skip(repeated_psks)?
+            .repeated(self.psks.clone(), |ctx, (pskid, psk)| {
                 ctx.fork(|ctx| {
-                    ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?
-                        .absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
-                        .commit()?
-                        .mask(&self.key)
+                    if self.anonymous {
+                        ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                            .commit()?
+                            .squeeze(Mac(HINT_SIZE))?
+                            .mask(&self.key)
+                    } else {
+                        ctx.mask(<&NBytes<psk::PskIdSize>>::from(pskid))?
+                            .absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                            .commit()?
+                            .mask(&self.key)
+                    }
                 })
             })?
             .skip(repeated_ke_pks)?
-            .repeated(self.ke_pks.clone().into_iter(), |ctx, (sig_pk, ke_pk)| {
-                ctx.fork(|ctx|
-                         ctx
-                         .absorb(sig_pk)?
-                         .x25519(ke_pk, &self.key)
-                )
+            .repeated(self.ke_pks.clone(), |ctx, (sig_pk, ke_pk)| {
+                ctx.fork(|ctx| {
+                    if self.anonymous {
+                        ctx
+                            .x25519(ke_pk, &self.key)?
+                            .commit()?
+                            .squeeze(Mac(HINT_SIZE))
+                    } else {
+                        ctx
+                            .absorb(sig_pk)?
+                            .x25519(ke_pk, &self.key)
+                    }
+                })
             })?
-            .absorb(External(&self.key))?
-            .ed25519(self.sig_kp, HashSig)?
+            .absorb(External(&self.key))?;
+        if self.anonymous {
+            ctx.repeated(self.ke_pks.clone(), |ctx, (sig_pk, _)| ctx.mask(sig_pk))?;
+        }
+        ctx.ed25519(self.sig_kp, HashSig)?
             .commit()?;
         Ok(ctx)
     }
@@ -177,7 +286,14 @@ pub struct ContentUnwrap<'a, F, Link: HasLink, LookupArg: 'a, LookupPsk, LookupK
     pub(crate) lookup_ke_sk: LookupKeSk,
     pub(crate) ke_pks: Vec<ed25519::PublicKey>,
     pub key: Option<NBytes<U32>>, // TODO: unify with spongos::Spongos::<F>::KEY_SIZE
-    pub(crate) sig_pk: &'a ed25519::PublicKey,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) with_sig_pk: bool,
+    // Candidate keys tried against every fork of an anonymous keyload.
+    pub(crate) anonymous: bool,
+    pub(crate) anonymous_psks: Vec<psk::IPsk<'a>>,
+    pub(crate) anonymous_ke_sk: Option<&'a x25519::StaticSecret>,
+    // Session key recovered from a preceding part of the keyload joined to the same link.
+    pub(crate) carried_key: Option<(<Link as HasLink>::Rel, NBytes<U32>)>,
     _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
@@ -194,7 +310,6 @@ where
         lookup_arg: &'a LookupArg,
         lookup_psk: LookupPsk,
         lookup_ke_sk: LookupKeSk,
-        sig_pk: &'a ed25519::PublicKey,
     ) -> Self {
         Self {
             link: <<Link as HasLink>::Rel as Default>::default(),
@@ -205,10 +320,142 @@ where
             lookup_ke_sk,
             ke_pks: Vec::new(),
             key: None,
-            sig_pk,
+            sig_pk: ed25519::PublicKey::default(),
+            with_sig_pk: true,
+            anonymous: false,
+            anonymous_psks: Vec::new(),
+            anonymous_ke_sk: None,
+            carried_key: None,
             _phantom: core::marker::PhantomData,
         }
     }
+
+    /// Unwrap keyload which doesn't carry the public key of its sender, `sig_pk` is the expected signer.
+    pub fn with_signer(mut self, sig_pk: ed25519::PublicKey) -> Self {
+        self.sig_pk = sig_pk;
+        self.with_sig_pk = false;
+        self
+    }
+
+    /// Unwrap anonymous keyload trying the given pre-shared keys and X25519 secret key
+    /// against every fork.
+    pub fn with_anonymous(mut self, psks: Vec<psk::IPsk<'a>>, ke_sk: Option<&'a x25519::StaticSecret>) -> Self {
+        self.anonymous = true;
+        self.anonymous_psks = psks;
+        self.anonymous_ke_sk = ke_sk;
+        self
+    }
+
+    /// Use session key recovered from a `KeyloadPart` joined to `link` if the recipient
+    /// is not found in this keyload.
+    pub fn with_carried_key(mut self, link: <Link as HasLink>::Rel, key: NBytes<U32>) -> Self {
+        self.carried_key = Some((link, key));
+        self
+    }
+
+    fn use_carried_key(&mut self) {
+        if self.key.is_none() {
+            if let Some((link, key)) = self.carried_key.take() {
+                if link == self.link {
+                    self.key = Some(key);
+                }
+            }
+        }
+    }
+
+    fn unwrap_anonymous<'c, Store, IS>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>>
+    where
+        Store: LinkStore<F, <Link as HasLink>::Rel>,
+        IS: io::IStream,
+    {
+        let mut repeated_psks = Size(0);
+        let mut repeated_ke_pks = Size(0);
+        let mut hint = NBytes::<HintSize>::default();
+        let mut tag = External(NBytes::<HintSize>::default());
+
+        ctx
+            .join(store, &mut self.link)?
+            .absorb(&mut self.nonce)?;
+        if self.with_sig_pk {
+            ctx.absorb(&mut self.sig_pk)?;
+        }
+        ctx.skip(&mut repeated_psks)?
+            .repeated(repeated_psks, |ctx| {
+                if self.key.is_none() {
+                    ctx.fork(|ctx| {
+                        // Hint is not absorbed, it can be read before trying candidate keys.
+                        ctx.skip(&mut hint)?;
+                        let mut found = None;
+                        for (_, psk) in self.anonymous_psks.iter() {
+                            ctx.fork(|ctx| {
+                                ctx.absorb(External(<&NBytes<psk::PskSize>>::from(*psk)))?
+                                    .commit()?
+                                    .squeeze(&mut tag)
+                            })?;
+                            if tag.0 == hint {
+                                found = Some(*psk);
+                                break;
+                            }
+                        }
+                        if let Some(psk) = found {
+                            let mut key = NBytes::<U32>::default();
+                            ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?
+                                .commit()?
+                                .squeeze(&mut tag)?
+                                .mask(&mut key)?;
+                            self.key = Some(key);
+                            Ok(ctx)
+                        } else {
+                            let n = Size(spongos::KeySize::<F>::USIZE);
+                            ctx.drop(n)
+                        }
+                    })
+                } else {
+                    // Drop entire fork.
+                    let n = Size(HINT_SIZE + spongos::KeySize::<F>::USIZE);
+                    ctx.drop(n)
+                }
+            })?
+            .skip(&mut repeated_ke_pks)?
+            .repeated(repeated_ke_pks, |ctx| {
+                match self.anonymous_ke_sk {
+                    Some(ke_sk) if self.key.is_none() => ctx.fork(|ctx| {
+                        let mut key = NBytes::<U32>::default();
+                        ctx.x25519(ke_sk, &mut key)?
+                            .commit()?
+                            .skip(&mut hint)?
+                            .squeeze(&mut tag)?;
+                        if tag.0 == hint {
+                            self.key = Some(key);
+                        }
+                        Ok(ctx)
+                    }),
+                    _ => {
+                        // Drop entire fork.
+                        let n = Size(x25519::PUBLIC_KEY_LENGTH + spongos::KeySize::<F>::USIZE + HINT_SIZE);
+                        ctx.drop(n)
+                    }
+                }
+            })?;
+        self.use_carried_key();
+        if let Some(ref key) = self.key {
+            ctx
+                .absorb(External(key))?
+                .repeated(repeated_ke_pks, |ctx| {
+                    let mut ke_pk = ed25519::PublicKey::default();
+                    ctx.mask(&mut ke_pk)?;
+                    self.ke_pks.push(ke_pk);
+                    Ok(ctx)
+                })?
+                .ed25519(&self.sig_pk, HashSig)?
+                .commit()?;
+        }
+        Ok(ctx)
+    }
 }
 
 impl<'a, F, Link, Store, LookupArg, LookupPsk, LookupKeSk> message::ContentUnwrap<F, Store>
@@ -227,14 +474,21 @@ where
         store: &Store,
         ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        if self.anonymous {
+            return self.unwrap_anonymous(store, ctx);
+        }
+
         let mut repeated_psks = Size(0);
         let mut repeated_ke_pks = Size(0);
         let mut pskid = psk::PskId::default();
 
         ctx
             .join(store, &mut self.link)?
-            .absorb(&mut self.nonce)?
-            .skip(&mut repeated_psks)?
+            .absorb(&mut self.nonce)?;
+        if self.with_sig_pk {
+            ctx.absorb(&mut self.sig_pk)?;
+        }
+        ctx.skip(&mut repeated_psks)?
             .repeated(repeated_psks, |ctx| {
                 if self.key.is_none() {
                     ctx.fork(|ctx| {
@@ -248,13 +502,13 @@ where
                             Ok(ctx)
                         } else {
                             // Just drop the rest of the forked message so not to waste Spongos operations
-                            let n = Size(0 + 0 + spongos::KeySize::<F>::USIZE);
+                            let n = Size(spongos::KeySize::<F>::USIZE);
                             ctx.drop(n)
                         }
                     })
                 } else {
                     // Drop entire fork.
-                    let n = Size(psk::PSKID_SIZE + 0 + 0 + spongos::KeySize::<F>::USIZE);
+                    let n = Size(psk::PSKID_SIZE + spongos::KeySize::<F>::USIZE);
                     ctx.drop(n)
                 }
             })?
@@ -268,7 +522,7 @@ where
                         ctx.x25519(ke_sk, &mut key)?;
                         self.key = Some(key);
                         // Save the relevant public key
-                        self.ke_pk = ke_pk.clone();
+                        self.ke_pk = ke_pk;
                         self.ke_pks.push(ke_pk);
                         Ok(ctx)
                     } else {
@@ -282,10 +536,11 @@ where
             })?
             //.guard(self.key.is_some(), "Key not found")?
         ;
+        self.use_carried_key();
         if let Some(ref key) = self.key {
             ctx
                 .absorb(External(key))?
-                .ed25519(self.sig_pk, HashSig)?
+                .ed25519(&self.sig_pk, HashSig)?
                 .commit()?
             ;
         }
@@ -18,6 +18,24 @@ pub mod subscribe;
 /// Sequence message.
 pub mod sequence;
 
+/// TreeKeyload message.
+pub mod tree_keyload;
+
+/// RoleAssignment message.
+pub mod role_assignment;
+
+/// Certificate message.
+pub mod certificate;
+
+/// AdminSet message.
+pub mod admin_set;
+
+/// DirectMessage message.
+pub mod direct_message;
+
+/// Topic message.
+pub mod topic;
+
 pub const ANNOUNCE: u8 = 0;
 pub const KEYLOAD: u8 = 1;
 pub const SEQUENCE: u8 = 2;
@@ -25,6 +43,15 @@ pub const SIGNED_PACKET: u8 = 3;
 pub const TAGGED_PACKET: u8 = 4;
 pub const SUBSCRIBE: u8 = 5;
 pub const UNSUBSCRIBE: u8 = 6;
+pub const TREE_KEYLOAD: u8 = 7;
+pub const KEYLOAD_PART: u8 = 8;
+pub const ROLE_ASSIGNMENT: u8 = 9;
+pub const CERTIFICATE: u8 = 10;
+pub const ADMIN_SET: u8 = 11;
+pub const PRIVATE_KEYLOAD: u8 = 12;
+pub const DIRECT_MESSAGE: u8 = 13;
+pub const HIDDEN_ANNOUNCE: u8 = 14;
+pub const TOPIC: u8 = 15;
 
 // Unsubscribe message.
 // pub mod unsubscribe;
@@ -0,0 +1,163 @@
+//! `RoleAssignment` message content. The message carries the whole table of roles assigned
+//! by Author and replaces the table previously known to the recipient.
+//!
+//! ```ddml
+//! message RoleAssignment {
+//!     join link msgid;
+//!     absorb u8 sig_pk[32];
+//!     skip repeated {
+//!         mask u8 pk[32];
+//!         mask u8 role;
+//!     }
+//!     commit;
+//!     squeeze external u8 hash[64];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `sig_pk` -- public key of the assigning user, must be Author's.
+//!
+//! * `pk` -- public key of a participant.
+//!
+//! * `role` -- role of the participant: `0` reader, `1` writer, `2` admin.
+//!
+//! * `sig` -- signature of the assigning user.
+//!
+//! Notes:
+//! 1) Roles are masked, so the table is only revealed to the recipients of the keyload
+//!     the message is linked to.
+
+use core::convert::TryFrom as _;
+
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::{
+    err,
+    prelude::Vec,
+    sponge::prp::PRP,
+    Errors::BadRole,
+    Result,
+    LOCATION_LOG,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+use crate::api::role::Role;
+
+pub struct ContentWrap<'a, F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a,
+{
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) roles: Vec<(&'a ed25519::PublicKey, Role)>,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        let repeated_roles = Size(self.roles.len());
+        ctx.join(&store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .skip(repeated_roles)?
+            .repeated(self.roles.iter(), |ctx, (pk, role)| ctx.mask(*pk)?.mask(Uint8((*role).into())))?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        let repeated_roles = Size(self.roles.len());
+        ctx.join(store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .skip(repeated_roles)?
+            .repeated(self.roles.iter(), |ctx, (pk, role)| ctx.mask(*pk)?.mask(&Uint8((*role).into())))?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) roles: Vec<(ed25519::PublicKey, Role)>,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<F, Link> Default for ContentUnwrap<F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+{
+    fn default() -> Self {
+        Self {
+            link: <<Link as HasLink>::Rel as Default>::default(),
+            sig_pk: ed25519::PublicKey::default(),
+            roles: Vec::new(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut repeated_roles = Size(0);
+        ctx.join(store, &mut self.link)?
+            .absorb(&mut self.sig_pk)?
+            .skip(&mut repeated_roles)?
+            .repeated(repeated_roles, |ctx| {
+                let mut pk = ed25519::PublicKey::default();
+                let mut role = Uint8(0);
+                ctx.mask(&mut pk)?.mask(&mut role)?;
+                match Role::try_from(role.0) {
+                    Ok(role) => self.roles.push((pk, role)),
+                    Err(()) => return err!(BadRole(role.0)),
+                }
+                Ok(ctx)
+            })?
+            .ed25519(&self.sig_pk, HashSig)?;
+        Ok(ctx)
+    }
+}
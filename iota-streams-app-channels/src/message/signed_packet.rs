@@ -6,6 +6,10 @@
 //! ```pb3
 //! message SignedPacket {
 //!     join link msgid;
+//!     absorb u8 sig_pk[32];
+//!     if flags & FLAG_CERTIFIED_PUBLISHERS_MASK {
+//!         absorb u64 timestamp;
+//!     }
 //!     absorb bytes public_payload;
 //!     mask bytes masked_payload;
 //!     commit;
@@ -18,6 +22,12 @@
 //!
 //! * `msgid` -- link to the base message.
 //!
+//! * `sig_pk` -- public key of the publisher.
+//!
+//! * `timestamp` -- publication time, milliseconds since Unix epoch, present in channels with
+//!     certified publishers. It's asserted by the publisher, so certificates are checked against
+//!     the time the packet is received instead.
+//!
 //! * `public_payload` -- public part of payload.
 //!
 //! * `masked_payload` -- masked part of payload.
@@ -52,6 +62,7 @@ where
     pub(crate) link: &'a <Link as HasLink>::Rel,
     pub(crate) public_payload: &'a Bytes,
     pub(crate) masked_payload: &'a Bytes,
+    pub(crate) timestamp: Option<u64>,
     pub(crate) sig_kp: &'a ed25519::Keypair,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
@@ -65,8 +76,11 @@ where
     fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
         let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
         ctx.join(&store, self.link)?
-            .absorb(&self.sig_kp.public)?
-            .absorb(self.public_payload)?
+            .absorb(&self.sig_kp.public)?;
+        if let Some(timestamp) = self.timestamp {
+            ctx.absorb(Uint64(timestamp))?;
+        }
+        ctx.absorb(self.public_payload)?
             .mask(self.masked_payload)?
             .ed25519(self.sig_kp, HashSig)?;
         // TODO: Is both public and masked payloads are ok? Leave public only or masked only?
@@ -87,8 +101,11 @@ where
         ctx: &'c mut wrap::Context<F, OS>,
     ) -> Result<&'c mut wrap::Context<F, OS>> {
         ctx.join(store, self.link)?
-            .absorb(&self.sig_kp.public)?
-            .absorb(self.public_payload)?
+            .absorb(&self.sig_kp.public)?;
+        if let Some(timestamp) = self.timestamp {
+            ctx.absorb(Uint64(timestamp))?;
+        }
+        ctx.absorb(self.public_payload)?
             .mask(self.masked_payload)?
             .ed25519(self.sig_kp, HashSig)?;
         Ok(ctx)
@@ -100,9 +117,18 @@ pub struct ContentUnwrap<F, Link: HasLink> {
     pub(crate) public_payload: Bytes,
     pub(crate) masked_payload: Bytes,
     pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) timestamp: Option<u64>,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
+impl<F, Link: HasLink> ContentUnwrap<F, Link> {
+    /// Expect publication time signed in the packet.
+    pub fn with_timestamp(mut self) -> Self {
+        self.timestamp = Some(0);
+        self
+    }
+}
+
 impl<F, Link> Default for ContentUnwrap<F, Link>
 where
     Link: HasLink,
@@ -114,6 +140,7 @@ where
             public_payload: Bytes::default(),
             masked_payload: Bytes::default(),
             sig_pk: ed25519::PublicKey::default(),
+            timestamp: None,
             _phantom: core::marker::PhantomData,
         }
     }
@@ -132,8 +159,13 @@ where
         ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
         ctx.join(store, &mut self.link)?
-            .absorb(&mut self.sig_pk)?
-            .absorb(&mut self.public_payload)?
+            .absorb(&mut self.sig_pk)?;
+        if let Some(timestamp) = self.timestamp.as_mut() {
+            let mut signed = Uint64(0);
+            ctx.absorb(&mut signed)?;
+            *timestamp = signed.0;
+        }
+        ctx.absorb(&mut self.public_payload)?
             .mask(&mut self.masked_payload)?
             .ed25519(&self.sig_pk, HashSig)?;
         Ok(ctx)
@@ -106,6 +106,16 @@ pub struct ContentUnwrap<F, Link: HasLink> {
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
+impl<F, Link> Default for ContentUnwrap<F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+ {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<F, Link> ContentUnwrap<F, Link>
 where
     Link: HasLink,
@@ -0,0 +1,136 @@
+//! `Topic` message content. Author declares a named topic, the message is the root of
+//! the topic branch: topic keyloads are linked to it and topic packets are linked to
+//! topic keyloads. Subscribers may follow only selected topics.
+//!
+//! ```ddml
+//! message Topic {
+//!     join link msgid;
+//!     absorb u8 sig_pk[32];
+//!     mask bytes name;
+//!     commit;
+//!     squeeze external u8 hash[64];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `sig_pk` -- public key of the sender, must be Author's.
+//!
+//! * `name` -- topic name, unique within the channel.
+//!
+//! * `sig` -- Author's signature.
+//!
+//! Notes:
+//! 1) Topic name is only confidential if the message is linked to a keyload, topics
+//!     declared next to the announcement are visible to every subscriber.
+
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::{
+    sponge::prp::PRP,
+    Result,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+pub struct ContentWrap<'a, F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a,
+{
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) name: &'a Bytes,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        ctx.join(&store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .mask(self.name)?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.join(store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .mask(self.name)?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) name: Bytes,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<F, Link> Default for ContentUnwrap<F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+{
+    fn default() -> Self {
+        Self {
+            link: <<Link as HasLink>::Rel as Default>::default(),
+            sig_pk: ed25519::PublicKey::default(),
+            name: Bytes::default(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        ctx.join(store, &mut self.link)?
+            .absorb(&mut self.sig_pk)?
+            .mask(&mut self.name)?
+            .ed25519(&self.sig_pk, HashSig)?;
+        Ok(ctx)
+    }
+}
@@ -0,0 +1,302 @@
+//! `TreeKeyload` message content. This message distributes refreshed keys of a logical key
+//! hierarchy (key tree). New members get their leaf keys with X25519 key exchange, other keys
+//! are encrypted with the keys of their children. The root key is the group (session) key.
+//!
+//! ```ddml
+//! message TreeKeyload {
+//!     join link msgid;
+//!     absorb u8 nonce[16];
+//!     skip repeated {
+//!         fork;
+//!         absorb u8 pk[32];
+//!         absorb u8 eph_key[32];
+//!         x25519(eph_key) u8 xkey[32];
+//!         commit;
+//!         mask u8 leaf_key[32];
+//!         mask u32 leaf;
+//!     }
+//!     skip repeated {
+//!         absorb u32 child;
+//!         fork;
+//!         absorb external u8 child_key[32];
+//!         commit;
+//!         squeeze u8 hint[16];
+//!         mask u8 parent_key[32];
+//!     }
+//!     absorb external u8 root_key[32];
+//!     ed25519(hash) sig;
+//!     commit;
+//! }
+//! ```
+//!
+//! # Fields:
+//!
+//! * `nonce` -- A nonce to be used with the group key.
+//!
+//! * `pk` -- New member's Ed25519 public key.
+//!
+//! * `leaf_key`, `leaf` -- Key and identifier of the leaf assigned to the new member.
+//!
+//! * `child` -- Identifier of the node whose key encrypts the refreshed key of its parent `child / 2`.
+//!
+//! * `hint` -- Tag allowing a member to check it holds the current key of `child`.
+//!
+//! * `parent_key` -- Refreshed key of the parent node.
+//!
+//! * `root_key` -- Group key; a member recovers it from the entries on its path.
+//!
+//! * `sig` -- Author's signature.
+//!
+//! Notes:
+//! 1) Entries are ordered bottom-up so that a member recovers its path keys in one pass.
+//! 2) A member must process all tree keyloads in order, keys refreshed by a missed tree keyload
+//!     can't be recovered.
+//! 3) Updates not fitting into one message are split into several tree keyloads joined to the same
+//!     link, each carrying whole paths of a group of members and both children of the root. A member
+//!     recovers its path keys from the one carrying its path and the group key from the following ones.
+
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::{
+    prelude::{
+        typenum::Unsigned as _,
+        HashMap,
+        Vec,
+    },
+    sponge::{
+        prp::PRP,
+        spongos,
+    },
+    Result,
+};
+use iota_streams_core_edsig::{
+    key_exchange::x25519,
+    signature::ed25519,
+};
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+use super::keyload::HINT_SIZE;
+use crate::api::key_tree::{
+    NodeId,
+    NodeKey,
+    ROOT_NODE,
+};
+
+/// Size of a new member entry.
+pub fn join_size<F: PRP>() -> usize {
+    ed25519::PUBLIC_KEY_LENGTH + x25519::PUBLIC_KEY_LENGTH + spongos::KeySize::<F>::USIZE + 4
+}
+
+/// Size of a refreshed key entry.
+pub fn update_size<F: PRP>() -> usize {
+    4 + HINT_SIZE + spongos::KeySize::<F>::USIZE
+}
+
+pub struct ContentWrap<'a, F, Link: HasLink> {
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub nonce: NBytes<U16>,
+    pub(crate) joins: Vec<(&'a ed25519::PublicKey, &'a x25519::PublicKey, NodeId, &'a NodeKey)>,
+    pub(crate) updates: Vec<(NodeId, &'a NodeKey, &'a NodeKey)>,
+    pub(crate) key: &'a NodeKey,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: 'a + PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        let repeated_joins = Size(self.joins.len());
+        let repeated_updates = Size(self.updates.len());
+        ctx.join(&store, self.link)?
+            .absorb(&self.nonce)?
+            .skip(repeated_joins)?
+            .repeated(self.joins.iter(), |ctx, (sig_pk, ke_pk, leaf, leaf_key)| {
+                ctx.fork(|ctx| ctx.absorb(*sig_pk)?.x25519(*ke_pk, *leaf_key)?.mask(&Uint32(*leaf)))
+            })?
+            .skip(repeated_updates)?
+            .repeated(self.updates.iter(), |ctx, (child, child_key, parent_key)| {
+                ctx.absorb(Uint32(*child))?.fork(|ctx| {
+                    ctx.absorb(External(*child_key))?
+                        .commit()?
+                        .squeeze(Mac(HINT_SIZE))?
+                        .mask(*parent_key)
+                })
+            })?
+            .absorb(External(self.key))?
+            .ed25519(self.sig_kp, HashSig)?
+            .commit()?;
+        Ok(ctx)
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: 'a + PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        let repeated_joins = Size(self.joins.len());
+        let repeated_updates = Size(self.updates.len());
+        ctx.join(store, self.link)?
+            .absorb(&self.nonce)?
+            .skip(repeated_joins)?
+            .repeated(self.joins.iter(), |ctx, (sig_pk, ke_pk, leaf, leaf_key)| {
+                ctx.fork(|ctx| ctx.absorb(*sig_pk)?.x25519(*ke_pk, *leaf_key)?.mask(&Uint32(*leaf)))
+            })?
+            .skip(repeated_updates)?
+            .repeated(self.updates.iter(), |ctx, (child, child_key, parent_key)| {
+                ctx.absorb(Uint32(*child))?.fork(|ctx| {
+                    ctx.absorb(External(*child_key))?
+                        .commit()?
+                        .squeeze(Mac(HINT_SIZE))?
+                        .mask(*parent_key)
+                })
+            })?
+            .absorb(External(self.key))?
+            .ed25519(self.sig_kp, HashSig)?
+            .commit()?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<'a, F, Link: HasLink> {
+    pub link: <Link as HasLink>::Rel,
+    pub nonce: NBytes<U16>,
+    pub(crate) sig_pk: &'a ed25519::PublicKey,
+    pub(crate) own_pk: &'a ed25519::PublicKey,
+    pub(crate) ke_sk: &'a x25519::StaticSecret,
+    /// Path keys known before the message, updated with the recovered keys.
+    pub(crate) keys: HashMap<NodeId, NodeKey>,
+    /// New members listed in the message.
+    pub(crate) joins: Vec<ed25519::PublicKey>,
+    pub key: Option<NodeKey>,
+    _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> ContentUnwrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+{
+    pub fn new(
+        sig_pk: &'a ed25519::PublicKey,
+        own_pk: &'a ed25519::PublicKey,
+        ke_sk: &'a x25519::StaticSecret,
+        keys: HashMap<NodeId, NodeKey>,
+    ) -> Self {
+        Self {
+            link: <<Link as HasLink>::Rel as Default>::default(),
+            nonce: NBytes::default(),
+            sig_pk,
+            own_pk,
+            ke_sk,
+            keys,
+            joins: Vec::new(),
+            key: None,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<'a, F, Link>
+where
+    F: PRP + Clone,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut repeated_joins = Size(0);
+        let mut repeated_updates = Size(0);
+        let mut hint = NBytes::<U16>::default();
+        let mut tag = External(NBytes::<U16>::default());
+        let mut root_updated = false;
+        let mut root_recovered = false;
+
+        ctx.join(store, &mut self.link)?
+            .absorb(&mut self.nonce)?
+            .skip(&mut repeated_joins)?
+            .repeated(repeated_joins, |ctx| {
+                ctx.fork(|ctx| {
+                    let mut pk = ed25519::PublicKey::default();
+                    ctx.absorb(&mut pk)?;
+                    if pk == *self.own_pk {
+                        let mut leaf_key = NodeKey::default();
+                        let mut leaf = Uint32(0);
+                        ctx.x25519(self.ke_sk, &mut leaf_key)?.mask(&mut leaf)?;
+                        // Joining again invalidates previously known path.
+                        self.keys.clear();
+                        self.keys.insert(leaf.0, leaf_key);
+                    } else {
+                        let n = Size(x25519::PUBLIC_KEY_LENGTH + spongos::KeySize::<F>::USIZE + 4);
+                        ctx.drop(n)?;
+                    }
+                    self.joins.push(pk);
+                    Ok(ctx)
+                })
+            })?
+            .skip(&mut repeated_updates)?
+            .repeated(repeated_updates, |ctx| {
+                let mut child = Uint32(0);
+                ctx.absorb(&mut child)?;
+                let parent = child.0 >> 1;
+                root_updated |= parent == ROOT_NODE;
+                ctx.fork(|ctx| {
+                    if let Some(child_key) = self.keys.get(&child.0) {
+                        ctx.absorb(External(child_key))?
+                            .commit()?
+                            .skip(&mut hint)?
+                            .squeeze(&mut tag)?;
+                        if tag.0 == hint {
+                            let mut parent_key = NodeKey::default();
+                            ctx.mask(&mut parent_key)?;
+                            self.keys.insert(parent, parent_key);
+                            root_recovered |= parent == ROOT_NODE;
+                            return Ok(ctx);
+                        }
+                        // Stale key, eg. the member has been removed.
+                        ctx.drop(Size(spongos::KeySize::<F>::USIZE))
+                    } else {
+                        ctx.drop(Size(HINT_SIZE + spongos::KeySize::<F>::USIZE))
+                    }
+                })
+            })?;
+
+        // Previously known group key is only valid if it has not been refreshed.
+        if !root_updated || root_recovered {
+            self.key = self.keys.get(&ROOT_NODE).cloned();
+        }
+        if let Some(ref key) = self.key {
+            ctx.absorb(External(key))?
+                .ed25519(self.sig_pk, HashSig)?
+                .commit()?;
+        }
+        Ok(ctx)
+    }
+}
@@ -71,8 +71,8 @@ where
         header.unwrap(&store, &mut ctx)?;
 
         Ok(PreparsedMessage {
-            header: header,
-            ctx: ctx,
+            header,
+            ctx,
         })
     }
 }
@@ -17,6 +17,13 @@ use iota_streams_ddml::{
 use super::*;
 
 pub const FLAG_BRANCHING_MASK: u8 = 1;
+/// Keyload forks carry no recipient identifiers. Publishers' public keys are masked with
+/// the session key, so that recipients still fetch packets of each other.
+pub const FLAG_ANONYMOUS_KEYLOAD_MASK: u8 = 2;
+pub const FLAG_RATCHET_MASK: u8 = 4;
+pub const FLAG_CERTIFIED_PUBLISHERS_MASK: u8 = 8;
+pub const FLAG_ADMINS_MASK: u8 = 16;
+pub const FLAG_AUDITORS_MASK: u8 = 32;
 
 #[derive(Clone)]
 pub struct HDF<Link> {
@@ -48,7 +55,7 @@ impl<Link> HDF<Link> {
     }
 
     pub fn with_content_type(mut self, content_type: u8) -> Result<Self> {
-        try_or!(content_type < 0x10, ValueOutOfRange(0x10 as usize, content_type as usize))?;
+        try_or!(content_type < 0x10, ValueOutOfRange(0x10_usize, content_type as usize))?;
         self.content_type = content_type;
         Ok(self)
     }
@@ -60,7 +67,7 @@ impl<Link> HDF<Link> {
     pub fn with_payload_length(mut self, payload_length: usize) -> Result<Self> {
         try_or!(
             payload_length < 0x0400,
-            MaxSizeExceeded(0x0400 as usize, payload_length)
+            MaxSizeExceeded(0x0400_usize, payload_length)
         )?;
         self.payload_length = payload_length;
         Ok(self)
@@ -73,7 +80,7 @@ impl<Link> HDF<Link> {
     pub fn with_payload_frame_count(mut self, payload_frame_count: u32) -> Result<Self> {
         try_or!(
             payload_frame_count < 0x400000,
-            MaxSizeExceeded(0x400000 as usize, payload_frame_count as usize)
+            MaxSizeExceeded(0x400000_usize, payload_frame_count as usize)
         )?;
         self.payload_frame_count = payload_frame_count;
         Ok(self)
@@ -93,10 +100,10 @@ impl<Link> HDF<Link> {
     }
 
     pub fn new_with_fields(link: Link, content_type: u8, payload_length: usize, seq_num: u64) -> Result<Self> {
-        try_or!(content_type < 0x10, ValueOutOfRange(0x10 as usize, content_type as usize))?;
+        try_or!(content_type < 0x10, ValueOutOfRange(0x10_usize, content_type as usize))?;
         try_or!(
             payload_length < 0x0400,
-            MaxSizeExceeded(0x0400 as usize, payload_length)
+            MaxSizeExceeded(0x0400_usize, payload_length)
         )?;
         Ok(Self {
             encoding: UTF8,
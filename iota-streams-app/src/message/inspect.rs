@@ -0,0 +1,58 @@
+//! Inspection of message headers for routing and indexing.
+//!
+//! Header fields are not encrypted, they are read from the message alone without any keys
+//! or link store.
+
+use iota_streams_core::{
+    sponge::prp::PRP,
+    Result,
+};
+
+use crate::transport::tangle::{
+    AppInst,
+    MsgId,
+    TangleMessage,
+};
+
+/// Public header fields of a Tangle message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderInfo {
+    /// Payload encoding, `UTF8`.
+    pub encoding: u8,
+    /// Message syntax version, `STREAMS_1_VER`.
+    pub version: u8,
+    /// Application specific content type, eg. `SIGNED_PACKET` for Channels.
+    pub content_type: u8,
+    /// Length of the payload.
+    pub payload_length: usize,
+    /// Number of payload frames following the header.
+    pub payload_frame_count: u32,
+    /// Sequence number of the message publisher.
+    pub seq_num: u64,
+    /// Application instance the message is published in.
+    pub appinst: AppInst,
+    /// Message identifier within the application instance.
+    pub msgid: MsgId,
+    /// Timestamp of the bundle carrying the message.
+    pub timestamp: u64,
+}
+
+/// Parse header of a message without unwrapping its content.
+///
+///   # Arguments
+///   * `msg` - Message as received from transport
+///
+pub fn inspect<F: PRP>(msg: &TangleMessage<F>) -> Result<HeaderInfo> {
+    let header = msg.binary.parse_header()?.header;
+    Ok(HeaderInfo {
+        encoding: header.encoding.0,
+        version: header.version.0,
+        content_type: header.content_type,
+        payload_length: header.payload_length,
+        payload_frame_count: header.payload_frame_count,
+        seq_num: header.seq_num.0,
+        appinst: header.link.appinst,
+        msgid: header.link.msgid,
+        timestamp: msg.timestamp,
+    })
+}
@@ -1,6 +1,9 @@
 use core::fmt;
 
-use iota_streams_core::Result;
+use iota_streams_core::{
+    psk,
+    Result,
+};
 use iota_streams_core_edsig::signature::ed25519;
 
 use super::hdf::HDF;
@@ -102,6 +105,10 @@ pub trait LinkGenerator<Link: HasLink>: Default {
     /// Used by Author to generate a new application instance: channels address and announcement message identifier
     fn gen(&mut self, pk: &ed25519::PublicKey, idx: u64);
 
+    /// Used by Author to generate a hidden application instance, channel address is derived
+    /// from a pre-shared key instead of Author's public key
+    fn gen_hidden(&mut self, psk: &psk::Psk, idx: u64);
+
     /// Used by Author to get announcement message id, it's just stored internally by link generator
     fn get(&self) -> Link;
 
@@ -22,3 +22,10 @@ mod preparsed;
 pub use preparsed::*;
 mod unwrapped;
 pub use unwrapped::*;
+#[cfg(feature = "tangle")]
+mod inspect;
+#[cfg(feature = "tangle")]
+pub use inspect::{
+    inspect,
+    HeaderInfo,
+};
@@ -54,13 +54,13 @@ impl PCF<()> {
         PCF {
             frame_type: self.frame_type,
             payload_frame_num: self.payload_frame_num,
-            content: content,
+            content,
         }
     }
 }
 
 fn payload_frame_num_from(n: u32) -> Result<NBytes<U3>> {
-    try_or!(n < 0x400000, ValueOutOfRange(0x400000 as usize, n as usize))?;
+    try_or!(n < 0x400000, ValueOutOfRange(0x400000_usize, n as usize))?;
     let v = n.to_be_bytes();
     let g = <GenericArray<u8, U3>>::from_slice(&v[1..]);
     Ok(NBytes::from(*g))
@@ -74,7 +74,7 @@ fn payload_frame_num_to(v: &NBytes<U3>) -> u32 {
 
 fn payload_frame_num_check(v: &NBytes<U3>) -> Result<()> {
     try_or!(v.as_ref()[0] < 0x40,
-                         ValueOutOfRange(0x40 as usize, v.as_ref()[0] as usize)
+                         ValueOutOfRange(0x40_usize, v.as_ref()[0] as usize)
     )?;
     Ok(())
 }
@@ -115,9 +115,9 @@ where
     F: PRP,
     Content: ContentSizeof<F>,
 {
-    fn sizeof<'c>(&self, mut ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+    fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
         ctx.absorb(&self.frame_type)?.skip(&self.payload_frame_num)?;
-        self.content.sizeof(&mut ctx)?;
+        self.content.sizeof(ctx)?;
         Ok(ctx)
     }
 }
@@ -130,10 +130,10 @@ where
     fn wrap<'c, OS: io::OStream>(
         &self,
         store: &Store,
-        mut ctx: &'c mut wrap::Context<F, OS>,
+        ctx: &'c mut wrap::Context<F, OS>,
     ) -> Result<&'c mut wrap::Context<F, OS>> {
         ctx.absorb(&self.frame_type)?.skip(&self.payload_frame_num)?;
-        self.content.wrap(store, &mut ctx)?;
+        self.content.wrap(store, ctx)?;
         Ok(ctx)
     }
 }
@@ -146,11 +146,11 @@ where
     fn unwrap<'c, IS: io::IStream>(
         &mut self,
         store: &Store,
-        mut ctx: &'c mut unwrap::Context<F, IS>,
+        ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
         ctx.absorb(&mut self.frame_type)?.skip(&mut self.payload_frame_num)?;
         payload_frame_num_check(&self.payload_frame_num)?;
-        self.content.unwrap(&store, &mut ctx)?;
+        self.content.unwrap(store, ctx)?;
         Ok(ctx)
     }
 }
@@ -50,13 +50,16 @@ where
     HDF<Link>: ContentWrap<F, Store>,
     Content: ContentWrap<F, Store>,
 {
+    /// Size of the binary message body.
+    pub fn size(&self) -> Result<usize> {
+        let mut ctx = sizeof::Context::<F>::new();
+        self.header.sizeof(&mut ctx)?;
+        self.content.sizeof(&mut ctx)?;
+        Ok(ctx.get_size())
+    }
+
     pub fn wrap(&self) -> Result<WrappedMessage<F, Link>> {
-        let buf_size = {
-            let mut ctx = sizeof::Context::<F>::new();
-            self.header.sizeof(&mut ctx)?;
-            self.content.sizeof(&mut ctx)?;
-            ctx.get_size()
-        };
+        let buf_size = self.size()?;
 
         let mut buf = vec![0; buf_size];
 
@@ -73,7 +76,7 @@ where
         Ok(WrappedMessage {
             wrapped: WrapState {
                 link: self.header.link.clone(),
-                spongos: spongos,
+                spongos,
             },
             message: BinaryMessage {
                 link: self.header.link.clone(),
@@ -31,11 +31,11 @@ impl<'a, F, Link> PreparsedMessage<'a, F, Link> {
         F: PRP,
     {
         let mut pcf = pcf::PCF::default_with_content(content);
-        pcf.unwrap(&store, &mut self.ctx)?;
+        pcf.unwrap(store, &mut self.ctx)?;
         // Discard what's left of `self.ctx.stream`
         Ok(UnwrappedMessage {
             link: self.header.link,
-            pcf: pcf,
+            pcf,
             spongos: self.ctx.spongos,
         })
     }
@@ -12,11 +12,6 @@ use iota_streams_core::{
 #[cfg(feature = "async")]
 use iota_streams_core::Errors::MessageNotUnique;
 
-#[cfg(feature = "async")]
-use atomic_refcell::AtomicRefCell;
-#[cfg(feature = "async")]
-use iota_streams_core::prelude::Arc;
-
 pub struct BucketTransport<Link, Msg> {
     bucket: HashMap<Link, Vec<Msg>>,
 }
@@ -41,11 +36,11 @@ where
 
 impl<Link, Msg> TransportOptions for BucketTransport<Link, Msg> {
     type SendOptions = ();
-    fn get_send_options(&self) -> () {}
+    fn get_send_options(&self) {}
     fn set_send_options(&mut self, _opt: ()) {}
 
     type RecvOptions = ();
-    fn get_recv_options(&self) -> () {}
+    fn get_recv_options(&self) {}
     fn set_recv_options(&mut self, _opt: ()) {}
 }
 
@@ -103,7 +98,7 @@ where
 
         let mut msgs = self.recv_messages(link).await?;
         if let Some(msg) = msgs.pop() {
-            try_or!(msgs.is_empty(), MessageNotUnique(link.to_string()));
+            try_or!(msgs.is_empty(), MessageNotUnique(link.to_string()))?;
             Ok(msg)
         } else {
             err!(MessageLinkNotFound(link.to_string()))?
@@ -15,7 +15,9 @@ use atomic_refcell::AtomicRefCell;
 use iota_streams_core::prelude::{Arc, Box, };
 
 use iota_streams_core::prelude::Rc;
-use iota_streams_core::prelude::{Vec, string::ToString};
+use iota_streams_core::prelude::Vec;
+#[cfg(not(feature = "async"))]
+use iota_streams_core::prelude::string::ToString;
 
 pub trait TransportOptions {
     type SendOptions;
@@ -80,18 +82,18 @@ where
 impl<Tsp: TransportOptions> TransportOptions for Rc<RefCell<Tsp>> {
     type SendOptions = <Tsp as TransportOptions>::SendOptions;
     fn get_send_options(&self) -> Self::SendOptions {
-        (&*self).borrow().get_send_options()
+        (*self).borrow().get_send_options()
     }
     fn set_send_options(&mut self, opt: Self::SendOptions) {
-        (&*self).borrow_mut().set_send_options(opt)
+        (*self).borrow_mut().set_send_options(opt)
     }
 
     type RecvOptions = <Tsp as TransportOptions>::RecvOptions;
     fn get_recv_options(&self) -> Self::RecvOptions {
-        (&*self).borrow().get_recv_options()
+        (*self).borrow().get_recv_options()
     }
     fn set_recv_options(&mut self, opt: Self::RecvOptions) {
-        (&*self).borrow_mut().set_recv_options(opt)
+        (*self).borrow_mut().set_recv_options(opt)
     }
 }
 
@@ -99,7 +101,7 @@ impl<Tsp: TransportOptions> TransportOptions for Rc<RefCell<Tsp>> {
 impl<Link: Debug + Display, Msg, Tsp: Transport<Link, Msg>> Transport<Link, Msg> for Rc<RefCell<Tsp>> {
     /// Send a message.
     fn send_message(&mut self, msg: &Msg) -> Result<()> {
-        match (&*self).try_borrow_mut() {
+        match (*self).try_borrow_mut() {
             Ok(mut tsp) => tsp.send_message(msg),
             Err(err) => Err(wrapped_err!(TransportNotAvailable, WrappedError(err))),
         }
@@ -107,7 +109,7 @@ impl<Link: Debug + Display, Msg, Tsp: Transport<Link, Msg>> Transport<Link, Msg>
 
     /// Receive messages with default options.
     fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
-        match (&*self).try_borrow_mut() {
+        match (*self).try_borrow_mut() {
             Ok(mut tsp) => tsp.recv_messages(link),
             Err(err) => Err(wrapped_err!(TransportNotAvailable, WrappedError(err))),
         }
@@ -115,7 +117,7 @@ impl<Link: Debug + Display, Msg, Tsp: Transport<Link, Msg>> Transport<Link, Msg>
 
     /// Receive a message with default options.
     fn recv_message(&mut self, link: &Link) -> Result<Msg> {
-        match (&*self).try_borrow_mut() {
+        match (*self).try_borrow_mut() {
             Ok(mut tsp) => tsp.recv_message(link),
             Err(err) => Err(wrapped_err!(TransportNotAvailable, WrappedError(err))),
         }
@@ -134,18 +136,18 @@ pub fn new_shared_transport<T>(tsp: T) -> Rc<RefCell<T>> {
 impl<Tsp: TransportOptions> TransportOptions for Arc<AtomicRefCell<Tsp>> {
     type SendOptions = <Tsp as TransportOptions>::SendOptions;
     fn get_send_options(&self) -> Self::SendOptions {
-        (&*self).borrow().get_send_options()
+        (*self).borrow().get_send_options()
     }
     fn set_send_options(&mut self, opt: Self::SendOptions) {
-        (&*self).borrow_mut().set_send_options(opt)
+        (*self).borrow_mut().set_send_options(opt)
     }
 
     type RecvOptions = <Tsp as TransportOptions>::RecvOptions;
     fn get_recv_options(&self) -> Self::RecvOptions {
-        (&*self).borrow().get_recv_options()
+        (*self).borrow().get_recv_options()
     }
     fn set_recv_options(&mut self, opt: Self::RecvOptions) {
-        (&*self).borrow_mut().set_recv_options(opt)
+        (*self).borrow_mut().set_recv_options(opt)
     }
 }
 
@@ -185,8 +187,12 @@ pub fn new_shared_transport<T>(tsp: T) -> Arc<AtomicRefCell<T>> {
 
 mod bucket;
 pub use bucket::BucketTransport;
+#[cfg(not(feature = "async"))]
 use core::fmt::{Debug, Display};
-use iota_streams_core::{try_or, err, wrapped_err, WrappedError, LOCATION_LOG};
+use iota_streams_core::try_or;
+#[cfg(not(feature = "async"))]
+use iota_streams_core::{err, wrapped_err, WrappedError, LOCATION_LOG};
+#[cfg(not(feature = "async"))]
 use iota_streams_core::Errors::{MessageNotUnique, MessageLinkNotFound, TransportNotAvailable};
 
 #[cfg(feature = "tangle")]
@@ -21,6 +21,7 @@ use iota_streams_core::{
         Vec,
         Box,
     },
+    psk,
     sponge::{
         prp::PRP,
         spongos::Spongos,
@@ -64,14 +65,14 @@ impl<F> LinkedMessage<TangleAddress> for TangleMessage<F> {
 }
 
 // TODO: Use better feature to detect `chrono::Utc::new()`.
-#[cfg(all(feature = "std"))]//, not(feature = "wasmbind")
+#[cfg(feature = "std")]//, not(feature = "wasmbind")
 //#[cfg(all(feature = "std"))]
 impl<F> TangleMessage<F> {
     /// Create TangleMessage from BinaryMessage and add the current timestamp.
     pub fn new(msg: BinaryMessage<F, TangleAddress>) -> Self {
         Self {
             binary: msg,
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            timestamp: timestamp_now(),
         }
     }
 }
@@ -117,6 +118,18 @@ impl<F> TangleMessage<F> {
     }
 }
 
+/// Current time in milliseconds since Unix epoch as used in message timestamps.
+#[cfg(feature = "std")]
+pub fn timestamp_now() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+/// Clock is not available without `std`, timestamps are zero.
+#[cfg(not(feature = "std"))]
+pub fn timestamp_now() -> u64 {
+    0_u64
+}
+
 impl<F> TangleMessage<F> {
     /// Create TangleMessage from BinaryMessage and an explicit timestamp.
     pub fn with_timestamp(msg: BinaryMessage<F, TangleAddress>, timestamp: u64) -> Self {
@@ -124,31 +137,33 @@ impl<F> TangleMessage<F> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TangleAddress {
     pub appinst: AppInst,
     pub msgid: MsgId,
 }
 
 impl TangleAddress {
+    #[allow(clippy::result_unit_err)]
     pub fn from_str(appinst_str: &str, msgid_str: &str) -> Result<Self, ()> {
         let appinst = AppInst::from_str(appinst_str)?;
         let msgid = MsgId::from_str(msgid_str)?;
         Ok(TangleAddress { appinst, msgid })
     }
 
-    pub fn from_c_str(c_addr: *const c_char) -> *const Self {
-        unsafe {
-            c_addr.as_ref().map_or(null(), |c_addr|
-                CStr::from_ptr(c_addr).to_str().map_or(null(), |addr_str| {
-                    let addr_vec: Vec<&str> = addr_str.split(":").collect();
-                    Self::from_str(addr_vec[0], addr_vec[1])
-                        .map_or(null(), |addr|
-                            Box::into_raw(Box::new(addr))
-                        )
-                })
-            )
-        }
+    /// # Safety
+    ///
+    /// `c_addr` must be null or point to a nul-terminated string.
+    pub unsafe fn from_c_str(c_addr: *const c_char) -> *const Self {
+        c_addr.as_ref().map_or(null(), |c_addr|
+            CStr::from_ptr(c_addr).to_str().map_or(null(), |addr_str| {
+                let addr_vec: Vec<&str> = addr_str.split(':').collect();
+                Self::from_str(addr_vec[0], addr_vec[1])
+                    .map_or(null(), |addr|
+                        Box::into_raw(Box::new(addr))
+                    )
+            })
+        )
     }
 
 }
@@ -165,15 +180,6 @@ impl fmt::Display for TangleAddress {
     }
 }
 
-impl Default for TangleAddress {
-    fn default() -> Self {
-        Self {
-            appinst: AppInst::default(),
-            msgid: MsgId::default(),
-        }
-    }
-}
-
 impl PartialEq for TangleAddress {
     fn eq(&self, other: &Self) -> bool {
         self.appinst == other.appinst && self.msgid == other.msgid
@@ -239,8 +245,8 @@ impl<F: PRP> DefaultTangleLinkGenerator<F> {
         let mut s = Spongos::<F>::init();
         s.absorb(self.addr.appinst.id.as_ref());
         s.absorb(cursor.link.id.as_ref());
-        s.absorb(&cursor.branch_no.to_be_bytes());
-        s.absorb(&cursor.seq_no.to_be_bytes());
+        s.absorb(cursor.branch_no.to_be_bytes());
+        s.absorb(cursor.seq_no.to_be_bytes());
         s.commit();
         let mut new = MsgId::default();
         s.squeeze(new.id.as_mut());
@@ -251,8 +257,8 @@ impl<F: PRP> DefaultTangleLinkGenerator<F> {
         s.absorb(self.addr.appinst.id.as_ref());
         s.absorb(pk.as_ref());
         s.absorb(cursor.link.id.as_ref());
-        s.absorb(&cursor.branch_no.to_be_bytes());
-        s.absorb(&cursor.seq_no.to_be_bytes());
+        s.absorb(cursor.branch_no.to_be_bytes());
+        s.absorb(cursor.seq_no.to_be_bytes());
         s.commit();
         let mut new = MsgId::default();
         s.squeeze(new.id.as_mut());
@@ -267,6 +273,12 @@ impl<F: PRP> LinkGenerator<TangleAddress> for DefaultTangleLinkGenerator<F> {
         self.addr.msgid = self.gen_msgid(pk, Cursor::default().as_ref());
     }
 
+    /// Used by Author to generate a hidden application instance, only pre-shared key holders can derive it
+    fn gen_hidden(&mut self, psk: &psk::Psk, channel_idx: u64) {
+        self.addr.appinst = AppInst::from_psk::<F>(psk, channel_idx);
+        self.addr.msgid = self.gen_uniform_msgid(Cursor::default().as_ref());
+    }
+
     /// Used by Author to get announcement message id, it's just stored internally by link generator
     fn get(&self) -> TangleAddress {
         self.addr.clone()
@@ -311,12 +323,23 @@ impl AppInst {
         id[..32].copy_from_slice(pk.as_bytes());
         id[32..].copy_from_slice(&channel_idx.to_be_bytes());
         Self {
-            id: unsafe { core::mem::transmute(id) },
+            id: unsafe { core::mem::transmute::<[u8; APPINST_SIZE], NBytes<AppInstSize>>(id) },
         }
     }
+
+    /// Hidden application instance identifier, pseudo-randomly derived from a pre-shared key.
+    pub fn from_psk<F: PRP>(psk: &psk::Psk, channel_idx: u64) -> Self {
+        let mut s = Spongos::<F>::init();
+        s.absorb(&psk[..]);
+        s.absorb(channel_idx.to_be_bytes());
+        s.commit();
+        let mut appinst = Self::default();
+        s.squeeze(appinst.id.as_mut());
+        appinst
+    }
 }
 
-impl<'a> From<&'a [u8]> for AppInst {
+impl From<&[u8]> for AppInst {
     fn from(v: &[u8]) -> AppInst {
         AppInst {
             // TODO: Implement safer TryFrom or force check for length at call site.
@@ -424,7 +447,7 @@ pub struct MsgId {
     pub(crate) id: NBytes<MsgIdSize>,
 }
 
-impl<'a> From<&'a [u8]> for MsgId {
+impl From<&[u8]> for MsgId {
     fn from(v: &[u8]) -> MsgId {
         MsgId {
             // TODO: Implement safer TryFrom or force check for length at call site.
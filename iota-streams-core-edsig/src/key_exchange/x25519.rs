@@ -72,7 +72,7 @@ impl<'a> From<&'a mut PublicKey> for &'a mut PublicKeyWrap {
 pub type Pks = HashSet<PublicKeyWrap>;
 pub type IPk<'a> = &'a PublicKey;
 
-pub fn filter_ke_pks<'a>(allowed_pks: &'a Pks, target_pks: &'a Vec<PublicKey>) -> Vec<IPk<'a>> {
+pub fn filter_ke_pks<'a>(allowed_pks: &'a Pks, target_pks: &'a [PublicKey]) -> Vec<IPk<'a>> {
     target_pks
         .iter()
         .filter_map(|pk| allowed_pks.get(pk.into()).map(|pk| &pk.0))
@@ -81,7 +81,7 @@ pub fn filter_ke_pks<'a>(allowed_pks: &'a Pks, target_pks: &'a Vec<PublicKey>) -
 
 #[cfg(test)]
 mod tests {
-    struct FixedRng(Vec<u8>);
+    struct FixedRng([u8; 64]);
     impl rand::RngCore for FixedRng {
         fn next_u32(&mut self) -> u32 {
             7
@@ -105,15 +105,15 @@ mod tests {
     {
         let ed_kp = super::ed25519::Keypair::generate(rng);
         let x_kp = super::keypair_from_ed25519(&ed_kp);
-        let x_pk2 = super::public_from_ed25519(&ed_kp.public);
+        let x_pk2 = super::public_from_ed25519(&ed_kp.public).unwrap();
         assert!(x_kp.1.as_bytes() == x_pk2.as_bytes());
     }
 
     #[test]
     fn test_25519_fixed() {
-        let mut rng = FixedRng(vec![7_u8; 64]);
+        let mut rng = FixedRng([7_u8; 64]);
         test_25519(&mut rng);
-        rng = FixedRng(vec![11_u8; 64]);
+        rng = FixedRng([11_u8; 64]);
         test_25519(&mut rng);
     }
 
@@ -10,17 +10,11 @@ use iota_streams_core::{
 };
 use keccak;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct KeccakF1600 {
     state: [u64; 25],
 }
 
-impl Default for KeccakF1600 {
-    fn default() -> Self {
-        Self { state: [0u64; 25] }
-    }
-}
-
 impl KeccakF1600 {
     pub fn permutation(&mut self) {
         keccak::f1600(&mut self.state);
@@ -51,7 +45,7 @@ impl PRP for KeccakF1600 {
     fn from_inner(inner: &GenericArray<u8, Self::CapacitySize>) -> Self {
         let mut state = [0_u64; 25];
         let i = unsafe { &mut *(state.as_mut_ptr().add(21) as *mut GenericArray<u8, Self::CapacitySize>) };
-        *i = inner.clone();
+        *i = *inner;
         Self { state }
     }
 }
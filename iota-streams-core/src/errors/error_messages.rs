@@ -108,8 +108,12 @@ pub enum Errors {
     InvalidBitReservation,
     #[error("Message is not an announcement (found: {0})")]
     NotAnnouncement(u8),
+    #[error("Message is not a signed packet (found: {0})")]
+    NotSignedPacket(u8),
     #[error("Message info provided is not registered (found: {0})")]
     BadMessageInfo(u8),
+    #[error("Role provided is not registered (found: {0})")]
+    BadRole(u8),
     #[error("Failed to make message")]
     MessageCreationFailure,
 
@@ -133,6 +137,62 @@ pub enum Errors {
     SeqNumRetrievalFailure,
     #[error("State store has failed to retrieve")]
     StateStoreFailure,
+    #[error("Key tree has no free leaves (capacity: {0})")]
+    KeyTreeFull(usize),
+    #[error("Member not found in key tree")]
+    KeyTreeMemberNotFound,
+    #[error("Key tree has no members")]
+    KeyTreeEmpty,
+    #[error("Subscriber public key not found in user instance")]
+    SubscriberNotFound,
+    #[error("Subscription is not pending approval")]
+    SubscriptionNotPending,
+    #[error("Subscription callback is not restored with imported state, subscriber is kept pending")]
+    SubscriptionCallbackNotRestored,
+    #[error("Subscription policy provided is not registered (found: {0})")]
+    BadSubscriptionPolicy(u8),
+    #[error("Rekey is due, but packet is not linked to the branch of the latest keyload for everyone: {0}")]
+    RekeyBranchNotEveryone(String),
+    #[error("Publisher public key has not been approved")]
+    PublisherNotApproved,
+    #[error("Role assignment is not signed by Author")]
+    RoleAssignerNotAuthorized,
+    #[error("Publisher has no write permission")]
+    WritePermissionDenied,
+    #[error("Certificate is not issued by Author")]
+    CertificateIssuerNotAuthorized,
+    #[error("Publisher has no valid certificate")]
+    PublisherNotCertified,
+    #[error("Public key is not an administrator of the channel")]
+    AdminNotFound,
+    #[error("Keyload is not signed by Author or an administrator")]
+    KeyloadSignerNotAdmin,
+    #[error("Private keyload is signed by an unknown participant")]
+    KeyloadSignerUnknown,
+    #[error("Administrator set is not signed by Author")]
+    AdminSetIssuerNotAuthorized,
+    #[error("Channel is not announced with administrators (FLAG_ADMINS_MASK)")]
+    AdminsNotAnnounced,
+    #[error("Pre-shared key not found in user instance")]
+    PskNotFound,
+    #[error("Hidden announcement can't be unmasked with any of the known pre-shared keys")]
+    HiddenAnnouncementKeyNotFound,
+    #[error("Topic is not declared by Author")]
+    TopicCreatorNotAuthorized,
+    #[error("Topic already exists: {0}")]
+    TopicAlreadyExists(String),
+    #[error("Topic not found: {0}")]
+    TopicNotFound(String),
+    #[error("Topics are separate branches, channel is not multi-branching")]
+    TopicsNotMultiBranching,
+    #[error("Auditors can only be added by Author before the channel is announced")]
+    AuditorsAlreadyAnnounced,
+    #[error("Proof version not supported (expected: {0}, found: {1})")]
+    ProofVersionNotSupported(u8, u8),
+    #[error("Signed packet is not signed with the public key of the proof")]
+    ProofSignerMismatch,
+    #[error("Signed packet is not signed by the expected publisher")]
+    ProofPublisherMismatch,
 
 
     //////////
@@ -146,6 +206,21 @@ pub enum Errors {
     UserVersionRecoveryFailure(u8, u8),
     #[error("Recovered flag does not match expected: flag (expected: {0}, found: {1})")]
     UserFlagRecoveryFailure(u8, u8),
+    #[error("Export key derivation function is not supported (found: {0})")]
+    ExportKdfNotSupported(u8),
+    #[error("Export key derivation failed: {0}")]
+    ExportKeyDerivationFailure(String),
+    #[error("Export key derivation parameters are out of bounds (found: m_cost {0}, t_cost {1}, p_cost {2})")]
+    ExportKdfParamsOutOfBounds(u32, u32, u32),
+
+
+    //////////
+    // Storage
+    //////////
+    #[error("Storage operation failed: {0}")]
+    StorageFailure(String),
+    #[error("Checkpoint record not found in storage: {0}")]
+    CheckpointNotFound(String),
 
 
     //////////
@@ -151,7 +151,7 @@ impl<G: PRP> Rng<G> {
     }
     fn inc(&mut self) -> bool {
         for i in self.nonce.iter_mut() {
-            *i = *i + 1;
+            *i += 1;
             if *i != 0 {
                 return true;
             }
@@ -75,6 +75,6 @@ impl<F: PRP> From<Inner<F>> for GenericArray<u8, F::CapacitySize> {
 
 impl<'a, F: PRP> From<&'a Inner<F>> for &'a GenericArray<u8, F::CapacitySize> {
     fn from(inner: &'a Inner<F>) -> Self {
-        &(*inner).inner
+        &inner.inner
     }
 }
@@ -1,4 +1,5 @@
 mod inner;
 pub use inner::Inner;
+#[allow(clippy::module_inception)]
 mod prp;
 pub use prp::PRP;
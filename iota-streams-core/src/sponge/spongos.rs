@@ -335,15 +335,15 @@ impl<F: PRP> From<&Inner<F>> for Spongos<F> {
     }
 }
 
-impl<F: PRP> Into<Inner<F>> for Spongos<F> {
-    fn into(self) -> Inner<F> {
-        self.to_inner().unwrap()
+impl<F: PRP> From<Spongos<F>> for Inner<F> {
+    fn from(val: Spongos<F>) -> Self {
+        val.to_inner().unwrap()
     }
 }
 
-impl<F: PRP> Into<Inner<F>> for &Spongos<F> {
-    fn into(self) -> Inner<F> {
-        self.to_inner().unwrap()
+impl<F: PRP> From<&Spongos<F>> for Inner<F> {
+    fn from(val: &Spongos<F>) -> Self {
+        val.to_inner().unwrap()
     }
 }
 
@@ -10,7 +10,7 @@ use crate::prelude::{
 fn bytes_spongosn<F: PRP>(n: usize)
 {
     let mut rng = Spongos::<F>::init();
-    rng.absorb(&vec![0; 10]);
+    rng.absorb(vec![0; 10]);
     rng.commit();
     let k = rng.squeeze_n(n);
     let p = rng.squeeze_n(n);
@@ -140,7 +140,7 @@ pub fn encrypt_decrypt_n<F: PRP>(n: usize)
 {
     let rate = F::RateSize::USIZE;
     let mut s = Spongos::<F>::init();
-    s.absorb(&vec![1; 32]);
+    s.absorb(vec![1; 32]);
     s.commit();
 
     let x = s.clone().squeeze_n(n);
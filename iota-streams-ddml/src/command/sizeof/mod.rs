@@ -8,6 +8,12 @@ pub struct Context<F> {
     _phantom: core::marker::PhantomData<F>,
 }
 
+impl<F> Default for Context<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<F> Context<F> {
     /// Creates a new Context<F>.
     pub fn new() -> Self {
@@ -37,17 +43,3 @@ mod squeeze_external;
 mod ed25519;
 mod x25519;
 
-pub use absorb::*;
-pub use absorb_external::*;
-pub use commit::*;
-pub use dump::*;
-pub use fork::*;
-pub use join::*;
-pub use mask::*;
-pub use repeated::*;
-pub use skip::*;
-pub use squeeze::*;
-pub use squeeze_external::*;
-
-pub use ed25519::*;
-pub use x25519::*;
@@ -1,18 +1,14 @@
-use std::str::FromStr;
-
 use iota_streams_core::{
     prelude::{
         Vec,
         typenum::{U32, U64, },
         string::ToString,
     },
-    prng, sponge::{
-        prp::PRP,
-        spongos::Spongos,
-    },
+    prng, sponge::prp::PRP,
     try_or,
     Result,
-    Errors::*
+    Errors::*,
+    LOCATION_LOG,
 };
 use iota_streams_core_edsig::{
     key_exchange::x25519,
@@ -26,7 +22,7 @@ use crate::{
 };
 
 fn absorb_mask_u8<F: PRP>() -> Result<()> {
-    let mut buf = vec![0_u8; 2];
+    let mut buf = [0_u8; 2];
     let mut tag_wrap = External(NBytes::<U32>::default());
     let mut tag_unwrap = External(NBytes::<U32>::default());
 
@@ -61,11 +57,11 @@ fn absorb_mask_u8<F: PRP>() -> Result<()> {
             )?;
         }
 
-        try_or!(t == t2, ValueMismatch(t as usize, t2 as usize))?;
-        try_or!(t == t3, ValueMismatch(t as usize, t3 as usize))?;
+        try_or!(t == t2, ValueMismatch(t.0 as usize, t2.0 as usize))?;
+        try_or!(t == t3, ValueMismatch(t.0 as usize, t3.0 as usize))?;
         try_or!(
             tag_wrap == tag_unwrap,
-            InvalidTagSqueeze(tag_wrap.to_string(), tag_unwrap.to_string())
+            InvalidTagSqueeze(tag_wrap.0.to_string(), tag_unwrap.0.to_string())
         )?;
     }
     Ok(())
@@ -118,7 +114,7 @@ fn absorb_mask_size<F: PRP>() -> Result<()> {
         try_or!(s == s3, ValueMismatch(s.0, s3.0))?;
         try_or!(
             tag_wrap == tag_unwrap,
-            InvalidTagSqueeze(tag_wrap.to_string(), tag_unwrap.to_string())
+            InvalidTagSqueeze(tag_wrap.0.to_string(), tag_unwrap.0.to_string())
         )?;
     }
     Ok(())
@@ -136,7 +132,7 @@ fn absorb_mask_squeeze_bytes_mac<F: PRP>() -> Result<()> {
     let mut tag_unwrap = External(NBytes::<U32>::default());
 
     let prng = prng::dbg_init_str::<F>("TESTPRNGKEY");
-    let mut nonce = "TESTPRNGNONCE".as_bytes().to_vec();
+    let nonce = "TESTPRNGNONCE".as_bytes().to_vec();
 
     for n in NS.iter() {
         let ta = Bytes(prng.gen_n(&nonce, *n));
@@ -228,7 +224,7 @@ fn absorb_mask_squeeze_bytes_mac<F: PRP>() -> Result<()> {
         // try_or!(ents == ents2, "Invalid unwrapped ents value: {:?} != {:?}", ents, ents2);
         try_or!(
             tag_wrap == tag_unwrap,
-            InvalidTagSqueeze(tag_wrap.to_string(), tag_unwrap.to_string())
+            InvalidTagSqueeze(tag_wrap.0.to_string(), tag_unwrap.0.to_string())
         )?;
     }
 
@@ -241,7 +237,6 @@ fn bytes() {
 }
 
 fn absorb_ed25519<F: PRP>() -> Result<()> {
-    type N = U64;
     let secret = ed25519::SecretKey::from_bytes(&[7; ed25519::SECRET_KEY_LENGTH]).unwrap();
     let public = ed25519::PublicKey::from(&secret);
     let kp = ed25519::Keypair { secret, public };
@@ -358,8 +353,8 @@ fn x25519_static<F: PRP>() -> Result<()> {
 }
 
 fn x25519_ephemeral<F: PRP>() -> Result<()> {
-    let secret_a = x25519::EphemeralSecret::new(&mut rand::thread_rng());
-    let secret_b = x25519::EphemeralSecret::new(&mut rand::thread_rng());
+    let secret_a = x25519::EphemeralSecret::new(rand::thread_rng());
+    let secret_b = x25519::EphemeralSecret::new(rand::thread_rng());
     let public_a = x25519::PublicKey::from(&secret_a);
     let public_b = x25519::PublicKey::from(&secret_b);
     let mut public_b2 = x25519::PublicKey::from([0_u8; 32]);
@@ -410,7 +405,7 @@ fn x25519_ephemeral<F: PRP>() -> Result<()> {
 }
 
 fn x25519_transport<F: PRP>() -> Result<()> {
-    let secret_a = x25519::StaticSecret::new(&mut rand::thread_rng());
+    let secret_a = x25519::StaticSecret::new(rand::thread_rng());
     let public_a = x25519::PublicKey::from(&secret_a);
 
     let key = NBytes::<U32>::default();
@@ -45,7 +45,7 @@ impl<F, IS> AsMut<Context<F, IS>> for AbsorbExternalContext<F, IS> {
 
 impl<F: PRP, IS: io::IStream> Wrap for AbsorbExternalContext<F, IS> {
     fn wrap_u8(&mut self, u: u8) -> Result<&mut Self> {
-        self.ctx.spongos.absorb(&[u]);
+        self.ctx.spongos.absorb([u]);
         Ok(self)
     }
     fn wrapn(&mut self, bytes: &[u8]) -> Result<&mut Self> {
@@ -54,34 +54,34 @@ impl<F: PRP, IS: io::IStream> Wrap for AbsorbExternalContext<F, IS> {
     }
 }
 
-fn wrap_absorb_external_u8<'a, F: PRP, IS: io::IStream>(
-    ctx: &'a mut AbsorbExternalContext<F, IS>,
+fn wrap_absorb_external_u8<F: PRP, IS: io::IStream>(
+    ctx: &mut AbsorbExternalContext<F, IS>,
     u: Uint8,
-) -> Result<&'a mut AbsorbExternalContext<F, IS>> {
+) -> Result<&mut AbsorbExternalContext<F, IS>> {
     ctx.wrap_u8(u.0)
 }
-fn wrap_absorb_external_u16<'a, F: PRP, IS: io::IStream>(
-    ctx: &'a mut AbsorbExternalContext<F, IS>,
+fn wrap_absorb_external_u16<F: PRP, IS: io::IStream>(
+    ctx: &mut AbsorbExternalContext<F, IS>,
     u: Uint16,
-) -> Result<&'a mut AbsorbExternalContext<F, IS>> {
+) -> Result<&mut AbsorbExternalContext<F, IS>> {
     ctx.wrap_u16(u.0)
 }
-fn wrap_absorb_external_u32<'a, F: PRP, IS: io::IStream>(
-    ctx: &'a mut AbsorbExternalContext<F, IS>,
+fn wrap_absorb_external_u32<F: PRP, IS: io::IStream>(
+    ctx: &mut AbsorbExternalContext<F, IS>,
     u: Uint32,
-) -> Result<&'a mut AbsorbExternalContext<F, IS>> {
+) -> Result<&mut AbsorbExternalContext<F, IS>> {
     ctx.wrap_u32(u.0)
 }
-fn wrap_absorb_external_u64<'a, F: PRP, IS: io::IStream>(
-    ctx: &'a mut AbsorbExternalContext<F, IS>,
+fn wrap_absorb_external_u64<F: PRP, IS: io::IStream>(
+    ctx: &mut AbsorbExternalContext<F, IS>,
     u: Uint64,
-) -> Result<&'a mut AbsorbExternalContext<F, IS>> {
+) -> Result<&mut AbsorbExternalContext<F, IS>> {
     ctx.wrap_u64(u.0)
 }
-fn wrap_absorb_external_size<'a, F: PRP, IS: io::IStream>(
-    ctx: &'a mut AbsorbExternalContext<F, IS>,
+fn wrap_absorb_external_size<F: PRP, IS: io::IStream>(
+    ctx: &mut AbsorbExternalContext<F, IS>,
     size: Size,
-) -> Result<&'a mut AbsorbExternalContext<F, IS>> {
+) -> Result<&mut AbsorbExternalContext<F, IS>> {
     ctx.wrap_size(size)
 }
 fn wrap_absorb_external_bytes<'a, F: PRP, IS: io::IStream>(
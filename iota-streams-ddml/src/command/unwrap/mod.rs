@@ -22,7 +22,7 @@ impl<F: PRP, IS> Context<F, IS> {
     pub fn new(stream: IS) -> Self {
         Self {
             spongos: Spongos::<F>::init(),
-            stream: stream,
+            stream,
         }
     }
 }
@@ -54,6 +54,7 @@ where
     }
 }
 
+#[allow(clippy::module_inception)]
 mod unwrap;
 
 mod absorb;
@@ -72,18 +73,3 @@ mod squeeze_external;
 mod ed25519;
 mod x25519;
 
-pub use absorb::*;
-pub use absorb_external::*;
-pub use commit::*;
-pub use dump::*;
-pub use fork::*;
-pub use guard::*;
-pub use join::*;
-pub use mask::*;
-pub use repeated::*;
-pub use skip::*;
-pub use squeeze::*;
-pub use squeeze_external::*;
-
-pub use ed25519::*;
-pub use x25519::*;
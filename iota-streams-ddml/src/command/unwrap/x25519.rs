@@ -25,7 +25,7 @@ impl<'a, F: PRP, IS: io::IStream> X25519<&'a x25519::StaticSecret, &'a x25519::P
     }
 }
 
-impl<'a, F: PRP, IS: io::IStream> X25519<x25519::EphemeralSecret, &'a x25519::PublicKey> for Context<F, IS> {
+impl<F: PRP, IS: io::IStream> X25519<x25519::EphemeralSecret, &x25519::PublicKey> for Context<F, IS> {
     fn x25519(&mut self, sk: x25519::EphemeralSecret, pk: &x25519::PublicKey) -> Result<&mut Self> {
         let shared = sk.diffie_hellman(pk);
         self.spongos.absorb(shared.as_bytes());
@@ -55,34 +55,34 @@ impl<F: PRP, OS: io::OStream> Wrap for AbsorbContext<F, OS> {
     }
 }
 
-fn wrap_absorb_u8<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbContext<F, OS>,
+fn wrap_absorb_u8<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbContext<F, OS>,
     u: Uint8,
-) -> Result<&'a mut AbsorbContext<F, OS>> {
+) -> Result<&mut AbsorbContext<F, OS>> {
     ctx.wrap_u8(u.0)
 }
-fn wrap_absorb_u16<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbContext<F, OS>,
+fn wrap_absorb_u16<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbContext<F, OS>,
     u: Uint16,
-) -> Result<&'a mut AbsorbContext<F, OS>> {
+) -> Result<&mut AbsorbContext<F, OS>> {
     ctx.wrap_u16(u.0)
 }
-fn wrap_absorb_u32<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbContext<F, OS>,
+fn wrap_absorb_u32<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbContext<F, OS>,
     u: Uint32,
-) -> Result<&'a mut AbsorbContext<F, OS>> {
+) -> Result<&mut AbsorbContext<F, OS>> {
     ctx.wrap_u32(u.0)
 }
-fn wrap_absorb_u64<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbContext<F, OS>,
+fn wrap_absorb_u64<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbContext<F, OS>,
     u: Uint64,
-) -> Result<&'a mut AbsorbContext<F, OS>> {
+) -> Result<&mut AbsorbContext<F, OS>> {
     ctx.wrap_u64(u.0)
 }
-fn wrap_absorb_size<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbContext<F, OS>,
+fn wrap_absorb_size<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbContext<F, OS>,
     size: Size,
-) -> Result<&'a mut AbsorbContext<F, OS>> {
+) -> Result<&mut AbsorbContext<F, OS>> {
     ctx.wrap_size(size)
 }
 fn wrap_absorb_bytes<'a, F: PRP, OS: io::OStream>(
@@ -43,7 +43,7 @@ impl<F, OS> AsMut<Context<F, OS>> for AbsorbExternalContext<F, OS> {
 
 impl<F: PRP, OS: io::OStream> Wrap for AbsorbExternalContext<F, OS> {
     fn wrap_u8(&mut self, u: u8) -> Result<&mut Self> {
-        self.ctx.spongos.absorb(&[u]);
+        self.ctx.spongos.absorb([u]);
         Ok(self)
     }
     fn wrapn(&mut self, bytes: &[u8]) -> Result<&mut Self> {
@@ -52,34 +52,34 @@ impl<F: PRP, OS: io::OStream> Wrap for AbsorbExternalContext<F, OS> {
     }
 }
 
-fn wrap_absorb_external_u8<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbExternalContext<F, OS>,
+fn wrap_absorb_external_u8<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbExternalContext<F, OS>,
     u: Uint8,
-) -> Result<&'a mut AbsorbExternalContext<F, OS>> {
+) -> Result<&mut AbsorbExternalContext<F, OS>> {
     ctx.wrap_u8(u.0)
 }
-fn wrap_absorb_external_u16<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbExternalContext<F, OS>,
+fn wrap_absorb_external_u16<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbExternalContext<F, OS>,
     u: Uint16,
-) -> Result<&'a mut AbsorbExternalContext<F, OS>> {
+) -> Result<&mut AbsorbExternalContext<F, OS>> {
     ctx.wrap_u16(u.0)
 }
-fn wrap_absorb_external_u32<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbExternalContext<F, OS>,
+fn wrap_absorb_external_u32<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbExternalContext<F, OS>,
     u: Uint32,
-) -> Result<&'a mut AbsorbExternalContext<F, OS>> {
+) -> Result<&mut AbsorbExternalContext<F, OS>> {
     ctx.wrap_u32(u.0)
 }
-fn wrap_absorb_external_u64<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbExternalContext<F, OS>,
+fn wrap_absorb_external_u64<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbExternalContext<F, OS>,
     u: Uint64,
-) -> Result<&'a mut AbsorbExternalContext<F, OS>> {
+) -> Result<&mut AbsorbExternalContext<F, OS>> {
     ctx.wrap_u64(u.0)
 }
-fn wrap_absorb_external_size<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut AbsorbExternalContext<F, OS>,
+fn wrap_absorb_external_size<F: PRP, OS: io::OStream>(
+    ctx: &mut AbsorbExternalContext<F, OS>,
     size: Size,
-) -> Result<&'a mut AbsorbExternalContext<F, OS>> {
+) -> Result<&mut AbsorbExternalContext<F, OS>> {
     ctx.wrap_size(size)
 }
 fn wrap_absorb_external_bytes<'a, F: PRP, OS: io::OStream>(
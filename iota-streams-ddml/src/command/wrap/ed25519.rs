@@ -31,7 +31,7 @@ impl<F: PRP, OS: io::OStream> Ed25519<&ed25519::Keypair, &External<NBytes<U64>>>
         let context = "IOTAStreams".as_bytes();
         let mut prehashed = Prehashed::default();
         prehashed.0.as_mut_slice().copy_from_slice((hash.0).as_slice());
-        match kp.sign_prehashed(prehashed, Some(&context[..])) {
+        match kp.sign_prehashed(prehashed, Some(context)) {
             Ok(signature) => {
                 self.stream
                     .try_advance(ed25519::SIGNATURE_LENGTH)?
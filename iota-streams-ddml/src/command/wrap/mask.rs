@@ -53,34 +53,34 @@ impl<F: PRP, OS: io::OStream> Wrap for MaskContext<F, OS> {
     }
 }
 
-fn wrap_mask_u8<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut MaskContext<F, OS>,
+fn wrap_mask_u8<F: PRP, OS: io::OStream>(
+    ctx: &mut MaskContext<F, OS>,
     u: Uint8,
-) -> Result<&'a mut MaskContext<F, OS>> {
+) -> Result<&mut MaskContext<F, OS>> {
     ctx.wrap_u8(u.0)
 }
-fn wrap_mask_u16<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut MaskContext<F, OS>,
+fn wrap_mask_u16<F: PRP, OS: io::OStream>(
+    ctx: &mut MaskContext<F, OS>,
     u: Uint16,
-) -> Result<&'a mut MaskContext<F, OS>> {
+) -> Result<&mut MaskContext<F, OS>> {
     ctx.wrap_u16(u.0)
 }
-fn wrap_mask_u32<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut MaskContext<F, OS>,
+fn wrap_mask_u32<F: PRP, OS: io::OStream>(
+    ctx: &mut MaskContext<F, OS>,
     u: Uint32,
-) -> Result<&'a mut MaskContext<F, OS>> {
+) -> Result<&mut MaskContext<F, OS>> {
     ctx.wrap_u32(u.0)
 }
-fn wrap_mask_u64<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut MaskContext<F, OS>,
+fn wrap_mask_u64<F: PRP, OS: io::OStream>(
+    ctx: &mut MaskContext<F, OS>,
     u: Uint64,
-) -> Result<&'a mut MaskContext<F, OS>> {
+) -> Result<&mut MaskContext<F, OS>> {
     ctx.wrap_u64(u.0)
 }
-fn wrap_mask_size<'a, F: PRP, OS: io::OStream>(
-    ctx: &'a mut MaskContext<F, OS>,
+fn wrap_mask_size<F: PRP, OS: io::OStream>(
+    ctx: &mut MaskContext<F, OS>,
     size: Size,
-) -> Result<&'a mut MaskContext<F, OS>> {
+) -> Result<&mut MaskContext<F, OS>> {
     ctx.wrap_size(size)
 }
 fn wrap_mask_bytes<'a, F: PRP, OS: io::OStream>(
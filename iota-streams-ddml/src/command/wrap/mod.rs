@@ -14,11 +14,12 @@ impl<F: PRP, OS> Context<F, OS> {
     pub fn new(stream: OS) -> Self {
         Self {
             spongos: Spongos::<F>::init(),
-            stream: stream,
+            stream,
         }
     }
 }
 
+#[allow(clippy::module_inception)]
 mod wrap;
 pub(crate) use wrap::*;
 
@@ -38,18 +39,3 @@ mod squeeze_external;
 mod ed25519;
 mod x25519;
 
-pub use absorb::*;
-pub use absorb_external::*;
-pub use commit::*;
-pub use dump::*;
-pub use fork::*;
-pub use guard::*;
-pub use join::*;
-pub use mask::*;
-pub use repeated::*;
-pub use skip::*;
-pub use squeeze::*;
-pub use squeeze_external::*;
-
-pub use ed25519::*;
-pub use x25519::*;
@@ -13,12 +13,7 @@ where
     I: iter::Iterator,
     C: for<'a> FnMut(&'a mut Self, <I as iter::Iterator>::Item) -> Result<&'a mut Self>,
 {
-    fn repeated(&mut self, values_iter: I, mut value_handle: C) -> Result<&mut Self> {
-        values_iter.fold(Ok(self), |rctx, item| -> Result<&mut Self> {
-            match rctx {
-                Ok(ctx) => value_handle(ctx, item),
-                Err(e) => Err(e),
-            }
-        })
+    fn repeated(&mut self, mut values_iter: I, value_handle: C) -> Result<&mut Self> {
+        values_iter.try_fold(self, value_handle)
     }
 }
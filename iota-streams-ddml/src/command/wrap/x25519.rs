@@ -30,7 +30,7 @@ impl<'a, F: PRP, OS: io::OStream> X25519<&'a x25519::StaticSecret, &'a x25519::P
     }
 }
 
-impl<'a, F: PRP, OS: io::OStream> X25519<x25519::EphemeralSecret, &'a x25519::PublicKey> for Context<F, OS> {
+impl<F: PRP, OS: io::OStream> X25519<x25519::EphemeralSecret, &x25519::PublicKey> for Context<F, OS> {
     fn x25519(&mut self, sk: x25519::EphemeralSecret, pk: &x25519::PublicKey) -> Result<&mut Self> {
         let shared = sk.diffie_hellman(pk);
         self.spongos.absorb(shared.as_bytes());
@@ -41,7 +41,7 @@ impl<'a, F: PRP, OS: io::OStream> X25519<x25519::EphemeralSecret, &'a x25519::Pu
 #[cfg(feature = "std")]
 impl<'a, F: PRP, N: ArrayLength<u8>, OS: io::OStream> X25519<&'a x25519::PublicKey, &'a NBytes<N>> for Context<F, OS> {
     fn x25519(&mut self, pk: &x25519::PublicKey, key: &NBytes<N>) -> Result<&mut Self> {
-        let ephemeral_ke_sk = x25519::EphemeralSecret::new(&mut rand::thread_rng());
+        let ephemeral_ke_sk = x25519::EphemeralSecret::new(rand::thread_rng());
         let ephemeral_ke_pk = x25519::PublicKey::from(&ephemeral_ke_sk);
         self.absorb(&ephemeral_ke_pk)?
             .x25519(ephemeral_ke_sk, pk)?
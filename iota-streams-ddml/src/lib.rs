@@ -2,6 +2,8 @@
 //! DDML is implemented as an EDSL in rust.
 
 #![no_std]
+// Messages are prepared and unwrapped with their generic content types spelled out.
+#![allow(clippy::type_complexity)]
 
 #[cfg(not(feature = "std"))]
 #[macro_use]
@@ -1,10 +1,22 @@
 use iota_streams_core::Result;
-use core::hash;
+use core::{
+    cell::{
+        Cell,
+        RefCell,
+    },
+    hash,
+    time::Duration,
+};
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
 
 use iota_streams_core::{
     prelude::{
         Vec,
         HashMap,
+        HashSet,
         string::ToString
     },
     sponge::{
@@ -48,9 +60,21 @@ pub trait LinkStore<F, Link> {
 
     fn insert(&mut self, link: &Link, spongos: Inner<F>, info: Self::Info) -> Result<()> where F: PRP;
 
-    /// Remove link and associated info from the store.
+    /// Remove link and associated info from the store, removing a missing link is not an error.
     fn erase(&mut self, _link: &Link) {}
 
+    /// Remove link like `erase` but report failure, eg. of the storage the link is kept in.
+    fn try_erase(&mut self, link: &Link) -> Result<()> {
+        self.erase(link);
+        Ok(())
+    }
+
+    /// Links the store must not evict on its own, eg. the channel announcement. Replaces
+    /// the links pinned before. Stores without limits keep every link anyway.
+    fn pin(&mut self, _links: Vec<Link>) {}
+
+    /// Links kept by the store, eg. for export. Stores keeping links outside of memory
+    /// may iterate only the links kept in memory.
     fn iter(&self) -> Vec<(&Link, &(Inner<F>, Self::Info))> where F: PRP;
 }
 
@@ -178,3 +202,343 @@ where
         self.map.iter().collect()
     }
 }
+
+/// Default number of links kept by `LruLinkStore`.
+pub const DEFAULT_LRU_CAPACITY: usize = 1024;
+
+struct LruEntry<F: PRP, Info> {
+    value: (Inner<F>, Info),
+    used: Cell<u64>,
+}
+
+/// Link store keeping at most `capacity` links. When a link is inserted into a full store
+/// the least recently used link is evicted, both lookups and updates count as use.
+/// Messages joined to an evicted link can't be unwrapped anymore. Pinned links are never
+/// evicted, the store exceeds `capacity` if more links are pinned.
+pub struct LruLinkStore<F: PRP, Link, Info> {
+    map: HashMap<Link, LruEntry<F, Info>>,
+    // Links ordered by their last use, the least recently used first.
+    recency: RefCell<BTreeMap<u64, Link>>,
+    capacity: usize,
+    clock: Cell<u64>,
+    pinned: HashSet<Link>,
+}
+
+impl<F: PRP, Link, Info> LruLinkStore<F, Link, Info>
+where
+    Link: Eq + hash::Hash + Clone,
+{
+    /// Create store keeping at most `capacity` links, at least one link is always kept.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::new(),
+            recency: RefCell::new(BTreeMap::new()),
+            capacity: capacity.max(1),
+            clock: Cell::new(0),
+            pinned: HashSet::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change capacity evicting the least recently used links if the store is over it.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict();
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn tick(&self) -> u64 {
+        let t = self.clock.get() + 1;
+        self.clock.set(t);
+        t
+    }
+
+    fn touch(&self, link: &Link, entry: &LruEntry<F, Info>) {
+        let used = self.tick();
+        let mut recency = self.recency.borrow_mut();
+        recency.remove(&entry.used.get());
+        recency.insert(used, link.clone());
+        entry.used.set(used);
+    }
+
+    fn put(&mut self, link: &Link, value: (Inner<F>, Info)) {
+        let used = self.tick();
+        let recency = self.recency.get_mut();
+        if let Some(old) = self.map.insert(link.clone(), LruEntry { value, used: Cell::new(used) }) {
+            recency.remove(&old.used.get());
+        }
+        recency.insert(used, link.clone());
+        self.evict();
+    }
+
+    fn remove(&mut self, link: &Link) {
+        if let Some(entry) = self.map.remove(link) {
+            self.recency.get_mut().remove(&entry.used.get());
+        }
+    }
+
+    // Pinned links are skipped, there are only a few of them.
+    fn evict(&mut self) {
+        while self.capacity < self.map.len() {
+            let pinned = &self.pinned;
+            let lru = self
+                .recency
+                .get_mut()
+                .values()
+                .find(|link| !pinned.contains(*link))
+                .cloned();
+            match lru {
+                Some(link) => self.remove(&link),
+                None => break,
+            };
+        }
+    }
+}
+
+impl<F: PRP, Link, Info> Default for LruLinkStore<F, Link, Info>
+where
+    Link: Eq + hash::Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new(DEFAULT_LRU_CAPACITY)
+    }
+}
+
+impl<F: PRP, Link, Info> LinkStore<F, Link> for LruLinkStore<F, Link, Info>
+where
+    Link: Eq + hash::Hash + Clone + Display,
+    Info: Clone,
+{
+    type Info = Info;
+
+    fn lookup(&self, link: &Link) -> Result<(Spongos<F>, Info)> {
+        match self.map.get(link) {
+            Some(entry) => {
+                self.touch(link, entry);
+                let (inner, info) = &entry.value;
+                Ok((inner.into(), info.clone()))
+            }
+            None => err!(MessageLinkNotFoundInTangle(link.to_string()))
+        }
+    }
+
+    fn update(&mut self, link: &Link, spongos: Spongos<F>, info: Info) -> Result<()> {
+        let inner = spongos.to_inner()?;
+        self.put(link, (inner, info));
+        Ok(())
+    }
+
+    fn insert(&mut self, link: &Link, inner: Inner<F>, info: Self::Info) -> Result<()> {
+        self.put(link, (inner, info));
+        Ok(())
+    }
+
+    fn erase(&mut self, link: &Link) {
+        self.remove(link);
+    }
+
+    fn pin(&mut self, links: Vec<Link>) {
+        self.pinned = links.into_iter().collect();
+        self.evict();
+    }
+
+    fn iter(&self) -> Vec<(&Link, &(Inner<F>, Self::Info))> {
+        self.map.iter().map(|(link, entry)| (link, &entry.value)).collect()
+    }
+}
+
+/// Default number of the latest links kept by `AgedLinkStore`.
+pub const DEFAULT_MAX_MESSAGES: usize = 1024;
+
+/// Time elapsed since the UNIX epoch.
+#[cfg(feature = "std")]
+pub fn system_clock() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// There's no clock without std, time limit of `AgedLinkStore` takes effect
+/// only with a clock set by `AgedLinkStore::with_clock`.
+#[cfg(not(feature = "std"))]
+pub fn system_clock() -> Duration {
+    Duration::from_secs(0)
+}
+
+struct AgedEntry<F: PRP, Info> {
+    value: (Inner<F>, Info),
+    seq: u64,
+    time: Duration,
+}
+
+/// Link store evicting links put into the store more than `max_messages` links ago or
+/// earlier than `max_age` ago, either limit is optional. Expired links are not found
+/// by lookups and are removed on the next insertion. Pinned links never expire.
+pub struct AgedLinkStore<F: PRP, Link, Info> {
+    map: HashMap<Link, AgedEntry<F, Info>>,
+    seq: u64,
+    max_messages: Option<usize>,
+    max_age: Option<Duration>,
+    clock: fn() -> Duration,
+    pinned: HashSet<Link>,
+}
+
+impl<F: PRP, Link, Info> AgedLinkStore<F, Link, Info>
+where
+    Link: Eq + hash::Hash + Clone,
+{
+    /// Create store with the given limits, time is measured with `system_clock`.
+    ///
+    ///  # Arguments
+    ///  * `max_messages` - Number of the latest links kept
+    ///  * `max_age` - Time links are kept for
+    pub fn new(max_messages: Option<usize>, max_age: Option<Duration>) -> Self {
+        Self {
+            map: HashMap::new(),
+            seq: 0,
+            max_messages,
+            max_age,
+            clock: system_clock,
+            pinned: HashSet::new(),
+        }
+    }
+
+    /// Measure time with `clock` instead of `system_clock`.
+    pub fn with_clock(mut self, clock: fn() -> Duration) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn max_messages(&self) -> Option<usize> {
+        self.max_messages
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Change the message limit evicting the links which became expired.
+    pub fn set_max_messages(&mut self, max_messages: Option<usize>) {
+        self.max_messages = max_messages;
+        self.evict();
+    }
+
+    /// Change the time limit evicting the links which became expired.
+    pub fn set_max_age(&mut self, max_age: Option<Duration>) {
+        self.max_age = max_age;
+        self.evict();
+    }
+
+    /// Number of stored links, including the expired ones not evicted yet.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn is_expired(&self, link: &Link, entry: &AgedEntry<F, Info>, now: Duration) -> bool {
+        if self.pinned.contains(link) {
+            return false;
+        }
+        let too_old = self.max_messages.is_some_and(|n| n as u64 <= self.seq - entry.seq);
+        let too_late = self
+            .max_age
+            .is_some_and(|age| now.checked_sub(entry.time).is_some_and(|elapsed| age < elapsed));
+        too_old || too_late
+    }
+
+    fn put(&mut self, link: &Link, value: (Inner<F>, Info)) {
+        self.seq += 1;
+        let entry = AgedEntry {
+            value,
+            seq: self.seq,
+            time: (self.clock)(),
+        };
+        self.map.insert(link.clone(), entry);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        let now = (self.clock)();
+        let expired: Vec<Link> = self
+            .map
+            .iter()
+            .filter(|(link, entry)| self.is_expired(link, entry, now))
+            .map(|(link, _)| link.clone())
+            .collect();
+        for link in expired {
+            self.map.remove(&link);
+        }
+    }
+}
+
+impl<F: PRP, Link, Info> Default for AgedLinkStore<F, Link, Info>
+where
+    Link: Eq + hash::Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_MAX_MESSAGES), None)
+    }
+}
+
+impl<F: PRP, Link, Info> LinkStore<F, Link> for AgedLinkStore<F, Link, Info>
+where
+    Link: Eq + hash::Hash + Clone + Display,
+    Info: Clone,
+{
+    type Info = Info;
+
+    fn lookup(&self, link: &Link) -> Result<(Spongos<F>, Info)> {
+        match self.map.get(link) {
+            Some(entry) if !self.is_expired(link, entry, (self.clock)()) => {
+                let (inner, info) = &entry.value;
+                Ok((inner.into(), info.clone()))
+            }
+            _ => err!(MessageLinkNotFoundInTangle(link.to_string()))
+        }
+    }
+
+    fn update(&mut self, link: &Link, spongos: Spongos<F>, info: Info) -> Result<()> {
+        let inner = spongos.to_inner()?;
+        self.put(link, (inner, info));
+        Ok(())
+    }
+
+    fn insert(&mut self, link: &Link, inner: Inner<F>, info: Self::Info) -> Result<()> {
+        self.put(link, (inner, info));
+        Ok(())
+    }
+
+    fn erase(&mut self, link: &Link) {
+        self.map.remove(link);
+    }
+
+    fn pin(&mut self, links: Vec<Link>) {
+        self.pinned = links.into_iter().collect();
+        self.evict();
+    }
+
+    fn iter(&self) -> Vec<(&Link, &(Inner<F>, Self::Info))> {
+        let now = (self.clock)();
+        self.map
+            .iter()
+            .filter(|(link, entry)| !self.is_expired(link, entry, now))
+            .map(|(link, entry)| (link, &entry.value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test;
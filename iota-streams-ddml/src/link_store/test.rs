@@ -0,0 +1,163 @@
+use core::{
+    cell::Cell,
+    time::Duration,
+};
+
+use iota_streams_core::{
+    prelude::{
+        Vec,
+        string::ToString,
+    },
+    sponge::prp::{
+        Inner,
+        PRP,
+    },
+    try_or,
+    Result,
+    Errors::*
+};
+use iota_streams_core_keccak::sponge::prp::keccak::KeccakF1600;
+
+use super::*;
+
+fn put<F: PRP, LS: LinkStore<F, u64, Info = ()>>(store: &mut LS, links: &[u64]) -> Result<()> {
+    for link in links {
+        store.insert(link, Inner::<F>::default(), ())?;
+    }
+    Ok(())
+}
+
+// Links iterated by `store`, iteration doesn't count as use.
+fn ensure_links<F: PRP, LS: LinkStore<F, u64>>(store: &LS, expected: &[u64]) -> Result<()> {
+    let mut found: Vec<u64> = store.iter().into_iter().map(|(link, _)| *link).collect();
+    found.sort_unstable();
+    try_or!(
+        found == expected,
+        MessageLinkNotFound(format!("{:?} instead of {:?}", found, expected))
+    )
+}
+
+fn lru_eviction_order<F: PRP>() -> Result<()> {
+    let mut store = LruLinkStore::<F, u64, ()>::new(3);
+    put(&mut store, &[1, 2, 3])?;
+    ensure_links(&store, &[1, 2, 3])?;
+
+    // Lookup counts as use, the least recently used link 2 is evicted.
+    store.lookup(&1)?;
+    put(&mut store, &[4])?;
+    ensure_links(&store, &[1, 3, 4])?;
+
+    // Update counts as use too.
+    put(&mut store, &[3])?;
+    put(&mut store, &[5])?;
+    ensure_links(&store, &[3, 4, 5])?;
+
+    // Erased link frees its place.
+    store.erase(&4);
+    put(&mut store, &[6])?;
+    ensure_links(&store, &[3, 5, 6])?;
+    try_or!(store.lookup(&4).is_err(), MessageLinkNotFound(4.to_string()))?;
+
+    store.set_capacity(1);
+    ensure_links(&store, &[6])?;
+    Ok(())
+}
+
+#[test]
+fn test_lru_eviction_order() {
+    assert!(dbg!(lru_eviction_order::<KeccakF1600>()).is_ok());
+}
+
+fn lru_pin<F: PRP>() -> Result<()> {
+    let mut store = LruLinkStore::<F, u64, ()>::new(2);
+    put(&mut store, &[1, 2])?;
+    store.pin(vec![1]);
+
+    // Link 1 is the least recently used, but pinned.
+    put(&mut store, &[3])?;
+    ensure_links(&store, &[1, 3])?;
+    put(&mut store, &[4])?;
+    ensure_links(&store, &[1, 4])?;
+
+    // Store exceeds capacity rather than evicting pinned links.
+    store.pin(vec![1, 4]);
+    store.set_capacity(1);
+    ensure_links(&store, &[1, 4])?;
+
+    // Links are evicted once unpinned.
+    store.pin(vec![4]);
+    ensure_links(&store, &[4])?;
+    Ok(())
+}
+
+#[test]
+fn test_lru_pin() {
+    assert!(dbg!(lru_pin::<KeccakF1600>()).is_ok());
+}
+
+fn aged_max_messages<F: PRP>() -> Result<()> {
+    let mut store = AgedLinkStore::<F, u64, ()>::new(Some(2), None);
+    store.pin(vec![1]);
+    put(&mut store, &[1, 2, 3])?;
+    ensure_links(&store, &[1, 2, 3])?;
+
+    // Link 2 was put 2 links ago, pinned link 1 never expires.
+    put(&mut store, &[4])?;
+    ensure_links(&store, &[1, 3, 4])?;
+    try_or!(store.lookup(&2).is_err(), MessageLinkNotFound(2.to_string()))?;
+
+    store.set_max_messages(Some(1));
+    ensure_links(&store, &[1, 4])?;
+    Ok(())
+}
+
+#[test]
+fn test_aged_max_messages() {
+    assert!(dbg!(aged_max_messages::<KeccakF1600>()).is_ok());
+}
+
+std::thread_local! {
+    static NOW: Cell<u64> = const { Cell::new(0) };
+}
+
+fn test_clock() -> Duration {
+    NOW.with(|now| Duration::from_secs(now.get()))
+}
+
+fn set_now(secs: u64) {
+    NOW.with(|now| now.set(secs));
+}
+
+fn aged_max_age<F: PRP>() -> Result<()> {
+    let mut store = AgedLinkStore::<F, u64, ()>::new(None, Some(Duration::from_secs(10))).with_clock(test_clock);
+    store.pin(vec![1]);
+    set_now(100);
+    put(&mut store, &[1, 2])?;
+    set_now(105);
+    put(&mut store, &[3])?;
+
+    // Exactly `max_age` old links are still kept.
+    set_now(110);
+    ensure_links(&store, &[1, 2, 3])?;
+
+    // Expired links are not found before they are evicted.
+    set_now(111);
+    ensure_links(&store, &[1, 3])?;
+    try_or!(store.lookup(&2).is_err(), MessageLinkNotFound(2.to_string()))?;
+    try_or!(store.len() == 3, ValueMismatch(3, store.len()))?;
+    put(&mut store, &[4])?;
+    try_or!(store.len() == 3, ValueMismatch(3, store.len()))?;
+
+    set_now(200);
+    ensure_links(&store, &[1])?;
+    store.lookup(&1)?;
+
+    store.set_max_age(None);
+    ensure_links(&store, &[1, 3, 4])?;
+    Ok(())
+}
+
+#[test]
+fn test_aged_max_age() {
+    assert!(dbg!(aged_max_age::<KeccakF1600>()).is_ok());
+}
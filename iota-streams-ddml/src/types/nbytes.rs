@@ -13,12 +13,6 @@ pub use iota_streams_core::prelude::{
         GenericArray,
     },
     hex,
-    typenum::{
-        marker_traits::Unsigned,
-        U16,
-        U32,
-        U64,
-    },
 };
 
 /// Fixed-size array of bytes, the size is known at compile time and is not encoded in trinary representation.
@@ -77,9 +71,9 @@ impl<'a, N: ArrayLength<u8>> From<&'a mut GenericArray<u8, N>> for &'a mut NByte
     }
 }
 
-impl<N: ArrayLength<u8>> Into<GenericArray<u8, N>> for NBytes<N> {
-    fn into(self) -> GenericArray<u8, N> {
-        self.0
+impl<N: ArrayLength<u8>> From<NBytes<N>> for GenericArray<u8, N> {
+    fn from(val: NBytes<N>) -> Self {
+        val.0
     }
 }
 
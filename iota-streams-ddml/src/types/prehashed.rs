@@ -28,7 +28,7 @@ impl Digest for Prehashed {
     }
 
     fn finalize_reset(&mut self) -> GenericArray<u8, Self::OutputSize> {
-        self.0.clone()
+        self.0
     }
 
     fn reset(&mut self) {